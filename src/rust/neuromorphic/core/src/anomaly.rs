@@ -0,0 +1,241 @@
+//! Streaming novelty detection on spike patterns. Spikes are counted in
+//! fixed windows; each window's per-neuron and population counts are
+//! compared with running estimates of their typical mean and variance, and
+//! the RMS z-score is the window's novelty score. A window is anomalous
+//! when its score exceeds an adaptive threshold tracking the mean and
+//! spread of recent scores.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    /// Steps per scored window.
+    pub window_steps: usize,
+    /// Windows that train the pattern model before scoring starts; as many
+    /// again calibrate the threshold before anomalies are reported.
+    pub warmup_windows: usize,
+    /// Smoothing factor of the running statistics once warmed up; the
+    /// model forgets with a time constant of about `1 / adaptation` windows.
+    pub adaptation: f32,
+    /// Threshold in standard deviations above the mean recent score.
+    pub threshold_sigmas: f32,
+    /// Floor on the standard deviation of a count, in spikes, so rarely
+    /// firing neurons do not dominate the score.
+    pub min_std: f32,
+    /// Whether anomalous windows update the model. Off, a lasting change
+    /// keeps being reported until the model is reset.
+    pub learn_anomalies: bool,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window_steps: 20,
+            warmup_windows: 20,
+            adaptation: 0.02,
+            threshold_sigmas: 4.0,
+            min_std: 1.0,
+            learn_anomalies: false,
+        }
+    }
+}
+
+impl AnomalyConfig {
+    pub fn is_valid(&self) -> bool {
+        self.window_steps > 0
+            && self.warmup_windows > 0
+            && self.adaptation > 0.0
+            && self.adaptation <= 1.0
+            && self.threshold_sigmas.is_finite()
+            && self.threshold_sigmas >= 0.0
+            && self.min_std.is_finite()
+            && self.min_std > 0.0
+    }
+}
+
+/// Score of one completed window.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct NoveltyScore {
+    /// Simulation time of the window's last step.
+    pub time_ms: u64,
+    pub score: f32,
+    /// `None` while the threshold is calibrating.
+    pub threshold: Option<f32>,
+    pub anomaly: bool,
+}
+
+/// Exponentially weighted mean and variance, with the weight of a new
+/// sample starting at 1 and falling to `adaptation` (a cumulative average
+/// until then).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct RunningStats {
+    mean: Vec<f32>,
+    variance: Vec<f32>,
+    samples: usize,
+}
+
+impl RunningStats {
+    fn new(dim: usize) -> Self {
+        Self { mean: vec![0.0; dim], variance: vec![0.0; dim], samples: 0 }
+    }
+
+    fn update(&mut self, values: &[f32], adaptation: f32) {
+        let weight = (1.0 / (self.samples + 1) as f32).max(adaptation);
+        for ((mean, variance), &value) in self.mean.iter_mut().zip(&mut self.variance).zip(values) {
+            let delta = value - *mean;
+            *mean += weight * delta;
+            *variance = (1.0 - weight) * (*variance + weight * delta * delta);
+        }
+        self.samples += 1;
+    }
+}
+
+/// Window accumulator, pattern model and score threshold.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NoveltyDetector {
+    pub config: AnomalyConfig,
+    /// Per-neuron spike counts of the open window, then the population count.
+    counts: Vec<f32>,
+    elapsed: usize,
+    pattern: RunningStats,
+    scores: RunningStats,
+}
+
+impl NoveltyDetector {
+    pub fn new(config: AnomalyConfig, neurons: usize) -> Self {
+        Self {
+            config,
+            counts: vec![0.0; neurons + 1],
+            elapsed: 0,
+            pattern: RunningStats::new(neurons + 1),
+            scores: RunningStats::new(1),
+        }
+    }
+
+    /// Forgets the learned model and the open window.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config, self.counts.len() - 1);
+    }
+
+    /// True once anomalies can be reported.
+    pub fn is_calibrated(&self) -> bool {
+        self.scores.samples >= self.config.warmup_windows
+    }
+
+    /// Adds a step's spikes at simulation time `time_ms`; returns the score
+    /// when this step completes a window past the model warm-up.
+    pub fn observe(&mut self, spikes: &[bool], time_ms: u64) -> Option<NoveltyScore> {
+        let neurons = self.counts.len() - 1;
+        let mut population = 0;
+        for (count, &spike) in self.counts[..neurons].iter_mut().zip(spikes) {
+            if spike {
+                *count += 1.0;
+                population += 1;
+            }
+        }
+        self.counts[neurons] += population as f32;
+        self.elapsed += 1;
+        if self.elapsed < self.config.window_steps {
+            return None;
+        }
+
+        let score = self.finish_window(time_ms);
+        self.counts.fill(0.0);
+        self.elapsed = 0;
+        score
+    }
+
+    fn finish_window(&mut self, time_ms: u64) -> Option<NoveltyScore> {
+        let adaptation = self.config.adaptation;
+        if self.pattern.samples < self.config.warmup_windows {
+            self.pattern.update(&self.counts, adaptation);
+            return None;
+        }
+
+        let min_variance = self.config.min_std * self.config.min_std;
+        let squared_z: f32 = self.counts.iter()
+            .zip(self.pattern.mean.iter().zip(&self.pattern.variance))
+            .map(|(&count, (&mean, &variance))| (count - mean) * (count - mean) / variance.max(min_variance))
+            .sum();
+        let score = math::sqrt(squared_z / self.counts.len() as f32);
+
+        let threshold = self.is_calibrated().then(|| {
+            self.scores.mean[0] + self.config.threshold_sigmas * math::sqrt(self.scores.variance[0])
+        });
+        let anomaly = threshold.is_some_and(|threshold| score > threshold);
+        if !anomaly || self.config.learn_anomalies {
+            self.pattern.update(&self.counts, adaptation);
+            self.scores.update(&[score], adaptation);
+        }
+        Some(NoveltyScore { time_ms, score, threshold, anomaly })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::XorShift64;
+
+    const NEURONS: usize = 8;
+
+    fn config() -> AnomalyConfig {
+        AnomalyConfig { window_steps: 10, warmup_windows: 5, ..AnomalyConfig::default() }
+    }
+
+    /// Feeds `windows` windows in which each neuron fires with probability
+    /// `p` per step and returns the scores.
+    fn feed(detector: &mut NoveltyDetector, rng: &mut XorShift64, windows: usize, p: f32) -> Vec<NoveltyScore> {
+        (0..windows * detector.config.window_steps)
+            .filter_map(|t| {
+                let spikes: Vec<bool> = (0..NEURONS).map(|_| rng.uniform() < p).collect();
+                detector.observe(&spikes, t as u64)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scoring_starts_after_warmup_and_anomalies_after_calibration() {
+        let mut detector = NoveltyDetector::new(config(), NEURONS);
+        let mut rng = XorShift64::new(3);
+        assert!(feed(&mut detector, &mut rng, 5, 0.1).is_empty());
+        let scores = feed(&mut detector, &mut rng, 10, 0.1);
+        assert_eq!(scores.len(), 10);
+        assert!(scores[..5].iter().all(|s| s.threshold.is_none() && !s.anomaly));
+        assert!(scores[5..].iter().all(|s| s.threshold.is_some()));
+        assert!(detector.is_calibrated());
+        assert_eq!(scores[0].time_ms, 9);
+    }
+
+    #[test]
+    fn bursts_are_anomalous_until_the_model_is_reset() {
+        let mut detector = NoveltyDetector::new(config(), NEURONS);
+        let mut rng = XorShift64::new(3);
+        let baseline = feed(&mut detector, &mut rng, 40, 0.1);
+        assert!(baseline.iter().all(|s| !s.anomaly), "{:?}", baseline);
+
+        // Without learning anomalies, a lasting change keeps being reported
+        let burst = feed(&mut detector, &mut rng, 3, 0.9);
+        assert!(burst.iter().all(|s| s.anomaly));
+        assert!(burst.iter().all(|s| s.score > s.threshold.unwrap()));
+
+        detector.reset();
+        assert!(!detector.is_calibrated());
+        assert!(feed(&mut detector, &mut rng, 5, 0.9).is_empty());
+    }
+
+    #[test]
+    fn learned_anomalies_become_the_new_normal() {
+        let mut detector = NoveltyDetector::new(AnomalyConfig { learn_anomalies: true, adaptation: 0.2, ..config() }, NEURONS);
+        let mut rng = XorShift64::new(3);
+        feed(&mut detector, &mut rng, 40, 0.1);
+        let shifted = feed(&mut detector, &mut rng, 30, 0.9);
+        assert!(shifted[0].anomaly);
+        assert!(shifted[1..].iter().all(|s| !s.anomaly));
+        assert!(!AnomalyConfig { adaptation: 0.0, ..config() }.is_valid());
+    }
+}
@@ -0,0 +1,478 @@
+//! Unsupervised competitive learning after Diehl & Cook (2015): a Poisson
+//! input layer projects through plastic weights onto an excitatory LIF layer
+//! whose neurons compete through lateral inhibition (soft or hard
+//! winner-take-all) and homeostatic adaptive thresholds. Input weights learn
+//! with trace-based STDP and are normalised per neuron after each sample.
+//! `LabelAssignment` turns the learned responses into a classifier.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::population::NeuronPopulation;
+use crate::rng::XorShift64;
+use crate::LifParameters;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WtaMode {
+    /// Every excitatory spike injects `-inhibition` into all other
+    /// excitatory neurons on the next step.
+    Soft { inhibition: f32 },
+    /// Only the most strongly driven of the neurons crossing threshold in a
+    /// step fires; all others are reset.
+    Hard,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct AdaptiveThreshold {
+    /// Threshold increase per spike (mV).
+    pub theta_plus: f32,
+    /// Decay time constant of the increase; very long so the homeostasis
+    /// acts over the whole training run.
+    pub tau_theta_ms: f32,
+}
+
+impl Default for AdaptiveThreshold {
+    fn default() -> Self {
+        Self { theta_plus: 0.05, tau_theta_ms: 1.0e7 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct StdpParameters {
+    /// Depression on presynaptic spikes, scaled by the postsynaptic trace.
+    pub eta_pre: f32,
+    /// Potentiation on postsynaptic spikes.
+    pub eta_post: f32,
+    pub tau_pre_ms: f32,
+    pub tau_post_ms: f32,
+    /// Presynaptic trace target: inputs whose trace is below it at a
+    /// postsynaptic spike are depressed.
+    pub x_target: f32,
+    pub w_max: f32,
+    /// Soft-bound exponent of the `(w_max - w)^mu` weight dependence.
+    pub mu: f32,
+}
+
+impl Default for StdpParameters {
+    fn default() -> Self {
+        Self {
+            eta_pre: 0.0001,
+            eta_post: 0.01,
+            tau_pre_ms: 20.0,
+            tau_post_ms: 20.0,
+            x_target: 0.4,
+            w_max: 1.0,
+            mu: 0.2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct CompetitiveConfig {
+    pub input_size: usize,
+    pub excitatory_size: usize,
+    pub neuron: LifParameters,
+    pub wta: WtaMode,
+    pub adaptive_threshold: AdaptiveThreshold,
+    pub stdp: StdpParameters,
+    /// Current (nA) injected per input spike and unit weight.
+    pub input_gain: f32,
+    /// Poisson rate of an input at intensity 1.
+    pub max_rate_hz: f32,
+    /// Rate added to every active input on each retry of a sample that
+    /// produced fewer than `min_spikes` excitatory spikes.
+    pub retry_boost_hz: f32,
+    pub min_spikes: u32,
+    pub max_retries: u32,
+    pub presentation_steps: usize,
+    /// Target sum of the input weights onto each excitatory neuron; `None`
+    /// disables normalisation.
+    pub normalization: Option<f32>,
+    pub seed: u64,
+}
+
+impl Default for CompetitiveConfig {
+    fn default() -> Self {
+        Self {
+            input_size: 784,
+            excitatory_size: 100,
+            neuron: LifParameters {
+                tau_m_ms: 100.0,
+                refractory_ms: 5.0,
+                ..LifParameters::default()
+            },
+            wta: WtaMode::Soft { inhibition: 10.0 },
+            adaptive_threshold: AdaptiveThreshold::default(),
+            stdp: StdpParameters::default(),
+            input_gain: 5.0,
+            max_rate_hz: 63.75,
+            retry_boost_hz: 32.0,
+            min_spikes: 5,
+            max_retries: 3,
+            presentation_steps: 350,
+            normalization: Some(78.0),
+            seed: 1,
+        }
+    }
+}
+
+impl CompetitiveConfig {
+    pub fn is_valid(&self) -> bool {
+        self.input_size > 0
+            && self.excitatory_size > 0
+            && self.neuron.is_valid()
+            && self.stdp.tau_pre_ms > 0.0
+            && self.stdp.tau_post_ms > 0.0
+            && self.stdp.w_max > 0.0
+            && self.adaptive_threshold.tau_theta_ms > 0.0
+            && self.max_rate_hz >= 0.0
+            && self.presentation_steps > 0
+            && self.normalization.is_none_or(|total| total > 0.0)
+    }
+}
+
+/// Input layer, plastic input weights and the competing excitatory layer.
+pub struct CompetitiveNetwork {
+    pub config: CompetitiveConfig,
+    neurons: NeuronPopulation,
+    /// Indexed `[input][excitatory]`.
+    weights: Vec<Vec<f32>>,
+    theta: Vec<f32>,
+    pre_trace: Vec<f32>,
+    post_trace: Vec<f32>,
+    /// Excitatory spikes of the previous step, for soft inhibition.
+    last_spike_count: usize,
+    last_spiked: Vec<bool>,
+    rng: XorShift64,
+    time: u64,
+}
+
+impl CompetitiveNetwork {
+    pub fn new(config: CompetitiveConfig) -> Self {
+        let mut neurons = NeuronPopulation::new();
+        for _ in 0..config.excitatory_size {
+            neurons.push(config.neuron);
+        }
+
+        let mut rng = XorShift64::new(config.seed);
+        let weights = (0..config.input_size)
+            .map(|_| (0..config.excitatory_size).map(|_| 0.3 * rng.uniform() * config.stdp.w_max).collect())
+            .collect();
+
+        let mut network = Self {
+            config,
+            neurons,
+            weights,
+            theta: vec![0.0; config.excitatory_size],
+            pre_trace: vec![0.0; config.input_size],
+            post_trace: vec![0.0; config.excitatory_size],
+            last_spike_count: 0,
+            last_spiked: vec![false; config.excitatory_size],
+            rng,
+            time: 0,
+        };
+        network.normalize();
+        network
+    }
+
+    pub fn weights(&self) -> &[Vec<f32>] {
+        &self.weights
+    }
+
+    /// Adaptive threshold increase of every excitatory neuron.
+    pub fn theta(&self) -> &[f32] {
+        &self.theta
+    }
+
+    /// Presents one sample with intensities in [0, 1] as Poisson rates for
+    /// `presentation_steps`, retrying at higher rates while the layer stays
+    /// nearly silent, then resets the dynamic state. Returns the spike
+    /// count of every excitatory neuron; weights and thresholds adapt only
+    /// when `learn` is set.
+    pub fn present(&mut self, input: &[f32], learn: bool) -> Vec<u32> {
+        assert_eq!(input.len(), self.config.input_size);
+
+        let mut counts = vec![0; self.config.excitatory_size];
+        for attempt in 0..=self.config.max_retries {
+            let boost = attempt as f32 * self.config.retry_boost_hz;
+            let rates: Vec<f32> = input.iter()
+                .map(|&x| x.clamp(0.0, 1.0) * self.config.max_rate_hz + if x > 0.0 { boost } else { 0.0 })
+                .collect();
+
+            counts.fill(0);
+            for _ in 0..self.config.presentation_steps {
+                self.step(&rates, learn, &mut counts);
+            }
+            self.rest();
+
+            if counts.iter().sum::<u32>() >= self.config.min_spikes {
+                break;
+            }
+        }
+
+        if learn {
+            self.normalize();
+        }
+        counts
+    }
+
+    fn step(&mut self, rates: &[f32], learn: bool, counts: &mut [u32]) {
+        let dt_ms = self.config.neuron.dt_ms;
+        let stdp = self.config.stdp;
+        let pre_decay = math::exp(-dt_ms / stdp.tau_pre_ms);
+        let post_decay = math::exp(-dt_ms / stdp.tau_post_ms);
+        let theta_decay = math::exp(-dt_ms / self.config.adaptive_threshold.tau_theta_ms);
+
+        // Input layer
+        let mut input_spikes = Vec::new();
+        for (i, &rate) in rates.iter().enumerate() {
+            if self.rng.uniform() < rate * dt_ms / 1000.0 {
+                input_spikes.push(i);
+            }
+        }
+
+        // Excitatory drive and lateral inhibition
+        let mut currents = vec![0.0; self.config.excitatory_size];
+        for &i in &input_spikes {
+            for (current, &w) in currents.iter_mut().zip(&self.weights[i]) {
+                *current += self.config.input_gain * w;
+            }
+        }
+        if let WtaMode::Soft { inhibition } = self.config.wta {
+            for (j, current) in currents.iter_mut().enumerate() {
+                let others = self.last_spike_count - self.last_spiked[j] as usize;
+                *current -= inhibition * others as f32;
+            }
+        }
+
+        for (j, theta) in self.theta.iter_mut().enumerate() {
+            *theta *= theta_decay;
+            self.neurons.set_threshold(j, self.config.neuron.v_threshold + *theta);
+        }
+
+        let mut spikes = vec![false; self.config.excitatory_size];
+        self.time += 1;
+        self.neurons.step(&currents, self.time, &mut spikes, 1);
+
+        if matches!(self.config.wta, WtaMode::Hard) && spikes.iter().any(|&s| s) {
+            let winner = (0..spikes.len())
+                .filter(|&j| spikes[j])
+                .fold(None, |best: Option<usize>, j| match best {
+                    Some(b) if currents[b] >= currents[j] => Some(b),
+                    _ => Some(j),
+                })
+                .unwrap_or(0);
+            for (j, spike) in spikes.iter_mut().enumerate() {
+                if j != winner {
+                    *spike = false;
+                    self.neurons.set_state(j, self.config.neuron.v_reset, 0);
+                }
+            }
+        }
+
+        // Traces and STDP
+        for trace in &mut self.pre_trace {
+            *trace *= pre_decay;
+        }
+        for trace in &mut self.post_trace {
+            *trace *= post_decay;
+        }
+        for &i in &input_spikes {
+            self.pre_trace[i] = 1.0;
+            if learn {
+                for (w, &post) in self.weights[i].iter_mut().zip(&self.post_trace) {
+                    *w = (*w - stdp.eta_pre * post).max(0.0);
+                }
+            }
+        }
+
+        let mut spike_count = 0;
+        for (j, &spiked) in spikes.iter().enumerate() {
+            if !spiked {
+                continue;
+            }
+            spike_count += 1;
+            counts[j] += 1;
+            self.post_trace[j] = 1.0;
+            if learn {
+                self.theta[j] += self.config.adaptive_threshold.theta_plus;
+                for (row, &pre) in self.weights.iter_mut().zip(&self.pre_trace) {
+                    let w = &mut row[j];
+                    let bound = math::powf((stdp.w_max - *w).max(0.0), stdp.mu);
+                    *w = (*w + stdp.eta_post * (pre - stdp.x_target) * bound).clamp(0.0, stdp.w_max);
+                }
+            }
+        }
+        self.last_spike_count = spike_count;
+        self.last_spiked = spikes;
+    }
+
+    /// Returns the network to rest between samples, standing in for the
+    /// resting period of the original protocol. Thresholds keep their
+    /// adaptation.
+    fn rest(&mut self) {
+        for j in 0..self.config.excitatory_size {
+            self.neurons.set_state(j, self.config.neuron.v_rest, 0);
+        }
+        self.pre_trace.fill(0.0);
+        self.post_trace.fill(0.0);
+        self.last_spike_count = 0;
+        self.last_spiked.fill(false);
+    }
+
+    fn normalize(&mut self) {
+        let Some(total) = self.config.normalization else {
+            return;
+        };
+        for j in 0..self.config.excitatory_size {
+            let sum: f32 = self.weights.iter().map(|row| row[j]).sum();
+            if sum > 0.0 {
+                let scale = total / sum;
+                for row in &mut self.weights {
+                    row[j] = (row[j] * scale).min(self.config.stdp.w_max);
+                }
+            }
+        }
+    }
+}
+
+/// Assigns each neuron the class it responded to most strongly on average,
+/// then classifies by the mean response of the neurons assigned to each
+/// class.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LabelAssignment {
+    class_count: usize,
+    /// `[class][neuron]` summed spike counts.
+    response_sums: Vec<Vec<f64>>,
+    samples: Vec<u32>,
+    labels: Vec<Option<usize>>,
+}
+
+impl LabelAssignment {
+    pub fn new(class_count: usize, neuron_count: usize) -> Self {
+        Self {
+            class_count,
+            response_sums: vec![vec![0.0; neuron_count]; class_count],
+            samples: vec![0; class_count],
+            labels: vec![None; neuron_count],
+        }
+    }
+
+    pub fn class_count(&self) -> usize {
+        self.class_count
+    }
+
+    /// Accumulates the response to a sample of class `label`; returns false
+    /// for an out-of-range label.
+    pub fn record(&mut self, counts: &[u32], label: usize) -> bool {
+        if label >= self.class_count {
+            return false;
+        }
+        for (sum, &count) in self.response_sums[label].iter_mut().zip(counts) {
+            *sum += count as f64;
+        }
+        self.samples[label] += 1;
+        true
+    }
+
+    /// Labels every neuron with the class of highest mean response; neurons
+    /// that never fired stay unassigned.
+    pub fn assign(&mut self) -> &[Option<usize>] {
+        for (neuron, label) in self.labels.iter_mut().enumerate() {
+            let mut best: Option<(usize, f64)> = None;
+            for class in 0..self.class_count {
+                if self.samples[class] == 0 {
+                    continue;
+                }
+                let mean = self.response_sums[class][neuron] / self.samples[class] as f64;
+                if mean > 0.0 && best.is_none_or(|(_, m)| mean > m) {
+                    best = Some((class, mean));
+                }
+            }
+            *label = best.map(|(class, _)| class);
+        }
+        &self.labels
+    }
+
+    pub fn labels(&self) -> &[Option<usize>] {
+        &self.labels
+    }
+
+    /// Class whose assigned neurons have the highest mean spike count.
+    pub fn classify(&self, counts: &[u32]) -> Option<usize> {
+        let mut totals = vec![(0.0f64, 0u32); self.class_count];
+        for (&label, &count) in self.labels.iter().zip(counts) {
+            if let Some(class) = label {
+                totals[class].0 += count as f64;
+                totals[class].1 += 1;
+            }
+        }
+        totals.iter()
+            .enumerate()
+            .filter(|(_, &(_, neurons))| neurons > 0)
+            .map(|(class, &(sum, neurons))| (class, sum / neurons as f64))
+            .filter(|&(_, mean)| mean > 0.0)
+            .fold(None, |best: Option<(usize, f64)>, (class, mean)| match best {
+                Some((_, m)) if m >= mean => best,
+                _ => Some((class, mean)),
+            })
+            .map(|(class, _)| class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_config() -> CompetitiveConfig {
+        CompetitiveConfig {
+            input_size: 16,
+            excitatory_size: 4,
+            presentation_steps: 50,
+            normalization: Some(4.0),
+            ..CompetitiveConfig::default()
+        }
+    }
+
+    #[test]
+    fn out_of_range_labels_are_rejected_without_recording() {
+        let mut labels = LabelAssignment::new(2, 3);
+        assert_eq!(labels.class_count(), 2);
+        assert!(!labels.record(&[1, 2, 3], 2));
+        assert_eq!(labels.assign(), [None, None, None]);
+
+        assert!(labels.record(&[0, 4, 1], 0));
+        assert!(labels.record(&[3, 0, 0], 1));
+        assert_eq!(labels.assign(), [Some(1), Some(0), Some(0)]);
+        assert_eq!(labels.classify(&[5, 0, 0]), Some(1));
+        assert_eq!(labels.classify(&[0, 0, 0]), None);
+    }
+
+    #[test]
+    fn presentation_without_learning_leaves_weights_and_thresholds_alone() {
+        let mut network = CompetitiveNetwork::new(small_config());
+        let weights = network.weights().to_vec();
+        let input: Vec<f32> = (0..16).map(|i| (i % 2) as f32).collect();
+        network.present(&input, false);
+        assert_eq!(network.weights(), weights.as_slice());
+        assert!(network.theta().iter().all(|&theta| theta == 0.0));
+
+        network.present(&input, true);
+        assert_ne!(network.weights(), weights.as_slice());
+    }
+
+    #[test]
+    fn normalization_fixes_the_fan_in_of_every_neuron() {
+        let network = CompetitiveNetwork::new(small_config());
+        for post in 0..4 {
+            let total: f32 = network.weights().iter().map(|row| row[post]).sum();
+            assert!((total - 4.0).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Criticality measures on population spike counts: branching ratio
+//! (naive and multistep-regression estimators), neuronal avalanches with
+//! power-law exponents, and a multiplicative controller that steers the
+//! recurrent gain towards a target branching ratio.
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+
+/// Mean of `A[t+1] / A[t]` over steps with activity (Beggs & Plenz).
+/// Biased under subsampling and external drive; `None` without activity.
+pub fn naive_branching_ratio(counts: &[u32]) -> Option<f32> {
+    let ratios: Vec<f32> = counts.windows(2)
+        .filter(|pair| pair[0] > 0)
+        .map(|pair| pair[1] as f32 / pair[0] as f32)
+        .collect();
+    if ratios.is_empty() {
+        return None;
+    }
+    Some(ratios.iter().sum::<f32>() / ratios.len() as f32)
+}
+
+/// Multistep-regression estimator (Wilting & Priesemann 2018): the slopes
+/// `r_k` of `A[t+k]` against `A[t]` for `k = 1..=max_lag` decay as `b m^k`;
+/// `m` is fitted by log-linear least squares and is robust to subsampling
+/// and drive. `None` if fewer than two slopes are positive.
+pub fn mr_branching_ratio(counts: &[u32], max_lag: usize) -> Option<f32> {
+    let mut points: Vec<(f32, f32)> = Vec::new();
+    for k in 1..=max_lag.min(counts.len().saturating_sub(2)) {
+        let x = &counts[..counts.len() - k];
+        let y = &counts[k..];
+        let n = x.len() as f64;
+        let mean_x = x.iter().map(|&v| v as f64).sum::<f64>() / n;
+        let mean_y = y.iter().map(|&v| v as f64).sum::<f64>() / n;
+        let (mut covariance, mut variance) = (0.0, 0.0);
+        for (&a, &b) in x.iter().zip(y) {
+            covariance += (a as f64 - mean_x) * (b as f64 - mean_y);
+            variance += (a as f64 - mean_x) * (a as f64 - mean_x);
+        }
+        if variance > 0.0 && covariance > 0.0 {
+            points.push((k as f32, math::ln((covariance / variance) as f32)));
+        }
+    }
+    if points.len() < 2 {
+        return None;
+    }
+
+    let n = points.len() as f32;
+    let mean_k = points.iter().map(|p| p.0).sum::<f32>() / n;
+    let mean_ln = points.iter().map(|p| p.1).sum::<f32>() / n;
+    let numerator: f32 = points.iter().map(|&(k, r)| (k - mean_k) * (r - mean_ln)).sum();
+    let denominator: f32 = points.iter().map(|&(k, _)| (k - mean_k) * (k - mean_k)).sum();
+    Some(math::exp(numerator / denominator))
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Avalanche {
+    /// Total spikes.
+    pub size: u32,
+    /// Number of bins.
+    pub duration: u32,
+}
+
+/// Splits the activity, summed into bins of `bin_steps`, into avalanches:
+/// runs of non-empty bins bounded by empty ones. Runs touching the start
+/// or end of the recording are incomplete and dropped.
+pub fn avalanches(counts: &[u32], bin_steps: usize) -> Vec<Avalanche> {
+    let bins: Vec<u32> = counts.chunks(bin_steps.max(1)).map(|bin| bin.iter().sum()).collect();
+    let mut result = Vec::new();
+    let mut current: Option<Avalanche> = None;
+    let mut seen_silence = false;
+    for &bin in &bins {
+        if bin > 0 {
+            let avalanche = current.get_or_insert(Avalanche { size: 0, duration: 0 });
+            avalanche.size += bin;
+            avalanche.duration += 1;
+        } else {
+            if let Some(avalanche) = current.take() {
+                if seen_silence {
+                    result.push(avalanche);
+                }
+            }
+            seen_silence = true;
+        }
+    }
+    result
+}
+
+/// Maximum-likelihood exponent of a discrete power law `P(x) ∝ x^-α` for
+/// `x >= x_min` (continuous approximation of Clauset et al. 2009).
+pub fn power_law_exponent(values: impl Iterator<Item = u32>, x_min: u32) -> Option<f32> {
+    let x_min = x_min.max(1) as f32;
+    let (count, log_sum) = values
+        .filter(|&v| v as f32 >= x_min)
+        .fold((0usize, 0.0f32), |(n, sum), v| (n + 1, sum + math::ln(v as f32 / (x_min - 0.5))));
+    if count < 2 || log_sum <= 0.0 {
+        return None;
+    }
+    Some(1.0 + count as f32 / log_sum)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CriticalityReport {
+    pub steps: usize,
+    pub total_spikes: u64,
+    pub branching_ratio_naive: Option<f32>,
+    pub branching_ratio_mr: Option<f32>,
+    pub avalanche_count: usize,
+    pub mean_avalanche_size: f32,
+    pub mean_avalanche_duration: f32,
+    /// About 1.5 at criticality in the mean-field limit.
+    pub size_exponent: Option<f32>,
+    /// About 2 at criticality in the mean-field limit.
+    pub duration_exponent: Option<f32>,
+}
+
+impl CriticalityReport {
+    pub fn analyze(counts: &[u32], bin_steps: usize, max_lag: usize) -> Self {
+        let avalanches = avalanches(counts, bin_steps);
+        let n = avalanches.len().max(1) as f32;
+        Self {
+            steps: counts.len(),
+            total_spikes: counts.iter().map(|&c| c as u64).sum(),
+            branching_ratio_naive: naive_branching_ratio(counts),
+            branching_ratio_mr: mr_branching_ratio(counts, max_lag),
+            avalanche_count: avalanches.len(),
+            mean_avalanche_size: avalanches.iter().map(|a| a.size as f32).sum::<f32>() / n,
+            mean_avalanche_duration: avalanches.iter().map(|a| a.duration as f32).sum::<f32>() / n,
+            size_exponent: power_law_exponent(avalanches.iter().map(|a| a.size), 1),
+            duration_exponent: power_law_exponent(avalanches.iter().map(|a| a.duration), 1),
+        }
+    }
+
+    /// MR estimate, falling back to the naive one.
+    pub fn branching_ratio(&self) -> Option<f32> {
+        self.branching_ratio_mr.or(self.branching_ratio_naive)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct CriticalityTuning {
+    pub target_branching_ratio: f32,
+    /// Stop once the estimate is within this distance of the target.
+    pub tolerance: f32,
+    pub max_iterations: usize,
+    /// Steps simulated per estimate.
+    pub steps_per_iteration: usize,
+    /// Weak Poisson drive keeping the network from falling silent: each
+    /// neuron receives `drive_current` with `drive_probability` per step.
+    pub drive_current: f32,
+    pub drive_probability: f32,
+    pub max_lag: usize,
+    /// Exponent of the multiplicative update `gain *= (target / m)^rate`.
+    pub rate: f32,
+}
+
+impl Default for CriticalityTuning {
+    fn default() -> Self {
+        Self {
+            target_branching_ratio: 1.0,
+            tolerance: 0.02,
+            max_iterations: 30,
+            steps_per_iteration: 500,
+            drive_current: 1.0,
+            drive_probability: 0.01,
+            max_lag: 10,
+            rate: 0.5,
+        }
+    }
+}
+
+/// Floor applied to the gain before each update, so a controller started
+/// at zero gain can still grow it.
+pub const MIN_GAIN: f32 = 1e-3;
+
+impl CriticalityTuning {
+    /// Next recurrent gain given the current gain and measured branching
+    /// ratio. A silent network (no estimate) doubles the gain; each step is
+    /// limited to a factor of two either way and starts from at least
+    /// `MIN_GAIN`.
+    pub fn next_gain(&self, gain: f32, measured: Option<f32>) -> f32 {
+        let factor = match measured {
+            Some(m) if m > 0.0 => math::exp(self.rate * math::ln(self.target_branching_ratio / m)),
+            _ => 2.0,
+        };
+        gain.max(MIN_GAIN) * factor.clamp(0.5, 2.0)
+    }
+
+    pub fn converged(&self, measured: Option<f32>) -> bool {
+        measured.is_some_and(|m| (m - self.target_branching_ratio).abs() <= self.tolerance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_gain_grows_from_the_floor() {
+        let tuning = CriticalityTuning::default();
+        assert_eq!(tuning.next_gain(0.0, None), 2.0 * MIN_GAIN);
+        let mut gain = 0.0;
+        for _ in 0..5 {
+            gain = tuning.next_gain(gain, Some(0.25));
+        }
+        assert!(gain > 10.0 * MIN_GAIN);
+    }
+
+    #[test]
+    fn gain_steps_toward_the_target_and_are_clamped() {
+        let tuning = CriticalityTuning::default();
+        // (1 / 0.25)^0.5 = 2; (1 / 4)^0.5 = 0.5; larger errors hit the clamp
+        assert!((tuning.next_gain(1.0, Some(0.25)) - 2.0).abs() < 1e-5);
+        assert!((tuning.next_gain(1.0, Some(4.0)) - 0.5).abs() < 1e-5);
+        assert_eq!(tuning.next_gain(1.0, Some(100.0)), 0.5);
+        assert!(tuning.converged(Some(1.01)));
+        assert!(!tuning.converged(None));
+    }
+
+    #[test]
+    fn branching_ratios_of_a_geometric_cascade() {
+        let counts = [1, 2, 4, 8, 16, 32, 64, 128];
+        assert_eq!(naive_branching_ratio(&counts), Some(2.0));
+        assert_eq!(naive_branching_ratio(&[0, 0, 0]), None);
+        let report = CriticalityReport::analyze(&[0, 3, 2, 0, 0, 1, 0], 1, 2);
+        assert_eq!(report.avalanche_count, 2);
+        assert_eq!(report.total_spikes, 6);
+        assert_eq!(report.mean_avalanche_size, 3.0);
+    }
+}
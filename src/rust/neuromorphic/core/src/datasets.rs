@@ -0,0 +1,419 @@
+//! Loaders for offline benchmark datasets: IDX files (MNIST and friends),
+//! N-MNIST-style binary AER event streams and CSV time series. Parsers work
+//! on in-memory bytes and text; the file-system helpers need `std`. Samples
+//! are converted to network input with the encoders in `encoding`.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::encoding::AerEvent;
+use crate::rng::XorShift64;
+
+#[derive(Debug)]
+pub enum DatasetError {
+    /// The data ends before the size announced by its header.
+    Truncated,
+    BadMagic,
+    /// The dimensions in an IDX header describe more elements than fit in
+    /// memory.
+    BadHeader,
+    UnsupportedType(u8),
+    LengthMismatch { samples: usize, labels: usize },
+    Parse { line: usize, message: String },
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatasetError::Truncated => write!(f, "data is truncated"),
+            DatasetError::BadMagic => write!(f, "not an IDX file"),
+            DatasetError::BadHeader => write!(f, "IDX dimensions overflow the addressable size"),
+            DatasetError::UnsupportedType(code) => write!(f, "unsupported IDX element type 0x{:02x}", code),
+            DatasetError::LengthMismatch { samples, labels } => {
+                write!(f, "{} samples but {} labels", samples, labels)
+            }
+            DatasetError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            #[cfg(feature = "std")]
+            DatasetError::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DatasetError {}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for DatasetError {
+    fn from(error: std::io::Error) -> Self {
+        DatasetError::Io(error)
+    }
+}
+
+/// Labelled samples with train/test splitting and shuffled iteration.
+#[derive(Clone, Debug, Default)]
+pub struct Dataset<T> {
+    pub samples: Vec<T>,
+    pub labels: Vec<usize>,
+}
+
+impl<T> Dataset<T> {
+    pub fn new(samples: Vec<T>, labels: Vec<usize>) -> Result<Self, DatasetError> {
+        if samples.len() != labels.len() {
+            return Err(DatasetError::LengthMismatch { samples: samples.len(), labels: labels.len() });
+        }
+        Ok(Self { samples, labels })
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn class_count(&self) -> usize {
+        self.labels.iter().max().map_or(0, |&label| label + 1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.samples.iter().zip(self.labels.iter().copied())
+    }
+
+    /// Iterates in an order shuffled by `seed`.
+    pub fn shuffled(&self, seed: u64) -> impl Iterator<Item = (&T, usize)> {
+        shuffled_indices(self.len(), seed)
+            .into_iter()
+            .map(move |i| (&self.samples[i], self.labels[i]))
+    }
+
+    /// Splits into `(train, test)` with `test_fraction` of the samples,
+    /// chosen by a `seed`ed shuffle, in the test set.
+    pub fn split(self, test_fraction: f32, seed: u64) -> (Dataset<T>, Dataset<T>) {
+        let test_len = (self.len() as f32 * test_fraction.clamp(0.0, 1.0)) as usize;
+        let mut is_test = alloc::vec![false; self.len()];
+        for &i in shuffled_indices(self.len(), seed).iter().take(test_len) {
+            is_test[i] = true;
+        }
+
+        let mut train = Dataset { samples: Vec::new(), labels: Vec::new() };
+        let mut test = Dataset { samples: Vec::new(), labels: Vec::new() };
+        for ((sample, label), test_sample) in self.samples.into_iter().zip(self.labels).zip(is_test) {
+            let target = if test_sample { &mut test } else { &mut train };
+            target.samples.push(sample);
+            target.labels.push(label);
+        }
+        (train, test)
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Dataset<U> {
+        Dataset { samples: self.samples.into_iter().map(f).collect(), labels: self.labels }
+    }
+}
+
+fn shuffled_indices(len: usize, seed: u64) -> Vec<usize> {
+    let mut rng = XorShift64::new(seed);
+    let mut indices: Vec<usize> = (0..len).collect();
+    // Fisher–Yates
+    for i in (1..len).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        indices.swap(i, j);
+    }
+    indices
+}
+
+/// A decoded IDX tensor; elements of any type are widened to f32.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdxArray {
+    pub dims: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+pub fn parse_idx(bytes: &[u8]) -> Result<IdxArray, DatasetError> {
+    if bytes.len() < 4 {
+        return Err(DatasetError::Truncated);
+    }
+    if bytes[0] != 0 || bytes[1] != 0 {
+        return Err(DatasetError::BadMagic);
+    }
+    let element_size = match bytes[2] {
+        0x08 | 0x09 => 1,
+        0x0B => 2,
+        0x0C | 0x0D => 4,
+        0x0E => 8,
+        code => return Err(DatasetError::UnsupportedType(code)),
+    };
+
+    let rank = bytes[3] as usize;
+    let header = 4 + 4 * rank;
+    if bytes.len() < header {
+        return Err(DatasetError::Truncated);
+    }
+    let dims: Vec<usize> = bytes[4..header]
+        .chunks_exact(4)
+        .map(|d| u32::from_be_bytes([d[0], d[1], d[2], d[3]]) as usize)
+        .collect();
+    let end = element_count(&dims)
+        .and_then(|count| count.checked_mul(element_size))
+        .and_then(|size| size.checked_add(header))
+        .ok_or(DatasetError::BadHeader)?;
+    let body = bytes.get(header..end).ok_or(DatasetError::Truncated)?;
+
+    let data = body.chunks_exact(element_size)
+        .map(|e| match bytes[2] {
+            0x08 => e[0] as f32,
+            0x09 => e[0] as i8 as f32,
+            0x0B => i16::from_be_bytes([e[0], e[1]]) as f32,
+            0x0C => i32::from_be_bytes([e[0], e[1], e[2], e[3]]) as f32,
+            0x0D => f32::from_be_bytes([e[0], e[1], e[2], e[3]]),
+            _ => f64::from_be_bytes([e[0], e[1], e[2], e[3], e[4], e[5], e[6], e[7]]) as f32,
+        })
+        .collect();
+    Ok(IdxArray { dims, data })
+}
+
+/// Product of `dims`, or `None` if it overflows.
+fn element_count(dims: &[usize]) -> Option<usize> {
+    dims.iter().try_fold(1usize, |count, &dim| count.checked_mul(dim))
+}
+
+/// MNIST-style images and labels: each image is flattened row-major and
+/// scaled from 0..255 to [0, 1].
+pub fn parse_idx_images(images: &[u8], labels: &[u8]) -> Result<Dataset<Vec<f32>>, DatasetError> {
+    let images = parse_idx(images)?;
+    let labels = parse_idx(labels)?;
+    let count = images.dims.first().copied().unwrap_or(0);
+    let pixels = element_count(images.dims.get(1..).unwrap_or(&[])).ok_or(DatasetError::BadHeader)?;
+
+    let samples = images.data
+        .chunks_exact(pixels.max(1))
+        .take(count)
+        .map(|image| image.iter().map(|&p| p / 255.0).collect())
+        .collect();
+    Dataset::new(samples, labels.data.iter().map(|&l| l as usize).collect())
+}
+
+/// N-MNIST binary format: 40-bit big-endian events of x (8 bits), y
+/// (8 bits), polarity (1 bit) and timestamp in µs (23 bits).
+pub fn parse_aer(bytes: &[u8]) -> Result<Vec<AerEvent>, DatasetError> {
+    if !bytes.len().is_multiple_of(5) {
+        return Err(DatasetError::Truncated);
+    }
+    Ok(bytes.chunks_exact(5)
+        .map(|e| AerEvent {
+            x: e[0] as u16,
+            y: e[1] as u16,
+            polarity: e[2] & 0x80 != 0,
+            timestamp_us: (((e[2] & 0x7F) as u64) << 16) | ((e[3] as u64) << 8) | e[4] as u64,
+        })
+        .collect())
+}
+
+/// Numeric CSV columns, e.g. a multichannel time series with one row per
+/// timestep.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<f32>>,
+}
+
+impl CsvTable {
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == name)
+    }
+
+    /// Removes column `index` and returns it, e.g. to use as labels.
+    /// Returns `None` and leaves the table unchanged if some row has no
+    /// such column.
+    pub fn take_column(&mut self, index: usize) -> Option<Vec<f32>> {
+        if self.rows.iter().any(|row| index >= row.len()) {
+            return None;
+        }
+        if index < self.headers.len() {
+            self.headers.remove(index);
+        }
+        Some(self.rows.iter_mut().map(|row| row.remove(index)).collect())
+    }
+
+    /// Sliding windows of `length` rows every `stride` rows, as samples for
+    /// sequence tasks.
+    pub fn windows(&self, length: usize, stride: usize) -> Vec<Vec<Vec<f32>>> {
+        if length == 0 || self.rows.len() < length {
+            return Vec::new();
+        }
+        (0..=self.rows.len() - length)
+            .step_by(stride.max(1))
+            .map(|start| self.rows[start..start + length].to_vec())
+            .collect()
+    }
+}
+
+/// Parses comma-separated numbers. A first row that does not parse as
+/// numbers is taken as the header; blank lines and lines starting with `#`
+/// are skipped.
+pub fn parse_csv(text: &str) -> Result<CsvTable, DatasetError> {
+    let mut table = CsvTable::default();
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let values: Result<Vec<f32>, _> = fields.iter().map(|f| f.parse::<f32>()).collect();
+        match values {
+            Ok(values) => {
+                let width = table.rows.first().map_or(table.headers.len(), |row| row.len());
+                if width != 0 && values.len() != width {
+                    return Err(DatasetError::Parse {
+                        line: line_index + 1,
+                        message: alloc::format!("expected {} columns, found {}", width, values.len()),
+                    });
+                }
+                table.rows.push(values);
+            }
+            Err(_) if table.rows.is_empty() && table.headers.is_empty() => {
+                table.headers = fields.iter().map(|f| f.to_string()).collect();
+            }
+            Err(error) => {
+                return Err(DatasetError::Parse { line: line_index + 1, message: error.to_string() });
+            }
+        }
+    }
+    Ok(table)
+}
+
+#[cfg(feature = "std")]
+mod files {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    pub fn load_idx_images(images: &Path, labels: &Path) -> Result<Dataset<Vec<f32>>, DatasetError> {
+        parse_idx_images(&fs::read(images)?, &fs::read(labels)?)
+    }
+
+    pub fn load_aer(path: &Path) -> Result<Vec<AerEvent>, DatasetError> {
+        parse_aer(&fs::read(path)?)
+    }
+
+    /// Lists an N-MNIST split directory (`<root>/<digit>/*.bin`) without
+    /// reading the recordings; load each with `load_aer`.
+    pub fn list_nmnist(root: &Path) -> Result<Dataset<PathBuf>, DatasetError> {
+        let mut samples = Vec::new();
+        let mut labels = Vec::new();
+        for label in 0..10 {
+            let directory = root.join(label.to_string());
+            if !directory.is_dir() {
+                continue;
+            }
+            let mut files: Vec<PathBuf> = fs::read_dir(&directory)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "bin"))
+                .collect();
+            files.sort();
+            labels.extend(core::iter::repeat_n(label, files.len()));
+            samples.extend(files);
+        }
+        Dataset::new(samples, labels)
+    }
+
+    pub fn load_csv(path: &Path) -> Result<CsvTable, DatasetError> {
+        parse_csv(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(feature = "std")]
+pub use files::{list_nmnist, load_aer, load_csv, load_idx_images};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn idx(type_code: u8, dims: &[u32], body: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0, 0, type_code, dims.len() as u8];
+        for dim in dims {
+            bytes.extend_from_slice(&dim.to_be_bytes());
+        }
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn idx_round_trip() {
+        let images = idx(0x08, &[2, 2, 2], &[0, 255, 51, 102, 255, 0, 0, 255]);
+        let labels = idx(0x08, &[2], &[3, 7]);
+        let dataset = parse_idx_images(&images, &labels).unwrap();
+        assert_eq!(dataset.samples, [vec![0.0, 1.0, 0.2, 0.4], vec![1.0, 0.0, 0.0, 1.0]]);
+        assert_eq!(dataset.labels, [3, 7]);
+        assert_eq!(dataset.class_count(), 8);
+
+        let floats: Vec<u8> = [1.5f32, -2.0].iter().flat_map(|x| x.to_be_bytes()).collect();
+        assert_eq!(parse_idx(&idx(0x0D, &[2], &floats)).unwrap(), IdxArray { dims: vec![2], data: vec![1.5, -2.0] });
+        assert_eq!(parse_idx(&idx(0x0B, &[1], &(-300i16).to_be_bytes())).unwrap().data, [-300.0]);
+    }
+
+    #[test]
+    fn malformed_idx_files_are_rejected() {
+        assert!(matches!(parse_idx(&idx(0x08, &[2, 3], &[0; 5])), Err(DatasetError::Truncated)));
+        assert!(matches!(parse_idx(&[0, 0, 0x08, 2, 0, 0]), Err(DatasetError::Truncated)));
+        assert!(matches!(parse_idx(&[1, 0, 0x08, 0]), Err(DatasetError::BadMagic)));
+        assert!(matches!(parse_idx(&idx(0x0A, &[1], &[0])), Err(DatasetError::UnsupportedType(0x0A))));
+        assert!(matches!(parse_idx(&idx(0x0E, &[u32::MAX; 4], &[])), Err(DatasetError::BadHeader)));
+
+        // An empty first dimension hides the overflow from the element count
+        // but not from the per-image size
+        let images = idx(0x08, &[0, u32::MAX, u32::MAX, u32::MAX], &[]);
+        assert!(matches!(parse_idx_images(&images, &idx(0x08, &[0], &[])), Err(DatasetError::BadHeader)));
+    }
+
+    #[test]
+    fn aer_round_trip() {
+        let events = [
+            AerEvent { x: 3, y: 33, polarity: true, timestamp_us: 0x7F_FFFF },
+            AerEvent { x: 0, y: 255, polarity: false, timestamp_us: 1234 },
+        ];
+        let bytes: Vec<u8> = events.iter()
+            .flat_map(|e| {
+                let t = e.timestamp_us;
+                [e.x as u8, e.y as u8, ((e.polarity as u8) << 7) | (t >> 16) as u8, (t >> 8) as u8, t as u8]
+            })
+            .collect();
+        assert_eq!(parse_aer(&bytes).unwrap(), events);
+        assert!(matches!(parse_aer(&bytes[..7]), Err(DatasetError::Truncated)));
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let mut table = parse_csv("# comment\na, label, b\n\n1, 0, 2.5\n3, 1, -4\n5, 1, 6\n").unwrap();
+        assert_eq!(table.headers, ["a", "label", "b"]);
+        assert_eq!(table.windows(2, 1), [vec![vec![1.0, 0.0, 2.5], vec![3.0, 1.0, -4.0]], vec![vec![3.0, 1.0, -4.0], vec![5.0, 1.0, 6.0]]]);
+
+        let index = table.column_index("label").unwrap();
+        assert_eq!(table.take_column(index), Some(vec![0.0, 1.0, 1.0]));
+        assert_eq!(table.headers, ["a", "b"]);
+        assert_eq!(table.rows, [vec![1.0, 2.5], vec![3.0, -4.0], vec![5.0, 6.0]]);
+        assert_eq!(table.take_column(2), None);
+        assert_eq!(table.rows.len(), 3);
+
+        assert!(matches!(parse_csv("1, 2\n3\n"), Err(DatasetError::Parse { line: 2, .. })));
+        assert!(matches!(parse_csv("1, 2\nx, y\n"), Err(DatasetError::Parse { line: 2, .. })));
+    }
+
+    #[test]
+    fn splits_are_disjoint_and_reproducible() {
+        let dataset = Dataset::new((0..20).collect::<Vec<u32>>(), vec![0; 20]).unwrap();
+        let (train, test) = dataset.clone().split(0.25, 9);
+        assert_eq!((train.len(), test.len()), (15, 5));
+        let mut all: Vec<u32> = train.samples.iter().chain(&test.samples).copied().collect();
+        all.sort();
+        assert_eq!(all, (0..20).collect::<Vec<u32>>());
+        assert_eq!(dataset.split(0.25, 9).1.samples, test.samples);
+        assert!(Dataset::new(vec![1], vec![]).is_err());
+    }
+}
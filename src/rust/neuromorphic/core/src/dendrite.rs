@@ -0,0 +1,298 @@
+//! Dendritic compartments. Each branch is a passive leaky compartment
+//! coupled to the soma by a conductance, with a regenerative dendritic spike:
+//! crossing `spike_threshold` clamps the branch to `plateau_potential` for
+//! `plateau_ms` (an NMDA-like plateau), driving the soma through the
+//! coupling. A `DendriticTree` attaches branches to the targeted somas of a
+//! `NeuronPopulation`, so distal input gates the response to somatic input.
+//!
+//! Branch potentials are depolarisations relative to the soma's `v_rest`, so
+//! the same branch parameters work for somas in mV or in the dimensionless
+//! units of the default network.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::model::NetworkLayout;
+use crate::modulation::ModulationTarget;
+use crate::population::NeuronPopulation;
+
+/// Branch parameters in the units of `LifParameters`; potentials are
+/// relative to rest and `coupling` is a conductance in µS, so
+/// `coupling × ΔV` (mV) is a current in nA.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct DendriteParameters {
+    pub tau_ms: f32,
+    pub resistance: f32,
+    pub coupling: f32,
+    pub spike_threshold: f32,
+    pub plateau_potential: f32,
+    pub plateau_ms: f32,
+    /// Minimum time between the end of a plateau and the next one.
+    pub refractory_ms: f32,
+    pub dt_ms: f32,
+}
+
+impl Default for DendriteParameters {
+    fn default() -> Self {
+        Self {
+            tau_ms: 10.0,
+            resistance: 20.0,
+            coupling: 0.05,
+            spike_threshold: 20.0,
+            plateau_potential: 45.0,
+            plateau_ms: 20.0,
+            refractory_ms: 5.0,
+            dt_ms: 1.0,
+        }
+    }
+}
+
+impl DendriteParameters {
+    pub fn is_valid(&self) -> bool {
+        self.tau_ms > 0.0
+            && self.dt_ms > 0.0
+            && self.resistance > 0.0
+            && self.coupling >= 0.0
+            && self.plateau_ms >= 0.0
+            && self.refractory_ms >= 0.0
+            && self.spike_threshold > 0.0
+    }
+
+    fn steps(&self, ms: f32) -> u32 {
+        math::round(ms / self.dt_ms) as u32
+    }
+}
+
+/// State of one branch.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct Dendrite {
+    /// Depolarisation relative to rest.
+    pub potential: f32,
+    /// Steps left in the current plateau.
+    pub plateau_remaining: u32,
+    /// Steps left before a new plateau can start.
+    pub refractory_remaining: u32,
+}
+
+impl Dendrite {
+    /// Advances the branch one step with `input_current` and the soma held
+    /// at `soma_potential` (relative to its rest), returning whether a
+    /// dendritic spike started and the coupling current into the soma.
+    pub fn step(&mut self, params: &DendriteParameters, input_current: f32, soma_potential: f32) -> (bool, f32) {
+        let mut spiked = false;
+        if self.plateau_remaining > 0 {
+            self.plateau_remaining -= 1;
+            self.potential = params.plateau_potential;
+            if self.plateau_remaining == 0 {
+                self.refractory_remaining = params.steps(params.refractory_ms);
+            }
+        } else {
+            // Exact integration of tau dV/dt = -V + R (I + g (V_soma - V))
+            // with input and soma potential held over the step
+            let load = 1.0 + params.resistance * params.coupling;
+            let v_inf = params.resistance * (input_current + params.coupling * soma_potential) / load;
+            let decay = math::exp(-params.dt_ms * load / params.tau_ms);
+            self.potential = v_inf + (self.potential - v_inf) * decay;
+
+            if self.refractory_remaining > 0 {
+                self.refractory_remaining -= 1;
+            } else if self.potential >= params.spike_threshold && params.plateau_ms > 0.0 {
+                spiked = true;
+                self.potential = params.plateau_potential;
+                self.plateau_remaining = params.steps(params.plateau_ms).max(1);
+            }
+        }
+
+        (spiked, params.coupling * (self.potential - soma_potential))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DendriteConfig {
+    /// Branches per neuron; 0 removes the dendrites.
+    pub branches: usize,
+    #[serde(default)]
+    pub params: DendriteParameters,
+    /// Neurons that get the branches; the rest stay point neurons.
+    #[serde(default)]
+    pub target: ModulationTarget,
+}
+
+/// Dendritic branches for the targeted neurons of a `NeuronPopulation`,
+/// stored branch-major: branch `b` of the `k`-th targeted soma is at
+/// `b * somas.len() + k`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DendriticTree {
+    pub params: DendriteParameters,
+    branches: usize,
+    /// Size of the whole network, the stride of the branch inputs.
+    neurons: usize,
+    /// Neurons carrying branches, ascending.
+    somas: Vec<usize>,
+    dendrites: Vec<Dendrite>,
+    spike_count: u64,
+}
+
+impl DendriticTree {
+    /// Builds the branches for the neurons of `layout` selected by the
+    /// config's target, or `None` if it selects none or names an unknown
+    /// population.
+    pub fn new(config: DendriteConfig, layout: &NetworkLayout) -> Option<Self> {
+        let mut somas = config.target.indices(layout)?;
+        somas.sort_unstable();
+        somas.dedup();
+        if somas.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            params: config.params,
+            branches: config.branches,
+            neurons: layout.neuron_count(),
+            dendrites: vec![Dendrite::default(); config.branches * somas.len()],
+            somas,
+            spike_count: 0,
+        })
+    }
+
+    pub fn branches(&self) -> usize {
+        self.branches
+    }
+
+    /// Neurons carrying branches, ascending.
+    pub fn somas(&self) -> &[usize] {
+        &self.somas
+    }
+
+    /// Dendritic spikes since creation.
+    pub fn spike_count(&self) -> u64 {
+        self.spike_count
+    }
+
+    /// Branch `b` of every targeted soma, in the order of `somas()`.
+    pub fn branch(&self, b: usize) -> &[Dendrite] {
+        let count = self.somas.len();
+        &self.dendrites[b * count..(b + 1) * count]
+    }
+
+    /// Potential of branch `b` for every neuron of the network, 0 for
+    /// neurons without branches.
+    pub fn potentials(&self, b: usize) -> Vec<f32> {
+        let mut potentials = vec![0.0; self.neurons];
+        for (dendrite, &i) in self.branch(b).iter().zip(&self.somas) {
+            potentials[i] = dendrite.potential;
+        }
+        potentials
+    }
+
+    /// Advances every branch against the current somatic potentials and adds
+    /// the coupling currents into `soma_currents`. `inputs` is branch-major
+    /// over the whole network, one slice of `neurons` per branch; inputs to
+    /// neurons without branches are ignored. Returns the number of
+    /// dendritic spikes.
+    pub fn step(&mut self, inputs: &[f32], somas: &NeuronPopulation, soma_currents: &mut [f32]) -> usize {
+        let mut spikes = 0;
+        for (b, branch) in self.dendrites.chunks_mut(self.somas.len()).enumerate() {
+            let branch_inputs = &inputs[b * self.neurons..(b + 1) * self.neurons];
+            for (dendrite, &i) in branch.iter_mut().zip(&self.somas) {
+                let soma_potential = somas.membrane_potential(i) - somas.parameters(i).v_rest;
+                let (spiked, current) = dendrite.step(&self.params, branch_inputs[i], soma_potential);
+                soma_currents[i] += current;
+                spikes += spiked as usize;
+            }
+        }
+        self.spike_count += spikes as u64;
+        spikes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{PopulationInfo, ProjectionInfo};
+    use crate::LifParameters;
+    use alloc::string::{String, ToString};
+
+    fn config(branches: usize, target: ModulationTarget) -> DendriteConfig {
+        DendriteConfig { branches, params: DendriteParameters::default(), target }
+    }
+
+    fn tree(branches: usize, neurons: usize) -> (DendriticTree, NeuronPopulation) {
+        let mut somas = NeuronPopulation::new();
+        for _ in 0..neurons {
+            somas.push(LifParameters::default());
+        }
+        let layout = NetworkLayout::single("network", neurons);
+        (DendriticTree::new(config(branches, ModulationTarget::All), &layout).unwrap(), somas)
+    }
+
+    #[test]
+    fn subthreshold_branch_relaxes_to_its_steady_state() {
+        let params = DendriteParameters::default();
+        let mut dendrite = Dendrite::default();
+        for _ in 0..200 {
+            dendrite.step(&params, 0.5, 0.0);
+        }
+        let load = 1.0 + params.resistance * params.coupling;
+        assert!((dendrite.potential - params.resistance * 0.5 / load).abs() < 1e-4);
+        assert_eq!(dendrite.plateau_remaining, 0);
+    }
+
+    #[test]
+    fn plateau_lasts_plateau_ms_then_the_branch_is_refractory() {
+        let params = DendriteParameters { plateau_ms: 4.0, refractory_ms: 3.0, ..DendriteParameters::default() };
+        let mut dendrite = Dendrite::default();
+        let starts: Vec<usize> = (0..20)
+            .filter(|_| dendrite.step(&params, 20.0, 0.0).0)
+            .collect();
+        // Input 20 nA crosses threshold on the first step; 4 plateau steps and
+        // 3 refractory steps follow, so the next plateau starts on step 8
+        assert_eq!(starts, [0, 8, 16]);
+    }
+
+    #[test]
+    fn plateaus_drive_the_soma_through_the_coupling() {
+        let (mut tree, somas) = tree(2, 3);
+        let mut inputs = vec![0.0; 6];
+        inputs[3 + 1] = 20.0; // branch 1 of neuron 1
+        let mut currents = vec![0.0; 3];
+        assert_eq!(tree.step(&inputs, &somas, &mut currents), 1);
+        let params = tree.params;
+        assert_eq!(currents, [0.0, params.coupling * params.plateau_potential, 0.0]);
+        assert_eq!(tree.branch(1)[1].potential, params.plateau_potential);
+        assert_eq!(tree.spike_count(), 1);
+    }
+
+    #[test]
+    fn only_targeted_neurons_get_branches() {
+        let populations = vec![
+            PopulationInfo { id: "a".to_string(), start: 0, size: 2 },
+            PopulationInfo { id: "b".to_string(), start: 2, size: 2 },
+        ];
+        let layout = NetworkLayout::new(String::new(), populations, Vec::<ProjectionInfo>::new());
+        let mut somas = NeuronPopulation::new();
+        for _ in 0..4 {
+            somas.push(LifParameters::default());
+        }
+
+        let target = ModulationTarget::Populations { ids: vec!["b".to_string()] };
+        let mut tree = DendriticTree::new(config(1, target), &layout).unwrap();
+        assert_eq!(tree.somas(), [2, 3]);
+        assert_eq!(tree.branch(0).len(), 2);
+
+        // Input to neuron 0 has no branch to land on
+        let mut currents = vec![0.0; 4];
+        assert_eq!(tree.step(&[20.0, 0.0, 0.0, 20.0], &somas, &mut currents), 1);
+        let params = tree.params;
+        assert_eq!(currents, [0.0, 0.0, 0.0, params.coupling * params.plateau_potential]);
+        assert_eq!(tree.potentials(0), [0.0, 0.0, 0.0, params.plateau_potential]);
+
+        let unknown = ModulationTarget::Populations { ids: vec!["c".to_string()] };
+        assert!(DendriticTree::new(config(1, unknown), &layout).is_none());
+        let outside = ModulationTarget::Neurons { indices: vec![7] };
+        assert!(DendriticTree::new(config(1, outside), &layout).is_none());
+    }
+}
@@ -0,0 +1,182 @@
+//! Encoders turning static samples, event streams and time series into
+//! `InputFrames`: per-timestep input currents on a fixed number of channels,
+//! mapped onto the neurons of a network with `InputFrames::currents_at`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::XorShift64;
+
+/// Input currents, step-major: channel `c` of step `t` is at
+/// `t * channels + c`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct InputFrames {
+    pub channels: usize,
+    pub data: Vec<f32>,
+}
+
+impl InputFrames {
+    pub fn new(channels: usize, steps: usize) -> Self {
+        Self { channels, data: vec![0.0; channels * steps] }
+    }
+
+    pub fn steps(&self) -> usize {
+        self.data.len().checked_div(self.channels).unwrap_or(0)
+    }
+
+    pub fn frame(&self, step: usize) -> &[f32] {
+        &self.data[step * self.channels..(step + 1) * self.channels]
+    }
+
+    pub fn frame_mut(&mut self, step: usize) -> &mut [f32] {
+        &mut self.data[step * self.channels..(step + 1) * self.channels]
+    }
+
+    /// Adds the currents of `step` into the input of `currents.len()`
+    /// neurons. With fewer channels than neurons each channel drives a
+    /// contiguous block of neurons; with more, consecutive channels are
+    /// summed onto one neuron.
+    pub fn currents_at(&self, step: usize, currents: &mut [f32]) {
+        let neurons = currents.len();
+        let frame = self.frame(step);
+        if self.channels <= neurons {
+            for (i, current) in currents.iter_mut().enumerate() {
+                *current += frame[i * self.channels / neurons];
+            }
+        } else {
+            for (c, &value) in frame.iter().enumerate() {
+                currents[c * neurons / self.channels] += value;
+            }
+        }
+    }
+}
+
+/// Encoding of a static sample with intensities in [0, 1].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StaticEncoder {
+    /// The intensity times `amplitude`, held for `steps`.
+    Direct { steps: usize, amplitude: f32 },
+    /// Independent Poisson spike trains at `intensity × max_rate_hz`; each
+    /// spike injects `amplitude` for one step.
+    Rate { steps: usize, max_rate_hz: f32, amplitude: f32, dt_ms: f32, seed: u64 },
+    /// One spike per channel, earlier for stronger inputs; intensities below
+    /// `cutoff` stay silent.
+    Latency { steps: usize, amplitude: f32, cutoff: f32 },
+}
+
+impl StaticEncoder {
+    pub fn encode(&self, values: &[f32]) -> InputFrames {
+        match *self {
+            StaticEncoder::Direct { steps, amplitude } => {
+                let mut frames = InputFrames::new(values.len(), steps);
+                for step in 0..steps {
+                    for (out, &x) in frames.frame_mut(step).iter_mut().zip(values) {
+                        *out = x.clamp(0.0, 1.0) * amplitude;
+                    }
+                }
+                frames
+            }
+            StaticEncoder::Rate { steps, max_rate_hz, amplitude, dt_ms, seed } => {
+                let mut rng = XorShift64::new(seed);
+                let mut frames = InputFrames::new(values.len(), steps);
+                for step in 0..steps {
+                    for (out, &x) in frames.frame_mut(step).iter_mut().zip(values) {
+                        if rng.uniform() < x.clamp(0.0, 1.0) * max_rate_hz * dt_ms / 1000.0 {
+                            *out = amplitude;
+                        }
+                    }
+                }
+                frames
+            }
+            StaticEncoder::Latency { steps, amplitude, cutoff } => {
+                let mut frames = InputFrames::new(values.len(), steps);
+                if steps == 0 {
+                    return frames;
+                }
+                for (c, &x) in values.iter().enumerate() {
+                    let x = x.clamp(0.0, 1.0);
+                    if x >= cutoff && x > 0.0 {
+                        let step = ((1.0 - x) * (steps - 1) as f32) as usize;
+                        frames.frame_mut(step)[c] = amplitude;
+                    }
+                }
+                frames
+            }
+        }
+    }
+}
+
+/// Address-event (AER) stream from an event camera.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct AerEvent {
+    pub x: u16,
+    pub y: u16,
+    /// true for ON (brightness increase) events.
+    pub polarity: bool,
+    pub timestamp_us: u64,
+}
+
+/// Bins events into frames of `bin_us` with one channel per pixel and
+/// polarity (`(y * width + x) * 2 + polarity`); each event adds
+/// `amplitude`. Events outside `width × height` are dropped.
+pub fn encode_events(events: &[AerEvent], width: usize, height: usize, bin_us: u64, amplitude: f32) -> InputFrames {
+    let bin_us = bin_us.max(1);
+    let duration = events.iter().map(|e| e.timestamp_us).max().map_or(0, |t| t / bin_us + 1);
+    let mut frames = InputFrames::new(width * height * 2, duration as usize);
+    for event in events {
+        let (x, y) = (event.x as usize, event.y as usize);
+        if x < width && y < height {
+            let channel = (y * width + x) * 2 + event.polarity as usize;
+            frames.frame_mut((event.timestamp_us / bin_us) as usize)[channel] += amplitude;
+        }
+    }
+    frames
+}
+
+/// Encoding of a multichannel time series (`series[t][channel]`).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SeriesEncoder {
+    /// Each value scaled by `gain`, one sample per step.
+    Direct { gain: f32 },
+    /// Send-on-delta: an UP or DOWN pulse of `amplitude` whenever a channel
+    /// moves by `threshold` from its last encoded level. Two output
+    /// channels per input channel (`2c` up, `2c + 1` down).
+    Delta { threshold: f32, amplitude: f32 },
+}
+
+impl SeriesEncoder {
+    pub fn encode(&self, series: &[Vec<f32>]) -> InputFrames {
+        let channels = series.first().map_or(0, |row| row.len());
+        match *self {
+            SeriesEncoder::Direct { gain } => {
+                let mut frames = InputFrames::new(channels, series.len());
+                for (step, row) in series.iter().enumerate() {
+                    for (out, &x) in frames.frame_mut(step).iter_mut().zip(row) {
+                        *out = gain * x;
+                    }
+                }
+                frames
+            }
+            SeriesEncoder::Delta { threshold, amplitude } => {
+                let mut frames = InputFrames::new(channels * 2, series.len());
+                let mut level: Vec<f32> = series.first().cloned().unwrap_or_default();
+                for (step, row) in series.iter().enumerate() {
+                    let frame = frames.frame_mut(step);
+                    for (c, &x) in row.iter().enumerate().take(channels) {
+                        if threshold > 0.0 && x - level[c] >= threshold {
+                            frame[2 * c] = amplitude;
+                            level[c] += threshold * ((x - level[c]) / threshold) as i32 as f32;
+                        } else if threshold > 0.0 && level[c] - x >= threshold {
+                            frame[2 * c + 1] = amplitude;
+                            level[c] -= threshold * ((level[c] - x) / threshold) as i32 as f32;
+                        }
+                    }
+                }
+                frames
+            }
+        }
+    }
+}
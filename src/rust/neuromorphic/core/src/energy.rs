@@ -0,0 +1,148 @@
+//! Operation accounting and energy estimation. Simulation steps are counted
+//! as neuron updates, synaptic operations (one per synapse whose
+//! presynaptic neuron is active, plus one per gap junction) and spikes, and
+//! priced with the per-operation energies of a hardware profile. Dense
+//! workloads such as a transformer forward pass are priced per
+//! multiply-accumulate for comparison.
+
+use alloc::string::{String, ToString};
+use core::ops::AddAssign;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OperationCounts {
+    pub steps: u64,
+    /// Somatic and dendritic compartment updates.
+    pub neuron_updates: u64,
+    pub synaptic_ops: u64,
+    pub spikes: u64,
+}
+
+impl AddAssign for OperationCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.steps += other.steps;
+        self.neuron_updates += other.neuron_updates;
+        self.synaptic_ops += other.synaptic_ops;
+        self.spikes += other.spikes;
+    }
+}
+
+/// Energy per operation in picojoules.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HardwareProfile {
+    pub name: String,
+    pub pj_per_synaptic_op: f64,
+    pub pj_per_neuron_update: f64,
+    pub pj_per_spike: f64,
+    /// Dense multiply-accumulate on this hardware, for pricing
+    /// conventional networks.
+    pub pj_per_mac: f64,
+}
+
+impl HardwareProfile {
+    /// Intel Loihi (Davies et al. 2018): synaptic spike operation, active
+    /// neuron update and within-tile spike. Dense MACs are priced as fp32
+    /// at 45 nm.
+    pub fn loihi() -> Self {
+        Self {
+            name: "loihi".to_string(),
+            pj_per_synaptic_op: 23.6,
+            pj_per_neuron_update: 52.0,
+            pj_per_spike: 1.7,
+            pj_per_mac: 4.6,
+        }
+    }
+
+    /// fp32 arithmetic at 45 nm (Horowitz 2014): an add per synaptic
+    /// operation, a multiply-add per neuron update. Arithmetic only, so a
+    /// lower bound for CPUs and GPUs, where memory traffic dominates.
+    pub fn cpu_45nm() -> Self {
+        Self {
+            name: "cpu_45nm".to_string(),
+            pj_per_synaptic_op: 0.9,
+            pj_per_neuron_update: 4.6,
+            pj_per_spike: 0.0,
+            pj_per_mac: 4.6,
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "loihi" => Some(Self::loihi()),
+            "cpu_45nm" => Some(Self::cpu_45nm()),
+            _ => None,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        [self.pj_per_synaptic_op, self.pj_per_neuron_update, self.pj_per_spike, self.pj_per_mac]
+            .iter()
+            .all(|e| e.is_finite() && *e >= 0.0)
+    }
+
+    pub fn estimate(&self, counts: &OperationCounts) -> EnergyEstimate {
+        let synaptic_pj = counts.synaptic_ops as f64 * self.pj_per_synaptic_op;
+        let neuron_pj = counts.neuron_updates as f64 * self.pj_per_neuron_update;
+        let spike_pj = counts.spikes as f64 * self.pj_per_spike;
+        EnergyEstimate { synaptic_pj, neuron_pj, spike_pj, total_pj: synaptic_pj + neuron_pj + spike_pj }
+    }
+
+    pub fn dense_energy_pj(&self, macs: f64) -> f64 {
+        macs * self.pj_per_mac
+    }
+
+    /// Decoder-only transformer inference: about one MAC per parameter per
+    /// token (attention over the context ignored).
+    pub fn transformer_energy_pj(&self, parameters: f64, tokens: f64) -> f64 {
+        self.dense_energy_pj(parameters * tokens)
+    }
+}
+
+impl Default for HardwareProfile {
+    fn default() -> Self {
+        Self::loihi()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct EnergyEstimate {
+    pub synaptic_pj: f64,
+    pub neuron_pj: f64,
+    pub spike_pj: f64,
+    pub total_pj: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_price_each_operation_kind() {
+        let counts = OperationCounts { steps: 10, neuron_updates: 100, synaptic_ops: 40, spikes: 8 };
+        let estimate = HardwareProfile::loihi().estimate(&counts);
+        assert_eq!(estimate.synaptic_pj, 40.0 * 23.6);
+        assert_eq!(estimate.neuron_pj, 100.0 * 52.0);
+        assert_eq!(estimate.spike_pj, 8.0 * 1.7);
+        assert_eq!(estimate.total_pj, estimate.synaptic_pj + estimate.neuron_pj + estimate.spike_pj);
+        assert_eq!(HardwareProfile::default().estimate(&OperationCounts::default()), EnergyEstimate::default());
+    }
+
+    #[test]
+    fn counts_accumulate() {
+        let mut total = OperationCounts::default();
+        let step = OperationCounts { steps: 1, neuron_updates: 5, synaptic_ops: 3, spikes: 2 };
+        total += step;
+        total += step;
+        assert_eq!(total, OperationCounts { steps: 2, neuron_updates: 10, synaptic_ops: 6, spikes: 4 });
+    }
+
+    #[test]
+    fn presets_and_validation() {
+        assert_eq!(HardwareProfile::preset("cpu_45nm"), Some(HardwareProfile::cpu_45nm()));
+        assert_eq!(HardwareProfile::preset("gpu"), None);
+        assert!(HardwareProfile::loihi().is_valid());
+        assert!(!HardwareProfile { pj_per_spike: -1.0, ..HardwareProfile::loihi() }.is_valid());
+        assert!(!HardwareProfile { pj_per_mac: f64::NAN, ..HardwareProfile::loihi() }.is_valid());
+        assert_eq!(HardwareProfile::cpu_45nm().transformer_energy_pj(1e9, 2.0), 2e9 * 4.6);
+    }
+}
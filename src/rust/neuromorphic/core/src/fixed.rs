@@ -0,0 +1,291 @@
+//! Q-format integer variant of the LIF update, recurrent synaptic
+//! accumulation and the Hebbian learning rule, for the ESP32-S3 swarm nodes.
+//!
+//! Formats:
+//! - potentials, currents, resistance, decay and firing rates: Q16.16 in `i32`
+//! - synaptic weights: Q1.14 in `i16` (weights are clamped to [-1, 1])
+//!
+//! All products are rounded to nearest. With `q = 2^-16`, a membrane error
+//! `e` before a step grows to at most
+//!
+//! ```text
+//! e' <= d·e + (1 + d)·ε_inf + (|V - V_inf| + 1)·q/2
+//! ε_inf = (2 + |R| + |I|)·q/2
+//! ```
+//!
+//! where `d` is the per-step decay, so the deviation from the f32 model stays
+//! below `((1 + d)·ε_inf + (|V - V_inf| + 1)·q/2) / (1 - d)` (see
+//! [`membrane_error_bound`]). Spike times are identical to the f32 model
+//! except for steps on which the f32 potential lies within that bound of
+//! threshold. Recurrent input adds at most `2^-15·rate·scale` per synapse
+//! from weight quantisation, and each learning update changes a weight by at
+//! most `2^-15` more than the f32 rule.
+
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::population::NeuronPopulation;
+use crate::LifParameters;
+
+pub const FRAC_BITS: u32 = 16;
+pub const WEIGHT_FRAC_BITS: u32 = 14;
+
+const ONE: i64 = 1 << FRAC_BITS;
+const WEIGHT_ONE: i32 = 1 << WEIGHT_FRAC_BITS;
+
+pub fn to_fixed(value: f32) -> i32 {
+    math::round_f64(value as f64 * ONE as f64).clamp(i32::MIN as f64, i32::MAX as f64) as i32
+}
+
+pub fn from_fixed(value: i32) -> f32 {
+    (value as f64 / ONE as f64) as f32
+}
+
+pub fn to_weight(value: f32) -> i16 {
+    math::round(value.clamp(-1.0, 1.0) * WEIGHT_ONE as f32) as i16
+}
+
+pub fn from_weight(value: i16) -> f32 {
+    value as f32 / WEIGHT_ONE as f32
+}
+
+/// Q16.16 multiply with round-to-nearest and saturation.
+fn mul(a: i32, b: i32) -> i32 {
+    let product = (a as i64 * b as i64 + (ONE >> 1)) >> FRAC_BITS;
+    product.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+/// Upper bound on |V_fixed - V_f32| for a neuron with `params`, driven by
+/// inputs no larger than `max_abs_input` and with |V - V_inf| no larger than
+/// `max_abs_deviation`.
+pub fn membrane_error_bound(params: &LifParameters, max_abs_input: f32, max_abs_deviation: f32) -> f32 {
+    let q = 1.0 / ONE as f32;
+    let decay = math::exp(-params.dt_ms / params.tau_m_ms);
+    let eps_inf = (2.0 + params.resistance.abs() + max_abs_input) * q / 2.0;
+    let per_step = (1.0 + decay) * eps_inf + (max_abs_deviation + 1.0) * q / 2.0;
+    per_step / (1.0 - decay).max(f32::EPSILON)
+}
+
+/// Fixed-point neuron state, one entry per neuron.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FixedLifArrays {
+    pub membrane: Vec<i32>,
+    pub v_rest: Vec<i32>,
+    pub v_reset: Vec<i32>,
+    pub v_threshold: Vec<i32>,
+    pub resistance: Vec<i32>,
+    pub decay: Vec<i32>,
+    pub refractory: Vec<u16>,
+    pub refractory_steps: Vec<u16>,
+}
+
+impl FixedLifArrays {
+    /// Quantises the parameters and current state of `population`.
+    pub fn from_population(population: &NeuronPopulation) -> Self {
+        let mut arrays = Self::default();
+        for i in 0..population.len() {
+            let params = population.parameters(i);
+            arrays.membrane.push(to_fixed(population.membrane_potential(i)));
+            arrays.v_rest.push(to_fixed(params.v_rest));
+            arrays.v_reset.push(to_fixed(params.v_reset));
+            arrays.v_threshold.push(to_fixed(params.v_threshold));
+            arrays.resistance.push(to_fixed(params.resistance));
+            arrays.decay.push(to_fixed(math::exp(-params.dt_ms / params.tau_m_ms)));
+            arrays.refractory.push(population.refractory_remaining(i).min(u16::MAX as u32) as u16);
+            arrays.refractory_steps.push(math::round(params.refractory_ms / params.dt_ms).min(u16::MAX as f32) as u16);
+        }
+        arrays
+    }
+
+    /// Integer counterpart of `LeakyIntegrateFireNeuron::step` for every neuron.
+    #[allow(clippy::needless_range_loop)]
+    pub fn step(&mut self, inputs: &[i32], spikes: &mut [bool]) -> usize {
+        let mut count = 0;
+        for i in 0..self.membrane.len() {
+            spikes[i] = false;
+            if self.refractory[i] > 0 {
+                self.refractory[i] -= 1;
+                self.membrane[i] = self.v_reset[i];
+                continue;
+            }
+
+            let v_inf = self.v_rest[i].saturating_add(mul(self.resistance[i], inputs[i]));
+            let v = v_inf.saturating_add(mul(self.membrane[i].saturating_sub(v_inf), self.decay[i]));
+            if v >= self.v_threshold[i] {
+                self.membrane[i] = self.v_reset[i];
+                self.refractory[i] = self.refractory_steps[i];
+                spikes[i] = true;
+                count += 1;
+            } else {
+                self.membrane[i] = v;
+            }
+        }
+        count
+    }
+}
+
+/// Quantised network: neuron state plus Q1.14 weights indexed `[pre][post]`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FixedPointNetwork {
+    pub frac_bits: u32,
+    pub weight_frac_bits: u32,
+    pub neurons: FixedLifArrays,
+    pub weights: Vec<Vec<i16>>,
+}
+
+impl FixedPointNetwork {
+    pub fn quantize(population: &NeuronPopulation, weights: &[Vec<f32>]) -> Self {
+        Self {
+            frac_bits: FRAC_BITS,
+            weight_frac_bits: WEIGHT_FRAC_BITS,
+            neurons: FixedLifArrays::from_population(population),
+            weights: weights.iter()
+                .map(|row| row.iter().map(|&w| to_weight(w)).collect())
+                .collect(),
+        }
+    }
+
+    /// Re-quantises weights after they were changed on the f32 side.
+    pub fn load_weights(&mut self, weights: &[Vec<f32>]) {
+        for (fixed_row, row) in self.weights.iter_mut().zip(weights) {
+            for (fixed, &w) in fixed_row.iter_mut().zip(row) {
+                *fixed = to_weight(w);
+            }
+        }
+    }
+
+    /// Writes the dequantised weights back into `weights`.
+    pub fn store_weights(&self, weights: &mut [Vec<f32>]) {
+        for (row, fixed_row) in weights.iter_mut().zip(&self.weights) {
+            for (w, &fixed) in row.iter_mut().zip(fixed_row) {
+                *w = from_weight(fixed);
+            }
+        }
+    }
+
+    /// Integer counterpart of the recurrent accumulation: adds
+    /// `scale · w[pre][post] · rate[pre]` into `currents[post]` (all Q16.16
+    /// apart from the Q1.14 weights). Returns the number of synaptic
+    /// operations.
+    pub fn accumulate_recurrent(&self, rates: &[i32], scale: i32, epsilon: i16, currents: &mut [i32]) -> u64 {
+        let mut operations = 0;
+        for (pre, row) in self.weights.iter().enumerate() {
+            if rates[pre] == 0 {
+                continue;
+            }
+            let drive = mul(rates[pre], scale) as i64;
+            for (post, &weight) in row.iter().enumerate() {
+                if post != pre && weight.abs() > epsilon {
+                    let contribution = (weight as i64 * drive + (WEIGHT_ONE as i64 >> 1)) >> WEIGHT_FRAC_BITS;
+                    currents[post] = currents[post].saturating_add(contribution as i32);
+                    operations += 1;
+                }
+            }
+        }
+        operations
+    }
+
+    /// Integer counterpart of the Hebbian rule: existing synapses between
+    /// neurons both firing above `rate_threshold` are strengthened by the
+    /// postsynaptic neuron's entry in `increments` and clamped to [-1, 1].
+    /// Synapses for which `plastic` returns false are left untouched.
+    pub fn apply_learning(
+        &mut self,
+        rates: &[i32],
+        rate_threshold: i32,
+        increments: &[i16],
+        epsilon: i16,
+        plastic: impl Fn(usize, usize) -> bool,
+    ) {
+        for (i, row) in self.weights.iter_mut().enumerate() {
+            if rates[i] <= rate_threshold {
+                continue;
+            }
+            for (j, weight) in row.iter_mut().enumerate() {
+                if i != j && weight.abs() > epsilon && rates[j] > rate_threshold && plastic(i, j) {
+                    let updated = (*weight as i32 + increments[j] as i32).clamp(-WEIGHT_ONE, WEIGHT_ONE);
+                    *weight = updated as i16;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::XorShift64;
+    use alloc::vec;
+
+    const MAX_INPUT: f32 = 2.0;
+
+    fn population(params: &[LifParameters]) -> NeuronPopulation {
+        let mut neurons = NeuronPopulation::new();
+        for &p in params {
+            neurons.push(p);
+        }
+        neurons
+    }
+
+    #[test]
+    fn membrane_error_stays_within_the_analytic_bound() {
+        // Thresholds out of reach, so both models integrate freely
+        let params = [
+            LifParameters { v_threshold: 1000.0, ..LifParameters::default() },
+            LifParameters { v_threshold: 1000.0, tau_m_ms: 5.0, resistance: 3.0, ..LifParameters::default() },
+            LifParameters { v_threshold: 1000.0, tau_m_ms: 50.0, dt_ms: 0.1, ..LifParameters::default() },
+            LifParameters { v_threshold: 1000.0, ..LifParameters::from_dimensionless(1.0, 0.12) },
+        ];
+        let mut reference = population(&params);
+        let mut fixed = FixedLifArrays::from_population(&reference);
+        let bounds: Vec<f32> = params.iter()
+            .map(|p| membrane_error_bound(p, MAX_INPUT, 2.0 * p.resistance.abs() * MAX_INPUT))
+            .collect();
+
+        let mut rng = XorShift64::new(9);
+        let mut spikes = vec![false; params.len()];
+        for t in 0..5000 {
+            let inputs: Vec<f32> = params.iter().map(|_| MAX_INPUT * (2.0 * rng.uniform() - 1.0)).collect();
+            let fixed_inputs: Vec<i32> = inputs.iter().map(|&x| to_fixed(x)).collect();
+            reference.step(&inputs, t, &mut spikes, 1);
+            fixed.step(&fixed_inputs, &mut spikes);
+            for (i, bound) in bounds.iter().enumerate() {
+                let error = (from_fixed(fixed.membrane[i]) - reference.membrane_potential(i)).abs();
+                assert!(error <= *bound, "neuron {} step {}: |dV| = {} > {}", i, t, error, bound);
+            }
+        }
+    }
+
+    #[test]
+    fn spike_times_agree_away_from_threshold() {
+        let params = [LifParameters::default(); 8];
+        let mut reference = population(&params);
+        let mut fixed = FixedLifArrays::from_population(&reference);
+        let bound = membrane_error_bound(&params[0], MAX_INPUT, 2.0 * params[0].resistance * MAX_INPUT);
+
+        let mut rng = XorShift64::new(2);
+        let (mut f32_spikes, mut fixed_spikes) = (vec![false; 8], vec![false; 8]);
+        let mut spike_total = 0;
+        for t in 0..2000 {
+            let inputs: Vec<f32> = (0..8).map(|_| MAX_INPUT * (0.6 + 0.4 * rng.uniform())).collect();
+            let fixed_inputs: Vec<i32> = inputs.iter().map(|&x| to_fixed(x)).collect();
+            let before: Vec<f32> = (0..8).map(|i| reference.membrane_potential(i)).collect();
+            spike_total += reference.step(&inputs, t, &mut f32_spikes, 1);
+            fixed.step(&fixed_inputs, &mut fixed_spikes);
+            for i in 0..8 {
+                let p = &params[i];
+                let v_inf = p.v_rest + p.resistance * inputs[i];
+                let v = v_inf + (before[i] - v_inf) * math::exp(-p.dt_ms / p.tau_m_ms);
+                if (v - p.v_threshold).abs() > bound {
+                    assert_eq!(f32_spikes[i], fixed_spikes[i], "neuron {} step {}", i, t);
+                }
+            }
+            // Re-synchronise after a near-threshold disagreement
+            if f32_spikes != fixed_spikes {
+                fixed = FixedLifArrays::from_population(&reference);
+            }
+        }
+        assert!(spike_total > 100);
+    }
+}
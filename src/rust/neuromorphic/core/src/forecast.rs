@@ -0,0 +1,388 @@
+//! Time-series forecasting with the network as a reservoir: a scalar series
+//! drives every neuron through fixed random input weights, and linear
+//! readouts trained by ridge regression map the reservoir state to the
+//! value 1..=`horizon` steps ahead (one direct readout per lead).
+
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::population::NeuronPopulation;
+use crate::rates::{RateEstimator, RateEstimatorConfig};
+use crate::rng::XorShift64;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReservoirFeatures {
+    /// Exponentially filtered spike trains.
+    #[default]
+    Traces,
+    /// Membrane potentials, scaled to 0 at rest and 1 at threshold.
+    Membrane,
+    Both,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct ForecastConfig {
+    /// Furthest lead, in steps, that readouts are trained for.
+    pub horizon: usize,
+    /// Ridge penalty on the readout weights (features are O(1)).
+    pub ridge: f64,
+    /// Initial steps excluded from training while the reservoir settles.
+    pub washout: usize,
+    /// Current per standard deviation of the series, times each neuron's
+    /// input weight in [-1, 1].
+    pub input_gain: f32,
+    /// Constant current keeping the reservoir active at the series mean.
+    pub bias_current: f32,
+    pub trace_tau_ms: f32,
+    pub features: ReservoirFeatures,
+    /// Tail of the series held out by `fit` to report error metrics.
+    pub validation_fraction: f32,
+    pub seed: u64,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        Self {
+            horizon: 1,
+            ridge: 1e-3,
+            washout: 50,
+            input_gain: 1.0,
+            bias_current: 0.5,
+            trace_tau_ms: 20.0,
+            features: ReservoirFeatures::Traces,
+            validation_fraction: 0.2,
+            seed: 1,
+        }
+    }
+}
+
+impl ForecastConfig {
+    pub fn is_valid(&self) -> bool {
+        self.horizon > 0
+            && self.ridge.is_finite()
+            && self.ridge >= 0.0
+            && self.input_gain.is_finite()
+            && self.bias_current.is_finite()
+            && self.trace_tau_ms.is_finite()
+            && self.trace_tau_ms > 0.0
+            && (0.0..1.0).contains(&self.validation_fraction)
+    }
+}
+
+/// Normal equations `XᵀX w = Xᵀy` of a multi-output least-squares problem,
+/// accumulated one sample at a time so training data is never stored.
+#[derive(Clone, Debug)]
+pub struct RidgeAccumulator {
+    dim: usize,
+    outputs: usize,
+    xtx: Vec<f64>,
+    xty: Vec<f64>,
+    pub samples: usize,
+}
+
+impl RidgeAccumulator {
+    pub fn new(dim: usize, outputs: usize) -> Self {
+        Self { dim, outputs, xtx: vec![0.0; dim * dim], xty: vec![0.0; dim * outputs], samples: 0 }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn add(&mut self, features: &[f64], targets: &[f64]) {
+        for (i, &fi) in features.iter().enumerate() {
+            if fi == 0.0 {
+                continue;
+            }
+            // Upper triangle only; mirrored in `solve`
+            for (j, &fj) in features.iter().enumerate().skip(i) {
+                self.xtx[i * self.dim + j] += fi * fj;
+            }
+            for (o, &target) in targets.iter().enumerate() {
+                self.xty[i * self.outputs + o] += fi * target;
+            }
+        }
+        self.samples += 1;
+    }
+
+    /// Weights `[feature][output]` minimising squared error plus
+    /// `ridge · |w|²`, with the first feature (the bias) unpenalised.
+    /// `None` if the system is singular.
+    pub fn solve(&self, ridge: f64) -> Option<Vec<f64>> {
+        let n = self.dim;
+        let mut a = vec![0.0; n * n];
+        for i in 0..n {
+            for j in i..n {
+                a[i * n + j] = self.xtx[i * n + j];
+                a[j * n + i] = self.xtx[i * n + j];
+            }
+            if i > 0 {
+                a[i * n + i] += ridge;
+            }
+        }
+
+        // Cholesky factorisation A = L Lᵀ, L stored in the lower triangle
+        for j in 0..n {
+            let mut diagonal = a[j * n + j];
+            for k in 0..j {
+                diagonal -= a[j * n + k] * a[j * n + k];
+            }
+            if diagonal <= 1e-12 {
+                return None;
+            }
+            let diagonal = math::sqrt_f64(diagonal);
+            a[j * n + j] = diagonal;
+            for i in j + 1..n {
+                let mut value = a[i * n + j];
+                for k in 0..j {
+                    value -= a[i * n + k] * a[j * n + k];
+                }
+                a[i * n + j] = value / diagonal;
+            }
+        }
+
+        let mut weights = vec![0.0; n * self.outputs];
+        let mut y = vec![0.0; n];
+        for o in 0..self.outputs {
+            for i in 0..n {
+                let mut value = self.xty[i * self.outputs + o];
+                for k in 0..i {
+                    value -= a[i * n + k] * y[k];
+                }
+                y[i] = value / a[i * n + i];
+            }
+            for i in (0..n).rev() {
+                let mut value = y[i];
+                for k in i + 1..n {
+                    value -= a[k * n + i] * weights[k * self.outputs + o];
+                }
+                weights[i * self.outputs + o] = value / a[i * n + i];
+            }
+        }
+        Some(weights)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ErrorMetrics {
+    /// Steps ahead.
+    pub lead: usize,
+    pub samples: usize,
+    pub mae: f32,
+    pub rmse: f32,
+}
+
+/// Absolute and squared prediction errors per lead.
+#[derive(Clone, Debug)]
+pub struct ErrorAccumulator {
+    absolute: Vec<f64>,
+    squared: Vec<f64>,
+    samples: Vec<usize>,
+}
+
+impl ErrorAccumulator {
+    pub fn new(horizon: usize) -> Self {
+        Self { absolute: vec![0.0; horizon], squared: vec![0.0; horizon], samples: vec![0; horizon] }
+    }
+
+    /// `lead` counts from 1.
+    pub fn add(&mut self, lead: usize, predicted: f32, actual: f32) {
+        let error = (predicted - actual) as f64;
+        self.absolute[lead - 1] += error.abs();
+        self.squared[lead - 1] += error * error;
+        self.samples[lead - 1] += 1;
+    }
+
+    /// Leads without samples are omitted.
+    pub fn metrics(&self) -> Vec<ErrorMetrics> {
+        (0..self.samples.len())
+            .filter(|&l| self.samples[l] > 0)
+            .map(|l| {
+                let n = self.samples[l] as f64;
+                ErrorMetrics {
+                    lead: l + 1,
+                    samples: self.samples[l],
+                    mae: (self.absolute[l] / n) as f32,
+                    rmse: math::sqrt_f64(self.squared[l] / n) as f32,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Input mapping, reservoir state features and trained readouts.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Forecaster {
+    pub config: ForecastConfig,
+    /// Series normalisation fixed at fit time.
+    pub mean: f32,
+    pub std: f32,
+    input_weights: Vec<f32>,
+    traces: RateEstimator,
+    /// Bias, the normalised last value, then the reservoir features.
+    features: Vec<f64>,
+    /// `[feature][lead - 1]`; empty until trained.
+    readout: Vec<f64>,
+}
+
+impl Forecaster {
+    /// Normalises by the mean and standard deviation of `series`.
+    pub fn new(config: ForecastConfig, neurons: usize, dt_ms: f32, series: &[f32]) -> Self {
+        let n = series.len().max(1) as f64;
+        let mean = series.iter().map(|&x| x as f64).sum::<f64>() / n;
+        let variance = series.iter().map(|&x| (x as f64 - mean) * (x as f64 - mean)).sum::<f64>() / n;
+        let std = math::sqrt_f64(variance);
+
+        let mut rng = XorShift64::new(config.seed);
+        let per_neuron = match config.features {
+            ReservoirFeatures::Traces | ReservoirFeatures::Membrane => 1,
+            ReservoirFeatures::Both => 2,
+        };
+        Self {
+            config,
+            mean: mean as f32,
+            std: if std > 1e-12 { std as f32 } else { 1.0 },
+            input_weights: (0..neurons).map(|_| 2.0 * rng.uniform() - 1.0).collect(),
+            traces: RateEstimator::new(RateEstimatorConfig::Exponential { tau_ms: config.trace_tau_ms }, neurons, dt_ms),
+            features: vec![0.0; 2 + per_neuron * neurons],
+            readout: Vec::new(),
+        }
+    }
+
+    pub fn feature_count(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn features(&self) -> &[f64] {
+        &self.features
+    }
+
+    pub fn is_trained(&self) -> bool {
+        !self.readout.is_empty()
+    }
+
+    pub fn normalize(&self, value: f32) -> f32 {
+        (value - self.mean) / self.std
+    }
+
+    /// Input currents for presenting `value`.
+    pub fn input_currents(&self, value: f32, currents: &mut [f32]) {
+        let drive = self.config.input_gain * self.normalize(value);
+        for (current, &weight) in currents.iter_mut().zip(&self.input_weights) {
+            *current += self.config.bias_current + drive * weight;
+        }
+    }
+
+    /// Updates the features after a step that presented `value`.
+    pub fn observe(&mut self, value: f32, spikes: &[bool], neurons: &NeuronPopulation) {
+        self.traces.update(spikes);
+        let n = neurons.len();
+        let trace_scale = self.config.trace_tau_ms / 1000.0;
+        self.features[0] = 1.0;
+        self.features[1] = self.normalize(value) as f64;
+
+        let mut offset = 2;
+        if self.config.features != ReservoirFeatures::Membrane {
+            for (feature, &rate) in self.features[offset..offset + n].iter_mut().zip(self.traces.rates()) {
+                *feature = (rate * trace_scale) as f64;
+            }
+            offset += n;
+        }
+        if self.config.features != ReservoirFeatures::Traces {
+            for (i, feature) in self.features[offset..offset + n].iter_mut().enumerate() {
+                let params = neurons.parameters(i);
+                let span = params.v_threshold - params.v_rest;
+                *feature = ((neurons.membrane_potential(i) - params.v_rest) / span) as f64;
+            }
+        }
+    }
+
+    /// Solves the readouts; false if the system was singular.
+    pub fn train(&mut self, ridge: &RidgeAccumulator) -> bool {
+        match ridge.solve(self.config.ridge) {
+            Some(weights) => {
+                self.readout = weights;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Predicted values 1..=`horizon` steps after the last observed one;
+    /// empty until trained.
+    pub fn predict(&self) -> Vec<f32> {
+        if !self.is_trained() {
+            return Vec::new();
+        }
+        let horizon = self.config.horizon;
+        (0..horizon)
+            .map(|lead| {
+                let normalized: f64 = self.features.iter()
+                    .enumerate()
+                    .map(|(i, &f)| f * self.readout[i * horizon + lead])
+                    .sum();
+                normalized as f32 * self.std + self.mean
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ridge_recovers_a_linear_map() {
+        // y0 = 1 + 2 x, y1 = -3 + 0.5 x with the bias as the first feature
+        let mut ridge = RidgeAccumulator::new(2, 2);
+        for x in [-2.0, -1.0, 0.5, 3.0] {
+            ridge.add(&[1.0, x], &[1.0 + 2.0 * x, -3.0 + 0.5 * x]);
+        }
+        assert_eq!(ridge.samples, 4);
+        let weights = ridge.solve(0.0).unwrap();
+        for (weight, expected) in weights.iter().zip([1.0, -3.0, 2.0, 0.5]) {
+            assert!((weight - expected).abs() < 1e-9, "{:?}", weights);
+        }
+
+        // The penalty shrinks the slope but not the bias
+        let shrunk = ridge.solve(100.0).unwrap();
+        assert!(shrunk[2].abs() < 2.0);
+    }
+
+    #[test]
+    fn singular_systems_are_reported() {
+        let mut ridge = RidgeAccumulator::new(2, 1);
+        ridge.add(&[1.0, 0.0], &[1.0]);
+        assert!(ridge.solve(0.0).is_none());
+        assert!(ridge.solve(1e-3).is_some());
+    }
+
+    #[test]
+    fn error_metrics_per_lead() {
+        let mut errors = ErrorAccumulator::new(3);
+        errors.add(1, 1.0, 2.0);
+        errors.add(1, 4.0, 1.0);
+        errors.add(3, 0.0, 0.0);
+        let metrics = errors.metrics();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0], ErrorMetrics { lead: 1, samples: 2, mae: 2.0, rmse: 5.0f32.sqrt() });
+        assert_eq!((metrics[1].lead, metrics[1].mae), (3, 0.0));
+    }
+
+    #[test]
+    fn forecaster_normalizes_and_predicts_only_once_trained() {
+        let forecaster = Forecaster::new(ForecastConfig::default(), 4, 1.0, &[1.0, 3.0, 1.0, 3.0]);
+        assert_eq!((forecaster.mean, forecaster.std), (2.0, 1.0));
+        assert_eq!(forecaster.normalize(4.0), 2.0);
+        assert_eq!(forecaster.feature_count(), 6);
+        assert!(forecaster.predict().is_empty());
+
+        let constant = Forecaster::new(ForecastConfig::default(), 4, 1.0, &[5.0; 10]);
+        assert_eq!(constant.std, 1.0);
+        assert!(!ForecastConfig { validation_fraction: 1.0, ..ForecastConfig::default() }.is_valid());
+    }
+}
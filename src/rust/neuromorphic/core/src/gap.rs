@@ -0,0 +1,142 @@
+//! Electrical synapses. A gap junction between neurons `a` and `b` with
+//! conductance `g` injects `g (V_b - V_a)` into `a` and the opposite current
+//! into `b` every step, pulling their membrane potentials together. Junctions
+//! are grouped by the projection that created them so their conductance can
+//! be tuned per projection.
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::population::NeuronPopulation;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct GapJunction {
+    pub a: usize,
+    pub b: usize,
+    pub conductance: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GapJunctionProjection {
+    pub id: String,
+    /// Population indices in the network layout.
+    pub pre: usize,
+    pub post: usize,
+    pub junctions: Vec<GapJunction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GapJunctions {
+    pub projections: Vec<GapJunctionProjection>,
+}
+
+impl GapJunctions {
+    /// Adds a projection of junctions from `(a, b, conductance)` pairs in
+    /// network indices. Junctions are undirected: self-pairs are dropped and
+    /// of `(a, b)` and `(b, a)` only the first is kept. Returns false if the
+    /// id is already taken.
+    pub fn add(&mut self, id: String, pre: usize, post: usize, pairs: &[(usize, usize, f32)]) -> bool {
+        if self.projections.iter().any(|p| p.id == id) {
+            return false;
+        }
+
+        let mut junctions: Vec<GapJunction> = Vec::with_capacity(pairs.len());
+        let mut seen = BTreeSet::new();
+        for &(a, b, conductance) in pairs {
+            if a != b && seen.insert((a.min(b), a.max(b))) {
+                junctions.push(GapJunction { a, b, conductance });
+            }
+        }
+
+        self.projections.push(GapJunctionProjection { id, pre, post, junctions });
+        true
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.projections.len();
+        self.projections.retain(|p| p.id != id);
+        self.projections.len() != before
+    }
+
+    /// Sets every junction of projection `id` to `conductance`.
+    pub fn set_conductance(&mut self, id: &str, conductance: f32) -> bool {
+        match self.projections.iter_mut().find(|p| p.id == id) {
+            Some(projection) => {
+                for junction in &mut projection.junctions {
+                    junction.conductance = conductance;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.projections.iter().all(|p| p.junctions.is_empty())
+    }
+
+    pub fn junction_count(&self) -> usize {
+        self.projections.iter().map(|p| p.junctions.len()).sum()
+    }
+
+    /// Adds the gap-junction currents for the current membrane potentials
+    /// into `currents`.
+    pub fn accumulate(&self, neurons: &NeuronPopulation, currents: &mut [f32]) {
+        for junction in self.projections.iter().flat_map(|p| &p.junctions) {
+            let current = junction.conductance
+                * (neurons.membrane_potential(junction.b) - neurons.membrane_potential(junction.a));
+            currents[junction.a] += current;
+            currents[junction.b] -= current;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LifParameters;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn duplicate_and_self_pairs_are_dropped() {
+        let mut gaps = GapJunctions::default();
+        assert!(gaps.add("g".to_string(), 0, 0, &[(0, 1, 0.1), (1, 0, 0.2), (2, 2, 0.3), (1, 2, 0.4)]));
+        let pairs: Vec<(usize, usize, f32)> = gaps.projections[0].junctions.iter()
+            .map(|j| (j.a, j.b, j.conductance))
+            .collect();
+        assert_eq!(pairs, [(0, 1, 0.1), (1, 2, 0.4)]);
+        assert!(!gaps.add("g".to_string(), 0, 0, &[]));
+    }
+
+    #[test]
+    fn dense_projections_keep_one_junction_per_pair() {
+        let n = 400;
+        let pairs: Vec<(usize, usize, f32)> = (0..n)
+            .flat_map(|a| (0..n).map(move |b| (a, b, 0.01)))
+            .collect();
+        let mut gaps = GapJunctions::default();
+        gaps.add("dense".to_string(), 0, 0, &pairs);
+        assert_eq!(gaps.junction_count(), n * (n - 1) / 2);
+    }
+
+    #[test]
+    fn currents_pull_potentials_together_and_conserve_charge() {
+        let mut neurons = NeuronPopulation::new();
+        for v_rest in [-70.0, -60.0, -65.0] {
+            neurons.push(LifParameters { v_rest, ..LifParameters::default() });
+        }
+        let mut gaps = GapJunctions::default();
+        gaps.add("g".to_string(), 0, 0, &[(0, 1, 0.5)]);
+        let mut currents = vec![0.0; 3];
+        gaps.accumulate(&neurons, &mut currents);
+        assert_eq!(currents, [5.0, -5.0, 0.0]);
+
+        assert!(gaps.set_conductance("g", 0.0));
+        let mut currents = vec![0.0; 3];
+        gaps.accumulate(&neurons, &mut currents);
+        assert_eq!(currents, [0.0; 3]);
+    }
+}
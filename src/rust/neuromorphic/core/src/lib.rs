@@ -0,0 +1,37 @@
+//! Simulation core of the neuromorphic processor: neuron models, population
+//! kernels, plasticity, stimuli, probes and statistics. Builds as `no_std` +
+//! `alloc` with default features disabled so it can run on the swarm nodes.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod math;
+
+pub mod anomaly;
+pub mod competitive;
+pub mod criticality;
+pub mod datasets;
+pub mod dendrite;
+pub mod encoding;
+pub mod energy;
+pub mod fixed;
+pub mod forecast;
+pub mod gap;
+pub mod link;
+pub mod model;
+pub mod modulation;
+pub mod neuron;
+pub mod parallel;
+pub mod population;
+pub mod probes;
+pub mod rates;
+pub mod rng;
+pub mod stats;
+pub mod stimulus;
+pub mod structural;
+pub mod surrogate;
+
+pub use neuron::{LeakyIntegrateFireNeuron, LifParameters, SpikeHistory};
+#[cfg(feature = "heapless")]
+pub use neuron::HeaplessLifNeuron;
@@ -0,0 +1,245 @@
+//! Links between separate networks: a projection from a population of one
+//! network onto a population of another, with an axonal delay. Each spike
+//! of a source neuron injects `weight` into its targets for one step,
+//! `delay` steps later.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::model::{
+    Connector, ModelError, NetworkLayout, PopulationInfo, ProjectionDescription, SynapseKind,
+    SynapsePlasticity, WeightDescription,
+};
+use crate::rng::XorShift64;
+
+/// Longest delay line a link may hold, in steps; each step buffers one
+/// current per target neuron.
+pub const MAX_DELAY_STEPS: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LinkConfig {
+    pub id: String,
+    /// Names of the linked networks.
+    pub source: String,
+    pub target: String,
+    pub source_population: String,
+    pub target_population: String,
+    pub connector: Connector,
+    #[serde(default)]
+    pub weight: WeightDescription,
+    /// Rounded to whole steps, at least one and at most `MAX_DELAY_STEPS`.
+    #[serde(default)]
+    pub delay_ms: f32,
+    /// Seed for probabilistic connectors and random weights.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Link {
+    pub config: LinkConfig,
+    source_range: Range<usize>,
+    target_range: Range<usize>,
+    /// Outgoing `(target, weight)` per source neuron, population-local.
+    synapses: Vec<Vec<(usize, f32)>>,
+    /// Target currents in flight, one slot per step of delay.
+    pending: Vec<Vec<f32>>,
+    head: usize,
+}
+
+impl Link {
+    pub fn new(config: LinkConfig, source: &NetworkLayout, target: &NetworkLayout, dt_ms: f32) -> Result<Self, ModelError> {
+        let find = |layout: &NetworkLayout, id: &str| {
+            layout.populations.iter().find(|p| p.id == id).cloned().ok_or_else(|| ModelError::UnknownPopulation {
+                projection: config.id.clone(),
+                population: id.to_string(),
+            })
+        };
+        let source_population = find(source, &config.source_population)?;
+        let target_population = find(target, &config.target_population)?;
+
+        let delay_steps = if dt_ms > 0.0 { config.delay_ms / dt_ms } else { 0.0 };
+        if delay_steps.is_nan() || delay_steps > MAX_DELAY_STEPS as f32 {
+            return Err(ModelError::InvalidDelay { projection: config.id.clone(), delay_ms: config.delay_ms });
+        }
+        let delay = math::round(delay_steps).max(1.0) as usize;
+
+        // Expand the connector as a projection between two populations laid
+        // out side by side, then shift back to population-local indices.
+        let populations = [
+            PopulationInfo { id: "source".to_string(), start: 0, size: source_population.size },
+            PopulationInfo { id: "target".to_string(), start: source_population.size, size: target_population.size },
+        ];
+        let projection = ProjectionDescription {
+            id: config.id.clone(),
+            pre: "source".to_string(),
+            post: "target".to_string(),
+            connector: config.connector.clone(),
+            weight: config.weight,
+            plasticity: SynapsePlasticity::Static,
+            synapse: SynapseKind::Chemical,
+        };
+        let expanded = projection.connect(&populations, &mut XorShift64::new(config.seed))?;
+
+        let mut synapses = vec![Vec::new(); source_population.size];
+        for (pre, post, weight) in expanded.connections {
+            synapses[pre].push((post - source_population.size, weight));
+        }
+
+        Ok(Self {
+            source_range: source_population.range(),
+            target_range: target_population.range(),
+            synapses,
+            pending: vec![vec![0.0; target_population.size]; delay],
+            head: 0,
+            config,
+        })
+    }
+
+    pub fn delay_steps(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn synapse_count(&self) -> usize {
+        self.synapses.iter().map(Vec::len).sum()
+    }
+
+    /// Adds the currents arriving this step to the target network's input.
+    pub fn deliver(&mut self, target_currents: &mut [f32]) {
+        let arriving = &mut self.pending[self.head];
+        for (current, value) in target_currents[self.target_range.clone()].iter_mut().zip(arriving.iter_mut()) {
+            *current += *value;
+            *value = 0.0;
+        }
+    }
+
+    /// Sends this step's spikes of the source network down the link and
+    /// advances the delay line by one step.
+    pub fn transmit(&mut self, source_spikes: &[bool]) {
+        let slot = &mut self.pending[self.head];
+        for (outgoing, &spiked) in self.synapses.iter().zip(&source_spikes[self.source_range.clone()]) {
+            if spiked {
+                for &(post, weight) in outgoing {
+                    slot[post] += weight;
+                }
+            }
+        }
+        self.head = (self.head + 1) % self.pending.len();
+    }
+
+    /// Drops the spikes in flight.
+    pub fn reset(&mut self) {
+        for slot in &mut self.pending {
+            slot.fill(0.0);
+        }
+        self.head = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ProjectionInfo;
+
+    fn layout(sizes: &[usize]) -> NetworkLayout {
+        let mut start = 0;
+        let populations = sizes.iter()
+            .enumerate()
+            .map(|(i, &size)| {
+                let population = PopulationInfo { id: alloc::format!("p{}", i), start, size };
+                start += size;
+                population
+            })
+            .collect();
+        NetworkLayout::new(String::new(), populations, Vec::<ProjectionInfo>::new())
+    }
+
+    fn link(delay_ms: f32, dt_ms: f32) -> Link {
+        let config = LinkConfig {
+            id: "l".to_string(),
+            source: "a".to_string(),
+            target: "b".to_string(),
+            source_population: "p1".to_string(),
+            target_population: "p0".to_string(),
+            connector: Connector::OneToOne,
+            weight: WeightDescription::Constant(0.5),
+            delay_ms,
+            seed: 0,
+        };
+        Link::new(config, &layout(&[2, 3]), &layout(&[3, 2]), dt_ms).unwrap()
+    }
+
+    /// Fires source neuron `fire` on step 0 and returns the target currents
+    /// delivered on each of the next `steps` steps.
+    fn deliveries(link: &mut Link, fire: usize, steps: usize) -> Vec<Vec<f32>> {
+        (0..steps)
+            .map(|step| {
+                let mut currents = vec![0.0; 5];
+                link.deliver(&mut currents);
+                let mut spikes = vec![false; 5];
+                spikes[fire] = step == 0;
+                link.transmit(&spikes);
+                currents
+            })
+            .collect()
+    }
+
+    #[test]
+    fn spikes_arrive_after_the_delay_on_the_target_population() {
+        let mut link = link(3.0, 1.0);
+        assert_eq!((link.delay_steps(), link.synapse_count()), (3, 3));
+        let arrivals = deliveries(&mut link, 3, 6);
+        for (step, currents) in arrivals.iter().enumerate() {
+            let expected = if step == 3 { vec![0.0, 0.5, 0.0, 0.0, 0.0] } else { vec![0.0; 5] };
+            assert_eq!(currents, &expected, "step {}", step);
+        }
+    }
+
+    #[test]
+    fn delays_round_to_whole_steps_of_at_least_one() {
+        assert_eq!(link(0.0, 1.0).delay_steps(), 1);
+        assert_eq!(link(2.6, 1.0).delay_steps(), 3);
+        assert_eq!(link(2.0, 0.5).delay_steps(), 4);
+        let arrivals = deliveries(&mut link(0.0, 1.0), 2, 2);
+        assert_eq!(arrivals[1], [0.5, 0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn reset_drops_spikes_in_flight() {
+        let mut link = link(2.0, 1.0);
+        let mut spikes = vec![false; 5];
+        spikes[4] = true;
+        link.transmit(&spikes);
+        link.reset();
+        let arrivals = deliveries(&mut link, 0, 3);
+        assert!(arrivals.iter().flatten().all(|&current| current == 0.0));
+    }
+
+    #[test]
+    fn unknown_populations_are_rejected() {
+        let mut config = link(1.0, 1.0).config;
+        config.target_population = "missing".to_string();
+        assert!(matches!(
+            Link::new(config, &layout(&[2, 3]), &layout(&[3, 2]), 1.0),
+            Err(ModelError::UnknownPopulation { .. })
+        ));
+    }
+
+    #[test]
+    fn delays_beyond_the_step_limit_are_rejected() {
+        let limit = MAX_DELAY_STEPS as f32;
+        assert_eq!(link(limit * 0.5, 0.5).delay_steps(), MAX_DELAY_STEPS);
+        for (delay_ms, dt_ms) in [(limit + 1.0, 1.0), (limit, 0.5), (f32::INFINITY, 1.0), (f32::NAN, 1.0)] {
+            let config = link(1.0, 1.0).config;
+            let config = LinkConfig { delay_ms, ..config };
+            assert!(matches!(
+                Link::new(config, &layout(&[2, 3]), &layout(&[3, 2]), dt_ms),
+                Err(ModelError::InvalidDelay { .. })
+            ), "{} ms at dt {}", delay_ms, dt_ms);
+        }
+    }
+}
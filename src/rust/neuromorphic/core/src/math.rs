@@ -0,0 +1,32 @@
+//! f32 functions that live in `std`; routed to `libm` for `no_std` builds,
+//! and on every target with the `deterministic` feature.
+
+#[cfg(all(feature = "std", not(feature = "deterministic")))]
+mod imp {
+    pub fn exp(x: f32) -> f32 { x.exp() }
+    pub fn ln(x: f32) -> f32 { x.ln() }
+    pub fn sqrt(x: f32) -> f32 { x.sqrt() }
+    pub fn sin(x: f32) -> f32 { x.sin() }
+    pub fn cos(x: f32) -> f32 { x.cos() }
+    pub fn round(x: f32) -> f32 { x.round() }
+    pub fn floor(x: f32) -> f32 { x.floor() }
+    pub fn powf(x: f32, n: f32) -> f32 { x.powf(n) }
+    pub fn round_f64(x: f64) -> f64 { x.round() }
+    pub fn sqrt_f64(x: f64) -> f64 { x.sqrt() }
+}
+
+#[cfg(any(not(feature = "std"), feature = "deterministic"))]
+mod imp {
+    pub fn exp(x: f32) -> f32 { libm::expf(x) }
+    pub fn ln(x: f32) -> f32 { libm::logf(x) }
+    pub fn sqrt(x: f32) -> f32 { libm::sqrtf(x) }
+    pub fn sin(x: f32) -> f32 { libm::sinf(x) }
+    pub fn cos(x: f32) -> f32 { libm::cosf(x) }
+    pub fn round(x: f32) -> f32 { libm::roundf(x) }
+    pub fn floor(x: f32) -> f32 { libm::floorf(x) }
+    pub fn powf(x: f32, n: f32) -> f32 { libm::powf(x, n) }
+    pub fn round_f64(x: f64) -> f64 { libm::round(x) }
+    pub fn sqrt_f64(x: f64) -> f64 { libm::sqrt(x) }
+}
+
+pub use imp::*;
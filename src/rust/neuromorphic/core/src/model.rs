@@ -0,0 +1,770 @@
+//! Declarative network description in the spirit of PyNN / NeuroML:
+//! populations of cells, projections between them with a connector and a
+//! weight distribution, and network-wide plasticity settings. The same
+//! schema is used for import and export.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+use serde::{Deserialize, Serialize};
+
+use crate::gap::GapJunctions;
+use crate::population::NeuronPopulation;
+use crate::rates::RateEstimatorConfig;
+use crate::rng::XorShift64;
+use crate::structural::{StructuralPlasticityConfig, CONNECTION_EPSILON};
+use crate::LifParameters;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NetworkDescription {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_dt_ms")]
+    pub dt_ms: f32,
+    /// Seed for probabilistic connectors and random weights.
+    #[serde(default)]
+    pub seed: u64,
+    pub populations: Vec<PopulationDescription>,
+    #[serde(default)]
+    pub projections: Vec<ProjectionDescription>,
+    #[serde(default)]
+    pub plasticity: PlasticityDescription,
+    #[serde(default)]
+    pub recurrence: RecurrenceDescription,
+}
+
+fn default_dt_ms() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PopulationDescription {
+    pub id: String,
+    pub size: usize,
+    #[serde(default)]
+    pub cell: CellDescription,
+    /// Optional per-neuron parameters; overrides `cell` when present.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cells: Vec<CellDescription>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum CellDescription {
+    Lif(LifParameters),
+}
+
+impl Default for CellDescription {
+    fn default() -> Self {
+        CellDescription::Lif(LifParameters::default())
+    }
+}
+
+impl CellDescription {
+    fn parameters(&self) -> LifParameters {
+        match self {
+            CellDescription::Lif(params) => *params,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectionDescription {
+    pub id: String,
+    pub pre: String,
+    pub post: String,
+    pub connector: Connector,
+    #[serde(default)]
+    pub weight: WeightDescription,
+    #[serde(default)]
+    pub plasticity: SynapsePlasticity,
+    #[serde(default)]
+    pub synapse: SynapseKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SynapseKind {
+    /// Weighted connections in the synaptic weight matrix.
+    #[default]
+    Chemical,
+    /// Bidirectional gap junctions; `weight` is the coupling conductance and
+    /// `plasticity` is ignored.
+    Electrical,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Connector {
+    AllToAll {
+        #[serde(default)]
+        allow_self_connections: bool,
+    },
+    OneToOne,
+    FixedProbability {
+        p: f32,
+        #[serde(default)]
+        allow_self_connections: bool,
+    },
+    /// `[pre, post, weight]` triples in population-local indices; the
+    /// projection's `weight` is ignored.
+    Explicit { connections: Vec<(usize, usize, f32)> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(untagged)]
+pub enum WeightDescription {
+    Constant(f32),
+    Uniform { low: f32, high: f32 },
+}
+
+impl Default for WeightDescription {
+    fn default() -> Self {
+        WeightDescription::Constant(0.1)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SynapsePlasticity {
+    Static,
+    /// The processor's rate-based Hebbian rule.
+    #[default]
+    Hebbian,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct PlasticityDescription {
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f32,
+    #[serde(default)]
+    pub structural: StructuralPlasticityConfig,
+}
+
+fn default_learning_rate() -> f32 {
+    0.01
+}
+
+impl Default for PlasticityDescription {
+    fn default() -> Self {
+        Self {
+            learning_rate: default_learning_rate(),
+            structural: StructuralPlasticityConfig::default(),
+        }
+    }
+}
+
+/// Recurrent drive of the processor: each presynaptic rate, from the
+/// `rates` estimator, is scaled by the weight and `gain`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RecurrenceDescription {
+    #[serde(default = "default_recurrent_gain")]
+    pub gain: f32,
+    #[serde(default)]
+    pub rates: RateEstimatorConfig,
+}
+
+fn default_recurrent_gain() -> f32 {
+    0.01
+}
+
+impl Default for RecurrenceDescription {
+    fn default() -> Self {
+        Self {
+            gain: default_recurrent_gain(),
+            rates: RateEstimatorConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelError {
+    EmptyNetwork,
+    DuplicateId(String),
+    UnknownPopulation { projection: String, population: String },
+    InvalidCell { population: String, index: usize },
+    CellCountMismatch { population: String, expected: usize, found: usize },
+    SizeMismatch { projection: String },
+    InvalidProbability { projection: String, p: f32 },
+    ConnectionOutOfRange { projection: String, pre: usize, post: usize },
+    InvalidWeight { projection: String },
+    InvalidDelay { projection: String, delay_ms: f32 },
+    InvalidPlasticity,
+    InvalidRecurrence,
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::EmptyNetwork => write!(f, "network has no neurons"),
+            ModelError::DuplicateId(id) => write!(f, "duplicate id '{}'", id),
+            ModelError::UnknownPopulation { projection, population } => {
+                write!(f, "projection '{}' refers to unknown population '{}'", projection, population)
+            }
+            ModelError::InvalidCell { population, index } => {
+                write!(f, "invalid cell parameters for neuron {} of population '{}'", index, population)
+            }
+            ModelError::CellCountMismatch { population, expected, found } => {
+                write!(f, "population '{}' lists {} cells but has size {}", population, found, expected)
+            }
+            ModelError::SizeMismatch { projection } => {
+                write!(f, "one_to_one projection '{}' joins populations of different sizes", projection)
+            }
+            ModelError::InvalidProbability { projection, p } => {
+                write!(f, "projection '{}' has connection probability {} outside [0, 1]", projection, p)
+            }
+            ModelError::ConnectionOutOfRange { projection, pre, post } => {
+                write!(f, "projection '{}' connects {} -> {} outside its populations", projection, pre, post)
+            }
+            ModelError::InvalidWeight { projection } => {
+                write!(f, "projection '{}' has a non-finite weight", projection)
+            }
+            ModelError::InvalidDelay { projection, delay_ms } => {
+                write!(f, "delay of {} ms on '{}' is not a finite number of steps within the limit", delay_ms, projection)
+            }
+            ModelError::InvalidPlasticity => write!(f, "invalid plasticity settings"),
+            ModelError::InvalidRecurrence => write!(f, "invalid recurrent gain or rate estimator"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PopulationInfo {
+    pub id: String,
+    pub start: usize,
+    pub size: usize,
+}
+
+impl PopulationInfo {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.start + self.size
+    }
+}
+
+/// A projection owns every synapse from its `pre` to its `post` population
+/// (the first projection listed wins if several join the same pair), so
+/// synapses added later by structural plasticity still belong to it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectionInfo {
+    pub id: String,
+    pub pre: usize,
+    pub post: usize,
+    pub plasticity: SynapsePlasticity,
+}
+
+/// Population and projection structure of a built network.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct NetworkLayout {
+    pub name: String,
+    pub populations: Vec<PopulationInfo>,
+    pub projections: Vec<ProjectionInfo>,
+    population_of: Vec<usize>,
+    /// `owner[pre_population][post_population]`: index of the owning projection.
+    owner: Vec<Vec<Option<usize>>>,
+}
+
+impl NetworkLayout {
+    /// Layout of an unstructured network: one population with a plastic
+    /// recurrent projection onto itself.
+    pub fn single(id: &str, size: usize) -> Self {
+        Self::new(
+            String::new(),
+            vec![PopulationInfo { id: id.to_string(), start: 0, size }],
+            vec![ProjectionInfo {
+                id: "recurrent".to_string(),
+                pre: 0,
+                post: 0,
+                plasticity: SynapsePlasticity::Hebbian,
+            }],
+        )
+    }
+
+    pub(crate) fn new(name: String, populations: Vec<PopulationInfo>, projections: Vec<ProjectionInfo>) -> Self {
+        let population_of = populations.iter()
+            .enumerate()
+            .flat_map(|(p, population)| core::iter::repeat_n(p, population.size))
+            .collect();
+
+        let mut owner = vec![vec![None; populations.len()]; populations.len()];
+        for (index, projection) in projections.iter().enumerate() {
+            owner[projection.pre][projection.post].get_or_insert(index);
+        }
+
+        Self { name, populations, projections, population_of, owner }
+    }
+
+    pub fn neuron_count(&self) -> usize {
+        self.population_of.len()
+    }
+
+    pub fn population_of(&self, neuron: usize) -> usize {
+        self.population_of[neuron]
+    }
+
+    /// Index of the projection that owns the synapse `pre -> post`, if any.
+    pub fn projection_of(&self, pre: usize, post: usize) -> Option<usize> {
+        self.owner[self.population_of[pre]][self.population_of[post]]
+    }
+
+    /// Synapses outside any declared projection are treated as plastic.
+    pub fn is_plastic(&self, pre: usize, post: usize) -> bool {
+        self.projection_of(pre, post)
+            .is_none_or(|p| self.projections[p].plasticity != SynapsePlasticity::Static)
+    }
+
+    /// Whether structural plasticity may create the synapse `pre -> post`:
+    /// only inside a declared, non-static projection.
+    pub fn can_grow(&self, pre: usize, post: usize) -> bool {
+        self.projection_of(pre, post)
+            .is_some_and(|p| self.projections[p].plasticity != SynapsePlasticity::Static)
+    }
+}
+
+/// Everything needed to construct a processor from a description.
+pub struct BuiltNetwork {
+    pub params: Vec<LifParameters>,
+    /// Indexed `[pre][post]`.
+    pub weights: Vec<Vec<f32>>,
+    pub gap_junctions: GapJunctions,
+    pub layout: NetworkLayout,
+    pub dt_ms: f32,
+    pub plasticity: PlasticityDescription,
+    pub recurrence: RecurrenceDescription,
+}
+
+/// A projection's connector expanded against the network's populations.
+pub struct ProjectionConnections {
+    /// Population indices.
+    pub pre: usize,
+    pub post: usize,
+    /// `(pre, post, weight)` in network indices.
+    pub connections: Vec<(usize, usize, f32)>,
+}
+
+impl ProjectionDescription {
+    pub fn connect(&self, populations: &[PopulationInfo], rng: &mut XorShift64) -> Result<ProjectionConnections, ModelError> {
+        let find = |id: &str| {
+            populations.iter().position(|p| p.id == id).ok_or_else(|| ModelError::UnknownPopulation {
+                projection: self.id.clone(),
+                population: id.to_string(),
+            })
+        };
+        let pre = find(&self.pre)?;
+        let post = find(&self.post)?;
+        let pre_range = populations[pre].range();
+        let post_range = populations[post].range();
+        let recurrent = pre == post;
+
+        let finite = match self.weight {
+            WeightDescription::Constant(w) => w.is_finite(),
+            WeightDescription::Uniform { low, high } => low.is_finite() && high.is_finite(),
+        };
+        let explicit_finite = match &self.connector {
+            Connector::Explicit { connections } => connections.iter().all(|&(_, _, w)| w.is_finite()),
+            _ => true,
+        };
+        if !finite || !explicit_finite {
+            return Err(ModelError::InvalidWeight { projection: self.id.clone() });
+        }
+
+        let sample_weight = |rng: &mut XorShift64| match self.weight {
+            WeightDescription::Constant(w) => w,
+            WeightDescription::Uniform { low, high } => low + (high - low) * rng.uniform(),
+        };
+
+        let mut connections = Vec::new();
+        match &self.connector {
+            Connector::AllToAll { allow_self_connections } => {
+                for i in pre_range.clone() {
+                    for j in post_range.clone() {
+                        if i != j || !recurrent || *allow_self_connections {
+                            connections.push((i, j, sample_weight(rng)));
+                        }
+                    }
+                }
+            }
+            Connector::OneToOne => {
+                if pre_range.len() != post_range.len() {
+                    return Err(ModelError::SizeMismatch { projection: self.id.clone() });
+                }
+                for (i, j) in pre_range.zip(post_range) {
+                    connections.push((i, j, sample_weight(rng)));
+                }
+            }
+            Connector::FixedProbability { p, allow_self_connections } => {
+                if !(0.0..=1.0).contains(p) {
+                    return Err(ModelError::InvalidProbability { projection: self.id.clone(), p: *p });
+                }
+                for i in pre_range.clone() {
+                    for j in post_range.clone() {
+                        if (i != j || !recurrent || *allow_self_connections) && rng.uniform() < *p {
+                            connections.push((i, j, sample_weight(rng)));
+                        }
+                    }
+                }
+            }
+            Connector::Explicit { connections: explicit } => {
+                for &(i, j, w) in explicit {
+                    if i >= pre_range.len() || j >= post_range.len() {
+                        return Err(ModelError::ConnectionOutOfRange { projection: self.id.clone(), pre: i, post: j });
+                    }
+                    connections.push((pre_range.start + i, post_range.start + j, w));
+                }
+            }
+        }
+
+        Ok(ProjectionConnections { pre, post, connections })
+    }
+}
+
+impl NetworkDescription {
+    pub fn build(&self) -> Result<BuiltNetwork, ModelError> {
+        if !self.plasticity.learning_rate.is_finite() || !self.plasticity.structural.is_valid() {
+            return Err(ModelError::InvalidPlasticity);
+        }
+        if !self.recurrence.gain.is_finite() || !self.recurrence.rates.is_valid(self.dt_ms) {
+            return Err(ModelError::InvalidRecurrence);
+        }
+
+        // Populations
+        let mut populations: Vec<PopulationInfo> = Vec::new();
+        let mut params = Vec::new();
+        for population in &self.populations {
+            if populations.iter().any(|p| p.id == population.id) {
+                return Err(ModelError::DuplicateId(population.id.clone()));
+            }
+            if !population.cells.is_empty() && population.cells.len() != population.size {
+                return Err(ModelError::CellCountMismatch {
+                    population: population.id.clone(),
+                    expected: population.size,
+                    found: population.cells.len(),
+                });
+            }
+
+            for index in 0..population.size {
+                let cell = population.cells.get(index).unwrap_or(&population.cell);
+                let mut cell_params = cell.parameters();
+                cell_params.dt_ms = self.dt_ms;
+                if !cell_params.is_valid() {
+                    return Err(ModelError::InvalidCell { population: population.id.clone(), index });
+                }
+                params.push(cell_params);
+            }
+
+            populations.push(PopulationInfo {
+                id: population.id.clone(),
+                start: params.len() - population.size,
+                size: population.size,
+            });
+        }
+        if params.is_empty() {
+            return Err(ModelError::EmptyNetwork);
+        }
+
+        // Projections
+        let n = params.len();
+        let mut weights = vec![vec![0.0; n]; n];
+        let mut projections: Vec<ProjectionInfo> = Vec::new();
+        let mut rng = XorShift64::new(self.seed);
+
+        let mut gap_junctions = GapJunctions::default();
+
+        for projection in &self.projections {
+            let duplicate = projections.iter().any(|p| p.id == projection.id)
+                || gap_junctions.projections.iter().any(|p| p.id == projection.id);
+            if duplicate {
+                return Err(ModelError::DuplicateId(projection.id.clone()));
+            }
+            let ProjectionConnections { pre, post, connections } = projection.connect(&populations, &mut rng)?;
+
+            match projection.synapse {
+                SynapseKind::Chemical => {
+                    for (i, j, w) in connections {
+                        weights[i][j] += w;
+                    }
+                    projections.push(ProjectionInfo {
+                        id: projection.id.clone(),
+                        pre,
+                        post,
+                        plasticity: projection.plasticity,
+                    });
+                }
+                SynapseKind::Electrical => {
+                    gap_junctions.add(projection.id.clone(), pre, post, &connections);
+                }
+            }
+        }
+
+        for row in &mut weights {
+            for w in row.iter_mut() {
+                *w = w.clamp(-1.0, 1.0);
+            }
+        }
+
+        Ok(BuiltNetwork {
+            params,
+            weights,
+            gap_junctions,
+            layout: NetworkLayout::new(self.name.clone(), populations, projections),
+            dt_ms: self.dt_ms,
+            plasticity: self.plasticity,
+            recurrence: self.recurrence,
+        })
+    }
+
+    /// Describes a running network. Cell parameters are collapsed to `cell`
+    /// when uniform within a population, and every projection is written
+    /// with an explicit connector holding the current weights. Synapses in
+    /// population pairs without a declared projection (e.g. grown by
+    /// structural plasticity) are exported as extra plastic projections,
+    /// named `{pre}_to_{post}` with a numeric suffix if that id is taken.
+    /// Gap junctions follow as electrical projections. `recurrence` is left
+    /// at its default for the caller to fill in.
+    pub fn from_network(
+        layout: &NetworkLayout,
+        neurons: &NeuronPopulation,
+        weights: &[Vec<f32>],
+        gap_junctions: &GapJunctions,
+        dt_ms: f32,
+        seed: u64,
+        plasticity: PlasticityDescription,
+    ) -> Self {
+        let populations = layout.populations.iter()
+            .map(|population| {
+                let cells: Vec<CellDescription> = population.range()
+                    .map(|i| CellDescription::Lif(*neurons.parameters(i)))
+                    .collect();
+                let uniform = cells.windows(2).all(|pair| {
+                    let (CellDescription::Lif(a), CellDescription::Lif(b)) = (pair[0], pair[1]);
+                    a == b
+                });
+                PopulationDescription {
+                    id: population.id.clone(),
+                    size: population.size,
+                    cell: cells.first().copied().unwrap_or_default(),
+                    cells: if uniform { Vec::new() } else { cells },
+                }
+            })
+            .collect();
+
+        let explicit = |pre: &PopulationInfo, post: &PopulationInfo| {
+            let mut connections = Vec::new();
+            for (i, row) in weights[pre.range()].iter().enumerate() {
+                for (j, &w) in row[post.range()].iter().enumerate() {
+                    if w.abs() > CONNECTION_EPSILON {
+                        connections.push((i, j, w));
+                    }
+                }
+            }
+            connections
+        };
+
+        let mut projections: Vec<ProjectionDescription> = Vec::new();
+        let mut covered = vec![vec![false; layout.populations.len()]; layout.populations.len()];
+        for projection in &layout.projections {
+            let pre = &layout.populations[projection.pre];
+            let post = &layout.populations[projection.post];
+            let owns = !covered[projection.pre][projection.post];
+            covered[projection.pre][projection.post] = true;
+            projections.push(ProjectionDescription {
+                id: projection.id.clone(),
+                pre: pre.id.clone(),
+                post: post.id.clone(),
+                connector: Connector::Explicit {
+                    connections: if owns { explicit(pre, post) } else { Vec::new() },
+                },
+                weight: WeightDescription::default(),
+                plasticity: projection.plasticity,
+                synapse: SynapseKind::Chemical,
+            });
+        }
+
+        for (p, pre) in layout.populations.iter().enumerate() {
+            for (q, post) in layout.populations.iter().enumerate() {
+                if covered[p][q] {
+                    continue;
+                }
+                let connections = explicit(pre, post);
+                if !connections.is_empty() {
+                    let taken = |id: &str| {
+                        projections.iter().any(|projection| projection.id == id)
+                            || gap_junctions.projections.iter().any(|projection| projection.id == id)
+                    };
+                    let base = format!("{}_to_{}", pre.id, post.id);
+                    let id = (1..)
+                        .map(|k| if k == 1 { base.clone() } else { format!("{}_{}", base, k) })
+                        .find(|id| !taken(id))
+                        .expect("unbounded suffixes");
+                    projections.push(ProjectionDescription {
+                        id,
+                        pre: pre.id.clone(),
+                        post: post.id.clone(),
+                        connector: Connector::Explicit { connections },
+                        weight: WeightDescription::default(),
+                        plasticity: SynapsePlasticity::Hebbian,
+                        synapse: SynapseKind::Chemical,
+                    });
+                }
+            }
+        }
+
+        for projection in &gap_junctions.projections {
+            let pre = &layout.populations[projection.pre];
+            let post = &layout.populations[projection.post];
+            let connections = projection.junctions.iter()
+                .map(|junction| {
+                    // Stored pairs may be reversed relative to pre/post
+                    let (a, b) = if pre.range().contains(&junction.a) && post.range().contains(&junction.b) {
+                        (junction.a, junction.b)
+                    } else {
+                        (junction.b, junction.a)
+                    };
+                    (a - pre.start, b - post.start, junction.conductance)
+                })
+                .collect();
+            projections.push(ProjectionDescription {
+                id: projection.id.clone(),
+                pre: pre.id.clone(),
+                post: post.id.clone(),
+                connector: Connector::Explicit { connections },
+                weight: WeightDescription::default(),
+                plasticity: SynapsePlasticity::Static,
+                synapse: SynapseKind::Electrical,
+            });
+        }
+
+        Self {
+            name: layout.name.clone(),
+            dt_ms,
+            seed,
+            populations,
+            projections,
+            plasticity,
+            recurrence: RecurrenceDescription::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn description(json: &str) -> NetworkDescription {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn population(built: &BuiltNetwork) -> NeuronPopulation {
+        let mut neurons = NeuronPopulation::new();
+        for &params in &built.params {
+            neurons.push(params);
+        }
+        neurons
+    }
+
+    fn export(built: &BuiltNetwork, seed: u64) -> NetworkDescription {
+        NetworkDescription::from_network(
+            &built.layout, &population(built), &built.weights, &built.gap_junctions, built.dt_ms, seed, built.plasticity,
+        )
+    }
+
+    const NETWORK: &str = r#"{
+        "name": "two_layer",
+        "dt_ms": 0.5,
+        "seed": 42,
+        "populations": [
+            {"id": "input", "size": 6, "cell": {"model": "lif", "v_threshold": -52.0}},
+            {"id": "hidden", "size": 4}
+        ],
+        "projections": [
+            {"id": "feedforward", "pre": "input", "post": "hidden",
+             "connector": {"kind": "fixed_probability", "p": 0.5}, "weight": {"low": -0.4, "high": 0.8}},
+            {"id": "recurrent", "pre": "hidden", "post": "hidden",
+             "connector": {"kind": "all_to_all"}, "weight": 0.2, "plasticity": "static"},
+            {"id": "coupling", "pre": "hidden", "post": "hidden",
+             "connector": {"kind": "all_to_all"}, "weight": 0.1, "synapse": "electrical"}
+        ]
+    }"#;
+
+    #[test]
+    fn export_then_import_rebuilds_the_same_network() {
+        let built = description(NETWORK).build().unwrap();
+        let exported = export(&built, 42);
+        assert_eq!(exported.seed, 42);
+        let rebuilt = exported.build().unwrap();
+
+        assert_eq!(rebuilt.params, built.params);
+        assert_eq!(rebuilt.weights, built.weights);
+        assert_eq!(rebuilt.dt_ms, 0.5);
+        assert_eq!(rebuilt.gap_junctions.projections.len(), 1);
+        assert_eq!(rebuilt.gap_junctions.projections[0].junctions, built.gap_junctions.projections[0].junctions);
+        assert_eq!(rebuilt.gap_junctions.junction_count(), 6);
+        let ids: Vec<&str> = rebuilt.layout.projections.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["feedforward", "recurrent"]);
+        assert!(!rebuilt.layout.is_plastic(7, 8));
+        assert!(rebuilt.layout.is_plastic(0, 7));
+    }
+
+    #[test]
+    fn stochastic_connectors_follow_the_seed() {
+        let weights = |seed: u64| {
+            let mut network = description(NETWORK);
+            network.seed = seed;
+            network.build().unwrap().weights
+        };
+        assert_eq!(weights(42), weights(42));
+        assert_ne!(weights(42), weights(1042));
+    }
+
+    #[test]
+    fn generated_projection_ids_do_not_collide() {
+        let mut built = description(r#"{
+            "populations": [{"id": "a", "size": 2}, {"id": "b", "size": 2}],
+            "projections": [
+                {"id": "a_to_b", "pre": "a", "post": "a", "connector": {"kind": "one_to_one"}, "weight": 0.3}
+            ]
+        }"#).build().unwrap();
+        // A synapse grown between populations without a projection
+        built.weights[0][3] = 0.5;
+        let exported = export(&built, 0);
+        let ids: Vec<&str> = exported.projections.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["a_to_b", "a_to_b_2"]);
+        assert_eq!(exported.build().unwrap().weights, built.weights);
+    }
+
+    #[test]
+    fn invalid_descriptions_are_rejected() {
+        let mut network = description(NETWORK);
+        network.projections[0].weight = WeightDescription::Constant(f32::NAN);
+        assert!(matches!(network.build(), Err(ModelError::InvalidWeight { .. })));
+
+        let mut network = description(NETWORK);
+        network.projections[1].connector = Connector::Explicit { connections: vec![(0, 1, f32::INFINITY)] };
+        assert!(matches!(network.build(), Err(ModelError::InvalidWeight { .. })));
+
+        let mut network = description(NETWORK);
+        network.plasticity.structural.max_fan_in = 0;
+        assert!(matches!(network.build(), Err(ModelError::InvalidPlasticity)));
+
+        let mut network = description(NETWORK);
+        network.projections[1].id = "feedforward".to_string();
+        assert!(matches!(network.build(), Err(ModelError::DuplicateId(_))));
+    }
+
+    #[test]
+    fn recurrence_defaults_and_validation() {
+        let minimal = description(r#"{"populations": [{"id": "a", "size": 2}]}"#);
+        assert_eq!(minimal.recurrence, RecurrenceDescription::default());
+        assert_eq!(minimal.build().unwrap().recurrence.gain, 0.01);
+
+        let invalid = description(r#"{"populations": [{"id": "a", "size": 2}],
+                                      "recurrence": {"rates": {"kind": "window", "window_ms": 0}}}"#);
+        assert!(matches!(invalid.build(), Err(ModelError::InvalidRecurrence)));
+
+        let oversized = description(r#"{"populations": [{"id": "a", "size": 2}],
+                                        "recurrence": {"rates": {"kind": "window", "window_ms": 1e12}}}"#);
+        assert!(matches!(oversized.build(), Err(ModelError::InvalidRecurrence)));
+    }
+}
@@ -0,0 +1,294 @@
+//! Global neuromodulators. A modulator is a named scalar level driven from
+//! outside the network that relaxes towards a baseline; bindings map its
+//! level through a transfer function onto input gain, firing threshold or
+//! learning rate of selected neurons.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::model::NetworkLayout;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModulatorConfig {
+    pub name: String,
+    /// Resting level, also the initial level.
+    #[serde(default)]
+    pub baseline: f32,
+    /// Relaxation time constant towards `baseline`; 0 holds the level until
+    /// it is set again.
+    #[serde(default)]
+    pub tau_ms: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModulatedParameter {
+    /// Multiplies the total input current.
+    Gain,
+    /// Added to the firing threshold (mV).
+    Threshold,
+    /// Multiplies the learning rate of synapses onto the neuron.
+    LearningRate,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TransferFunction {
+    /// `offset + slope * level`
+    Linear { slope: f32, offset: f32 },
+    /// Logistic curve from `low` to `high`, centred on `midpoint`.
+    Sigmoid { low: f32, high: f32, midpoint: f32, steepness: f32 },
+    /// `scale * exp(rate * level)`; suited to multiplicative parameters.
+    Exponential { scale: f32, rate: f32 },
+}
+
+impl TransferFunction {
+    pub fn apply(&self, level: f32) -> f32 {
+        match *self {
+            TransferFunction::Linear { slope, offset } => offset + slope * level,
+            TransferFunction::Sigmoid { low, high, midpoint, steepness } => {
+                low + (high - low) / (1.0 + math::exp(-steepness * (level - midpoint)))
+            }
+            TransferFunction::Exponential { scale, rate } => scale * math::exp(rate * level),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ModulationTarget {
+    #[default]
+    All,
+    /// Every neuron of the named populations.
+    Populations { ids: Vec<String> },
+    /// Neurons `start..end`.
+    Range { start: usize, end: usize },
+    Neurons { indices: Vec<usize> },
+}
+
+impl ModulationTarget {
+    /// Neuron indices selected by the target, or `None` if it names an
+    /// unknown population.
+    pub(crate) fn indices(&self, layout: &NetworkLayout) -> Option<Vec<usize>> {
+        let network_size = layout.neuron_count();
+        let indices = match self {
+            ModulationTarget::All => (0..network_size).collect(),
+            ModulationTarget::Populations { ids } => {
+                let mut indices = Vec::new();
+                for id in ids {
+                    let population = layout.populations.iter().find(|p| &p.id == id)?;
+                    indices.extend(population.range());
+                }
+                indices
+            }
+            ModulationTarget::Range { start, end } => (*start..(*end).min(network_size)).collect(),
+            ModulationTarget::Neurons { indices } => indices.iter()
+                .copied()
+                .filter(|&i| i < network_size)
+                .collect(),
+        };
+        Some(indices)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModulatorBinding {
+    pub modulator: String,
+    pub parameter: ModulatedParameter,
+    pub transfer: TransferFunction,
+    #[serde(default)]
+    pub target: ModulationTarget,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Modulator {
+    config: ModulatorConfig,
+    level: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Binding {
+    id: u32,
+    config: ModulatorBinding,
+    targets: Vec<usize>,
+}
+
+/// Per-neuron parameter changes produced by the current modulator levels.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ModulationEffects {
+    pub gain: Vec<f32>,
+    pub threshold_offset: Vec<f32>,
+    pub learning_rate: Vec<f32>,
+}
+
+impl ModulationEffects {
+    fn neutral(network_size: usize) -> Self {
+        Self {
+            gain: vec![1.0; network_size],
+            threshold_offset: vec![0.0; network_size],
+            learning_rate: vec![1.0; network_size],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ModulationSet {
+    modulators: Vec<Modulator>,
+    bindings: Vec<Binding>,
+    next_id: u32,
+}
+
+impl ModulationSet {
+    /// Defines a modulator, or redefines it and resets it to its baseline.
+    /// Returns false for a negative time constant.
+    pub fn define(&mut self, config: ModulatorConfig) -> bool {
+        if config.tau_ms.is_nan() || config.tau_ms < 0.0 {
+            return false;
+        }
+        let level = config.baseline;
+        match self.modulators.iter_mut().find(|m| m.config.name == config.name) {
+            Some(modulator) => *modulator = Modulator { config, level },
+            None => self.modulators.push(Modulator { config, level }),
+        }
+        true
+    }
+
+    /// Removes a modulator together with its bindings.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.modulators.len();
+        self.modulators.retain(|m| m.config.name != name);
+        self.bindings.retain(|b| b.config.modulator != name);
+        self.modulators.len() != before
+    }
+
+    pub fn set_level(&mut self, name: &str, level: f32) -> bool {
+        match self.modulators.iter_mut().find(|m| m.config.name == name) {
+            Some(modulator) => {
+                modulator.level = level;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn level(&self, name: &str) -> Option<f32> {
+        self.modulators.iter().find(|m| m.config.name == name).map(|m| m.level)
+    }
+
+    /// Binds a defined modulator to a parameter and returns the binding id.
+    pub fn bind(&mut self, config: ModulatorBinding, layout: &NetworkLayout) -> Option<u32> {
+        self.level(&config.modulator)?;
+        let targets = config.target.indices(layout)?;
+        if targets.is_empty() {
+            return None;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.bindings.push(Binding { id, config, targets });
+        Some(id)
+    }
+
+    pub fn unbind(&mut self, id: u32) -> bool {
+        let before = self.bindings.len();
+        self.bindings.retain(|binding| binding.id != id);
+        self.bindings.len() != before
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// Relaxes every modulator towards its baseline over `dt_ms`.
+    pub fn advance(&mut self, dt_ms: f32) {
+        for modulator in &mut self.modulators {
+            if modulator.config.tau_ms > 0.0 {
+                let decay = math::exp(-dt_ms / modulator.config.tau_ms);
+                let baseline = modulator.config.baseline;
+                modulator.level = baseline + (modulator.level - baseline) * decay;
+            }
+        }
+    }
+
+    /// Combines all bindings: gains and learning-rate factors multiply,
+    /// threshold offsets add.
+    pub fn effects(&self, network_size: usize) -> ModulationEffects {
+        let mut effects = ModulationEffects::neutral(network_size);
+        for binding in &self.bindings {
+            let Some(level) = self.level(&binding.config.modulator) else {
+                continue;
+            };
+            let value = binding.config.transfer.apply(level);
+            for &i in &binding.targets {
+                match binding.config.parameter {
+                    ModulatedParameter::Gain => effects.gain[i] *= value,
+                    ModulatedParameter::Threshold => effects.threshold_offset[i] += value,
+                    ModulatedParameter::LearningRate => effects.learning_rate[i] *= value,
+                }
+            }
+        }
+        effects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn modulator(tau_ms: f32) -> ModulatorConfig {
+        ModulatorConfig { name: "dopamine".to_string(), baseline: 0.0, tau_ms }
+    }
+
+    fn binding(parameter: ModulatedParameter, transfer: TransferFunction, target: ModulationTarget) -> ModulatorBinding {
+        ModulatorBinding { modulator: "dopamine".to_string(), parameter, transfer, target }
+    }
+
+    #[test]
+    fn levels_relax_exponentially_to_baseline() {
+        let mut set = ModulationSet::default();
+        assert!(set.define(modulator(10.0)));
+        set.set_level("dopamine", 1.0);
+        for _ in 0..10 {
+            set.advance(1.0);
+        }
+        assert!((set.level("dopamine").unwrap() - math::exp(-1.0)).abs() < 1e-5);
+        assert!(!set.define(modulator(-1.0)));
+    }
+
+    #[test]
+    fn bindings_combine_per_neuron() {
+        let layout = NetworkLayout::single("network", 4);
+        let mut set = ModulationSet::default();
+        set.define(modulator(0.0));
+        set.set_level("dopamine", 2.0);
+        let linear = TransferFunction::Linear { slope: 0.5, offset: 1.0 };
+        set.bind(binding(ModulatedParameter::Gain, linear, ModulationTarget::All), &layout).unwrap();
+        set.bind(binding(ModulatedParameter::Gain, linear, ModulationTarget::Range { start: 2, end: 9 }), &layout).unwrap();
+        set.bind(binding(ModulatedParameter::Threshold, linear, ModulationTarget::Neurons { indices: vec![1] }), &layout)
+            .unwrap();
+
+        let effects = set.effects(4);
+        assert_eq!(effects.gain, [2.0, 2.0, 4.0, 4.0]);
+        assert_eq!(effects.threshold_offset, [0.0, 2.0, 0.0, 0.0]);
+        assert_eq!(effects.learning_rate, [1.0; 4]);
+    }
+
+    #[test]
+    fn bindings_need_a_modulator_and_known_targets() {
+        let layout = NetworkLayout::single("network", 4);
+        let mut set = ModulationSet::default();
+        let gain = TransferFunction::Exponential { scale: 1.0, rate: 1.0 };
+        assert!(set.bind(binding(ModulatedParameter::Gain, gain, ModulationTarget::All), &layout).is_none());
+        set.define(modulator(0.0));
+        let unknown = ModulationTarget::Populations { ids: vec!["cortex".to_string()] };
+        assert!(set.bind(binding(ModulatedParameter::Gain, gain, unknown), &layout).is_none());
+        let id = set.bind(binding(ModulatedParameter::Gain, gain, ModulationTarget::All), &layout).unwrap();
+        assert!(set.remove("dopamine"));
+        assert!(!set.unbind(id));
+        assert!(set.is_empty());
+    }
+}
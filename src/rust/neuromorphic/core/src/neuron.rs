@@ -0,0 +1,290 @@
+use alloc::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::population::SPIKE_HISTORY_CAPACITY;
+
+/// LIF parameters in physical units. Potentials are in mV, time in ms and
+/// `resistance` maps input current to a steady-state depolarisation
+/// (MΩ × nA = mV).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct LifParameters {
+    pub tau_m_ms: f32,
+    pub v_rest: f32,
+    pub v_reset: f32,
+    pub v_threshold: f32,
+    pub resistance: f32,
+    pub refractory_ms: f32,
+    pub dt_ms: f32,
+}
+
+impl Default for LifParameters {
+    fn default() -> Self {
+        // Classic cortical LIF values
+        Self {
+            tau_m_ms: 20.0,
+            v_rest: -65.0,
+            v_reset: -65.0,
+            v_threshold: -50.0,
+            resistance: 10.0,
+            refractory_ms: 2.0,
+            dt_ms: 1.0,
+        }
+    }
+}
+
+impl LifParameters {
+    /// Equivalent of the original dimensionless model (per-step `leak_rate`,
+    /// rest and reset at 0, 5-step refractory period) at a 1 ms step.
+    pub fn from_dimensionless(threshold: f32, leak_rate: f32) -> Self {
+        let leak_rate = leak_rate.clamp(f32::EPSILON, 1.0 - f32::EPSILON);
+        Self {
+            tau_m_ms: -1.0 / math::ln(1.0 - leak_rate),
+            v_rest: 0.0,
+            v_reset: 0.0,
+            v_threshold: threshold,
+            resistance: 1.0 / leak_rate,
+            refractory_ms: 5.0,
+            dt_ms: 1.0,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let values = [
+            self.tau_m_ms,
+            self.v_rest,
+            self.v_reset,
+            self.v_threshold,
+            self.resistance,
+            self.refractory_ms,
+            self.dt_ms,
+        ];
+        values.iter().all(|value| value.is_finite())
+            && self.tau_m_ms > 0.0
+            && self.dt_ms > 0.0
+            && self.refractory_ms >= 0.0
+            && self.v_reset < self.v_threshold
+    }
+}
+
+/// Storage for a neuron's recent spike times. Implementations keep at most a
+/// fixed number of entries, dropping the oldest first.
+pub trait SpikeHistory {
+    fn record(&mut self, timestep: u64);
+    /// Recorded spike times, oldest first.
+    fn times(&self) -> impl Iterator<Item = u64> + '_;
+}
+
+impl SpikeHistory for VecDeque<u64> {
+    fn record(&mut self, timestep: u64) {
+        self.push_back(timestep);
+        
+        // Keep history manageable
+        if self.len() > SPIKE_HISTORY_CAPACITY {
+            self.pop_front();
+        }
+    }
+    
+    fn times(&self) -> impl Iterator<Item = u64> + '_ {
+        self.iter().copied()
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> SpikeHistory for heapless::HistoryBuffer<u64, N> {
+    fn record(&mut self, timestep: u64) {
+        self.write(timestep);
+    }
+    
+    fn times(&self) -> impl Iterator<Item = u64> + '_ {
+        self.oldest_ordered().copied()
+    }
+}
+
+/// Neuron whose spike history lives inline in a fixed-capacity ring buffer,
+/// for targets without a heap.
+#[cfg(feature = "heapless")]
+pub type HeaplessLifNeuron<const N: usize> = LeakyIntegrateFireNeuron<heapless::HistoryBuffer<u64, N>>;
+
+pub struct LeakyIntegrateFireNeuron<H: SpikeHistory = VecDeque<u64>> {
+    params: LifParameters,
+    membrane_potential: f32,
+    decay: f32,
+    refractory_steps: u32,
+    refractory_counter: u32,
+    spike_history: H,
+}
+
+impl LeakyIntegrateFireNeuron {
+    pub fn new(threshold: f32, leak_rate: f32) -> Self {
+        Self::with_parameters(LifParameters::from_dimensionless(threshold, leak_rate))
+    }
+    
+    pub fn with_parameters(params: LifParameters) -> Self {
+        Self::with_history(params, VecDeque::with_capacity(SPIKE_HISTORY_CAPACITY))
+    }
+}
+
+impl<H: SpikeHistory> LeakyIntegrateFireNeuron<H> {
+    pub fn with_history(params: LifParameters, spike_history: H) -> Self {
+        let mut neuron = Self {
+            params,
+            membrane_potential: params.v_rest,
+            decay: 0.0,
+            refractory_steps: 0,
+            refractory_counter: 0,
+            spike_history,
+        };
+        neuron.set_parameters(params);
+        neuron
+    }
+    
+    /// Replaces the parameters and recomputes the per-step propagators.
+    pub fn set_parameters(&mut self, params: LifParameters) {
+        self.params = params;
+        self.decay = math::exp(-params.dt_ms / params.tau_m_ms);
+        self.refractory_steps = math::round(params.refractory_ms / params.dt_ms) as u32;
+        self.refractory_counter = self.refractory_counter.min(self.refractory_steps);
+    }
+    
+    pub fn parameters(&self) -> &LifParameters {
+        &self.params
+    }
+    
+    pub fn threshold(&self) -> f32 {
+        self.params.v_threshold
+    }
+    
+    pub fn membrane_potential(&self) -> f32 {
+        self.membrane_potential
+    }
+    
+    pub fn step(&mut self, input_current: f32, timestep: u64) -> bool {
+        // Refractory period handling
+        if self.refractory_counter > 0 {
+            self.refractory_counter -= 1;
+            self.membrane_potential = self.params.v_reset;
+            return false;
+        }
+        
+        // Exact integration of tau_m dV/dt = -(V - V_rest) + R I for input
+        // held constant over the step
+        let v_inf = self.params.v_rest + self.params.resistance * input_current;
+        self.membrane_potential = v_inf + (self.membrane_potential - v_inf) * self.decay;
+        
+        // Check for spike
+        if self.membrane_potential >= self.params.v_threshold {
+            self.membrane_potential = self.params.v_reset;
+            self.refractory_counter = self.refractory_steps;
+            self.spike_history.record(timestep);
+            true
+        } else {
+            false
+        }
+    }
+    
+    /// Rate over the window `(current_time - window_ms, current_time]`.
+    pub fn get_firing_rate(&self, window_ms: u64, current_time: u64) -> f32 {
+        let window_start = current_time.saturating_sub(window_ms);
+        let recent_spikes = self.spike_history.times()
+            .filter(|&spike_time| spike_time > window_start && spike_time <= current_time)
+            .count();
+        
+        (recent_spikes as f32 / window_ms as f32) * 1000.0 // spikes per second
+    }
+    
+    pub fn spike_history(&self) -> impl Iterator<Item = u64> + '_ {
+        self.spike_history.times()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::NeuronPopulation;
+
+    #[test]
+    fn dimensionless_parameters_reproduce_the_original_update() {
+        let (threshold, leak_rate) = (1.2, 0.13);
+        let mut neurons = NeuronPopulation::new();
+        neurons.push(LifParameters::from_dimensionless(threshold, leak_rate));
+
+        // Original model: V <- V (1 - leak) + I, reset to 0 for 5 steps
+        let (mut v, mut refractory) = (0.0f32, 0);
+        let mut spikes = [false];
+        for t in 0..200 {
+            let input = 0.05 + 0.1 * (t % 7) as f32;
+            let expected_spike = if refractory > 0 {
+                refractory -= 1;
+                v = 0.0;
+                false
+            } else {
+                v = v * (1.0 - leak_rate) + input;
+                let spiked = v >= threshold;
+                if spiked {
+                    v = 0.0;
+                    refractory = 5;
+                }
+                spiked
+            };
+            neurons.step(&[input], t, &mut spikes, 1);
+            assert_eq!(spikes[0], expected_spike, "step {}", t);
+            assert!((neurons.membrane_potential(0) - v).abs() < 1e-5, "step {}", t);
+        }
+    }
+
+    #[test]
+    fn non_finite_parameters_are_invalid() {
+        assert!(LifParameters::default().is_valid());
+        assert!(!LifParameters { tau_m_ms: -1.0, ..Default::default() }.is_valid());
+        assert!(!LifParameters { v_reset: -40.0, ..Default::default() }.is_valid());
+        let setters: [fn(&mut LifParameters, f32); 7] = [
+            |p, x| p.tau_m_ms = x,
+            |p, x| p.v_rest = x,
+            |p, x| p.v_reset = x,
+            |p, x| p.v_threshold = x,
+            |p, x| p.resistance = x,
+            |p, x| p.refractory_ms = x,
+            |p, x| p.dt_ms = x,
+        ];
+        for (field, set) in setters.iter().enumerate() {
+            for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+                let mut params = LifParameters::default();
+                set(&mut params, value);
+                assert!(!params.is_valid(), "field {} = {}", field, value);
+            }
+        }
+    }
+
+    /// Steps a standalone neuron alongside a one-neuron population for long
+    /// enough to overflow the spike history.
+    fn assert_matches_population<H: SpikeHistory>(mut neuron: LeakyIntegrateFireNeuron<H>) {
+        let mut neurons = NeuronPopulation::new();
+        neurons.push(*neuron.parameters());
+        let mut spikes = [false];
+        for t in 0..2000 {
+            let input = 0.3 + 0.2 * (t % 5) as f32;
+            neurons.step(&[input], t, &mut spikes, 1);
+            assert_eq!(neuron.step(input, t), spikes[0], "step {}", t);
+            assert_eq!(neuron.membrane_potential(), neurons.membrane_potential(0), "step {}", t);
+        }
+        assert!(neurons.spike_history(0).count() == SPIKE_HISTORY_CAPACITY);
+        assert!(neuron.spike_history().eq(neurons.spike_history(0)));
+        assert_eq!(neuron.get_firing_rate(100, 2000), neurons.get_firing_rate(0, 100, 2000));
+    }
+
+    #[test]
+    fn standalone_neuron_matches_the_population_kernel() {
+        assert_matches_population(LeakyIntegrateFireNeuron::new(1.2, 0.13));
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn heapless_neuron_matches_the_population_kernel() {
+        let params = LifParameters::from_dimensionless(1.2, 0.13);
+        let neuron: HeaplessLifNeuron<SPIKE_HISTORY_CAPACITY> =
+            LeakyIntegrateFireNeuron::with_history(params, heapless::HistoryBuffer::new());
+        assert_matches_population(neuron);
+    }
+}
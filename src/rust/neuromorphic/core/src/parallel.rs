@@ -0,0 +1,160 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Below this many neurons per worker the cost of spawning threads outweighs
+/// the work, so partitions are merged.
+pub const MIN_NEURONS_PER_THREAD: usize = 512;
+
+/// Partition boundaries are multiples of this so every worker sees the same
+/// SIMD/scalar lane split as the single-threaded kernel.
+const LANE_ALIGNMENT: usize = 4;
+
+/// Threads available to the native backend. Always 1 on wasm, where the
+/// module runs on a single JS thread, and without `std`.
+pub fn available_threads() -> usize {
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    }
+    #[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+    {
+        1
+    }
+}
+
+/// Resolves a requested thread count: 0 means "all cores", anything else is
+/// capped at what the platform offers.
+pub fn resolve_thread_count(requested: usize) -> usize {
+    let available = available_threads();
+    if requested == 0 {
+        available
+    } else {
+        requested.min(available)
+    }
+}
+
+/// Splits `0..len` into at most `threads` contiguous, lane-aligned ranges.
+pub fn partition(len: usize, threads: usize) -> Vec<Range<usize>> {
+    let threads = threads.min(len / MIN_NEURONS_PER_THREAD).max(1);
+    let chunk = (len.div_ceil(threads).div_ceil(LANE_ALIGNMENT) * LANE_ALIGNMENT).max(LANE_ALIGNMENT);
+    (0..len)
+        .step_by(chunk)
+        .map(|start| start..(start + chunk).min(len))
+        .collect()
+}
+
+/// Adds `scale * weights[pre][post] * rates[pre]` into `currents[post]` for
+/// every existing synapse. Work is split by post-synaptic range and each
+/// neuron sums its inputs in pre-synaptic order, so the result does not
+/// depend on the thread count. Returns the number of synaptic operations
+/// (synapses with an active presynaptic neuron).
+pub fn accumulate_recurrent(
+    weights: &[Vec<f32>],
+    rates: &[f32],
+    scale: f32,
+    epsilon: f32,
+    currents: &mut [f32],
+    threads: usize,
+) -> u64 {
+    let accumulate = |offset: usize, currents: &mut [f32]| {
+        let mut operations = 0;
+        for (pre, row) in weights.iter().enumerate() {
+            if rates[pre] == 0.0 {
+                continue;
+            }
+            let targets = &row[offset..offset + currents.len()];
+            for (k, (current, &weight)) in currents.iter_mut().zip(targets).enumerate() {
+                if offset + k != pre && weight.abs() > epsilon {
+                    *current += weight * rates[pre] * scale;
+                    operations += 1;
+                }
+            }
+        }
+        operations
+    };
+
+    let ranges = partition(currents.len(), threads);
+    if ranges.len() <= 1 {
+        return accumulate(0, currents);
+    }
+
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    return std::thread::scope(|scope| {
+        let mut rest = currents;
+        let mut workers = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            let (head, tail) = rest.split_at_mut(range.len());
+            rest = tail;
+            workers.push(scope.spawn(move || accumulate(range.start, head)));
+        }
+        workers.into_iter().map(|worker| worker.join().expect("accumulate worker panicked")).sum()
+    });
+
+    #[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+    accumulate(0, currents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::NeuronPopulation;
+    use crate::rng::XorShift64;
+    use crate::LifParameters;
+    use alloc::vec;
+
+    const NEURONS: usize = 4 * MIN_NEURONS_PER_THREAD + 37;
+
+    #[test]
+    fn partitions_are_lane_aligned_and_cover_the_range() {
+        let ranges = partition(NEURONS, 4);
+        assert_eq!(ranges.len(), 4);
+        assert_eq!(ranges.first().unwrap().start, 0);
+        assert_eq!(ranges.last().unwrap().end, NEURONS);
+        for pair in ranges.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+            assert_eq!(pair[0].end % LANE_ALIGNMENT, 0);
+        }
+        assert_eq!(partition(100, 8).len(), 1);
+    }
+
+    #[test]
+    fn results_do_not_depend_on_the_thread_count() {
+        let mut rng = XorShift64::new(5);
+        let weights: Vec<Vec<f32>> = (0..NEURONS)
+            .map(|_| {
+                (0..NEURONS)
+                    .map(|_| if rng.uniform() < 0.02 { rng.uniform() - 0.5 } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+        let mut single = NeuronPopulation::new();
+        for i in 0..NEURONS {
+            single.push(LifParameters { tau_m_ms: 10.0 + (i % 17) as f32, ..LifParameters::default() });
+        }
+        let mut threaded = single.clone();
+        let mut rates = vec![0.0; NEURONS];
+
+        for t in 0..50 {
+            let drive: Vec<f32> = (0..NEURONS).map(|_| 2.0 * rng.uniform()).collect();
+            let mut currents = [drive.clone(), drive];
+            let operations = [1, 4].map(|threads| {
+                let index = usize::from(threads > 1);
+                accumulate_recurrent(&weights, &rates, 0.05, 1e-3, &mut currents[index], threads)
+            });
+            assert_eq!(operations[0], operations[1]);
+            assert_eq!(currents[0], currents[1], "currents differ at step {}", t);
+
+            let mut spikes = [vec![false; NEURONS], vec![false; NEURONS]];
+            let counts = [
+                single.step(&currents[0], t, &mut spikes[0], 1),
+                threaded.step(&currents[1], t, &mut spikes[1], 4),
+            ];
+            assert_eq!(counts[0], counts[1]);
+            assert_eq!(spikes[0], spikes[1], "spikes differ at step {}", t);
+            for (rate, &spike) in rates.iter_mut().zip(&spikes[0]) {
+                *rate = if spike { 100.0 } else { *rate * 0.9 };
+            }
+        }
+        assert!((0..NEURONS).all(|i| single.membrane_potential(i) == threaded.membrane_potential(i)));
+    }
+}
@@ -0,0 +1,504 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::parallel;
+use crate::LifParameters;
+
+/// Spikes remembered per neuron for rate and ISI estimates.
+pub const SPIKE_HISTORY_CAPACITY: usize = 100;
+
+/// Per-neuron LIF state stored as contiguous arrays so the per-timestep update
+/// can run as a SIMD kernel. Behaves like a `Vec<LeakyIntegrateFireNeuron>`
+/// stepped synchronously.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct NeuronPopulation {
+    params: Vec<LifParameters>,
+    kernel: LifArrays,
+    // Spike history: one fixed-size ring per neuron in a single flat buffer
+    spike_times: Vec<u64>,
+    spike_start: Vec<u32>,
+    spike_count: Vec<u32>,
+}
+
+/// Kernel operands. Refractory counters are kept as f32 so they can share
+/// SIMD lanes with the potentials; they only ever hold small integers.
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct LifArrays {
+    membrane: Vec<f32>,
+    v_rest: Vec<f32>,
+    v_reset: Vec<f32>,
+    v_threshold: Vec<f32>,
+    resistance: Vec<f32>,
+    decay: Vec<f32>,
+    refractory: Vec<f32>,
+    refractory_steps: Vec<f32>,
+    /// 1.0 for neurons that spiked on the last step, 0.0 otherwise.
+    spiked: Vec<f32>,
+}
+
+impl NeuronPopulation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, params: LifParameters) {
+        let k = &mut self.kernel;
+        k.membrane.push(params.v_rest);
+        k.v_rest.push(0.0);
+        k.v_reset.push(0.0);
+        k.v_threshold.push(0.0);
+        k.resistance.push(0.0);
+        k.decay.push(0.0);
+        k.refractory.push(0.0);
+        k.refractory_steps.push(0.0);
+        k.spiked.push(0.0);
+        self.params.push(params);
+        self.spike_times.extend(core::iter::repeat_n(0, SPIKE_HISTORY_CAPACITY));
+        self.spike_start.push(0);
+        self.spike_count.push(0);
+        self.set_parameters(self.params.len() - 1, params);
+    }
+
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Replaces the parameters of neuron `i` and recomputes its propagators.
+    pub fn set_parameters(&mut self, i: usize, params: LifParameters) {
+        let k = &mut self.kernel;
+        self.params[i] = params;
+        k.v_rest[i] = params.v_rest;
+        k.v_reset[i] = params.v_reset;
+        k.v_threshold[i] = params.v_threshold;
+        k.resistance[i] = params.resistance;
+        k.decay[i] = math::exp(-params.dt_ms / params.tau_m_ms);
+        k.refractory_steps[i] = math::round(params.refractory_ms / params.dt_ms);
+        k.refractory[i] = k.refractory[i].min(k.refractory_steps[i]);
+    }
+
+    pub fn parameters(&self, i: usize) -> &LifParameters {
+        &self.params[i]
+    }
+
+    pub fn threshold(&self, i: usize) -> f32 {
+        self.kernel.v_threshold[i]
+    }
+
+    /// Overrides the effective threshold of neuron `i` without touching its
+    /// parameters, e.g. for neuromodulation; `set_parameters` restores it.
+    pub fn set_threshold(&mut self, i: usize, v_threshold: f32) {
+        self.kernel.v_threshold[i] = v_threshold;
+    }
+
+    pub fn membrane_potential(&self, i: usize) -> f32 {
+        self.kernel.membrane[i]
+    }
+
+    pub fn refractory_remaining(&self, i: usize) -> u32 {
+        self.kernel.refractory[i] as u32
+    }
+
+    /// Overwrites the dynamic state of neuron `i`, e.g. to mirror a
+    /// fixed-point simulation.
+    pub fn set_state(&mut self, i: usize, membrane: f32, refractory_remaining: u32) {
+        self.kernel.membrane[i] = membrane;
+        self.kernel.refractory[i] = refractory_remaining as f32;
+    }
+
+    /// Advances every neuron by one step with `inputs[i]` held constant,
+    /// writes spike flags into `spikes` and returns the number of spikes.
+    /// The update is split across up to `threads` workers; spikes are
+    /// recorded in neuron order once all of them finish, so the result does
+    /// not depend on the thread count.
+    pub fn step(&mut self, inputs: &[f32], timestep: u64, spikes: &mut [bool], threads: usize) -> usize {
+        assert_eq!(inputs.len(), self.len());
+        assert_eq!(spikes.len(), self.len());
+
+        let ranges = parallel::partition(self.len(), threads);
+        if ranges.len() <= 1 {
+            self.kernel.lanes().integrate(inputs);
+        } else {
+            integrate_partitioned(self.kernel.lanes(), inputs, &ranges);
+        }
+
+        for (spike, &flag) in spikes.iter_mut().zip(&self.kernel.spiked) {
+            *spike = flag != 0.0;
+        }
+        self.record_spikes(spikes, timestep)
+    }
+
+    /// Appends `timestep` to the history of every neuron flagged in `spikes`
+    /// and returns the number of spikes.
+    pub fn record_spikes(&mut self, spikes: &[bool], timestep: u64) -> usize {
+        let mut count = 0;
+        for (i, &spike) in spikes.iter().enumerate() {
+            if spike {
+                self.record_spike(i, timestep);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    fn record_spike(&mut self, i: usize, timestep: u64) {
+        let ring = &mut self.spike_times[i * SPIKE_HISTORY_CAPACITY..(i + 1) * SPIKE_HISTORY_CAPACITY];
+        let start = self.spike_start[i] as usize;
+        let count = self.spike_count[i] as usize;
+        if count < SPIKE_HISTORY_CAPACITY {
+            ring[(start + count) % SPIKE_HISTORY_CAPACITY] = timestep;
+            self.spike_count[i] += 1;
+        } else {
+            ring[start] = timestep;
+            self.spike_start[i] = ((start + 1) % SPIKE_HISTORY_CAPACITY) as u32;
+        }
+    }
+
+    /// Spike times of neuron `i`, oldest first.
+    pub fn spike_history(&self, i: usize) -> impl Iterator<Item = u64> + '_ {
+        let ring = &self.spike_times[i * SPIKE_HISTORY_CAPACITY..(i + 1) * SPIKE_HISTORY_CAPACITY];
+        let start = self.spike_start[i] as usize;
+        (0..self.spike_count[i] as usize).map(move |k| ring[(start + k) % SPIKE_HISTORY_CAPACITY])
+    }
+
+    /// Rate of neuron `i` over the window `(current_time - window_ms,
+    /// current_time]`, the same half-open window the network statistics bin.
+    pub fn get_firing_rate(&self, i: usize, window_ms: u64, current_time: u64) -> f32 {
+        let window_start = current_time.saturating_sub(window_ms);
+        let recent_spikes = self.spike_history(i)
+            .filter(|&spike_time| spike_time > window_start && spike_time <= current_time)
+            .count();
+
+        (recent_spikes as f32 / window_ms as f32) * 1000.0 // spikes per second
+    }
+
+    pub fn firing_rates(&self, window_ms: u64, current_time: u64) -> Vec<f32> {
+        (0..self.len())
+            .map(|i| self.get_firing_rate(i, window_ms, current_time))
+            .collect()
+    }
+}
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+fn integrate_partitioned(mut lanes: LifLanes<'_>, mut inputs: &[f32], ranges: &[Range<usize>]) {
+    std::thread::scope(|scope| {
+        for range in ranges {
+            let (mut head, tail) = lanes.split_at(range.len());
+            let (head_inputs, tail_inputs) = inputs.split_at(range.len());
+            lanes = tail;
+            inputs = tail_inputs;
+            scope.spawn(move || head.integrate(head_inputs));
+        }
+    });
+}
+
+#[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+fn integrate_partitioned(mut lanes: LifLanes<'_>, inputs: &[f32], _ranges: &[Range<usize>]) {
+    lanes.integrate(inputs);
+}
+
+impl LifArrays {
+    fn lanes(&mut self) -> LifLanes<'_> {
+        LifLanes {
+            membrane: &mut self.membrane,
+            v_rest: &self.v_rest,
+            v_reset: &self.v_reset,
+            v_threshold: &self.v_threshold,
+            resistance: &self.resistance,
+            decay: &self.decay,
+            refractory: &mut self.refractory,
+            refractory_steps: &self.refractory_steps,
+            spiked: &mut self.spiked,
+        }
+    }
+}
+
+/// View over a contiguous range of neurons: the unit of work for the SIMD
+/// kernel and for each worker of the threaded backend.
+struct LifLanes<'a> {
+    membrane: &'a mut [f32],
+    v_rest: &'a [f32],
+    v_reset: &'a [f32],
+    v_threshold: &'a [f32],
+    resistance: &'a [f32],
+    decay: &'a [f32],
+    refractory: &'a mut [f32],
+    refractory_steps: &'a [f32],
+    spiked: &'a mut [f32],
+}
+
+impl<'a> LifLanes<'a> {
+    #[cfg_attr(not(all(feature = "std", not(target_arch = "wasm32"))), allow(dead_code))]
+    fn split_at(self, mid: usize) -> (LifLanes<'a>, LifLanes<'a>) {
+        let (membrane, membrane_tail) = self.membrane.split_at_mut(mid);
+        let (v_rest, v_rest_tail) = self.v_rest.split_at(mid);
+        let (v_reset, v_reset_tail) = self.v_reset.split_at(mid);
+        let (v_threshold, v_threshold_tail) = self.v_threshold.split_at(mid);
+        let (resistance, resistance_tail) = self.resistance.split_at(mid);
+        let (decay, decay_tail) = self.decay.split_at(mid);
+        let (refractory, refractory_tail) = self.refractory.split_at_mut(mid);
+        let (refractory_steps, refractory_steps_tail) = self.refractory_steps.split_at(mid);
+        let (spiked, spiked_tail) = self.spiked.split_at_mut(mid);
+        (
+            LifLanes { membrane, v_rest, v_reset, v_threshold, resistance, decay, refractory, refractory_steps, spiked },
+            LifLanes {
+                membrane: membrane_tail,
+                v_rest: v_rest_tail,
+                v_reset: v_reset_tail,
+                v_threshold: v_threshold_tail,
+                resistance: resistance_tail,
+                decay: decay_tail,
+                refractory: refractory_tail,
+                refractory_steps: refractory_steps_tail,
+                spiked: spiked_tail,
+            },
+        )
+    }
+
+    fn integrate(&mut self, inputs: &[f32]) {
+        let simd_len = self.integrate_simd(inputs);
+        self.integrate_scalar(inputs, simd_len);
+    }
+
+    /// Reference update for lanes `from..`, identical to
+    /// `LeakyIntegrateFireNeuron::step`.
+    #[allow(clippy::needless_range_loop)]
+    fn integrate_scalar(&mut self, inputs: &[f32], from: usize) {
+        for i in from..self.membrane.len() {
+            if self.refractory[i] > 0.0 {
+                self.refractory[i] -= 1.0;
+                self.membrane[i] = self.v_reset[i];
+                self.spiked[i] = 0.0;
+                continue;
+            }
+
+            let v_inf = self.v_rest[i] + self.resistance[i] * inputs[i];
+            let v = v_inf + (self.membrane[i] - v_inf) * self.decay[i];
+            if v >= self.v_threshold[i] {
+                self.membrane[i] = self.v_reset[i];
+                self.refractory[i] = self.refractory_steps[i];
+                self.spiked[i] = 1.0;
+            } else {
+                self.membrane[i] = v;
+                self.spiked[i] = 0.0;
+            }
+        }
+    }
+
+    /// Processes whole 4-lane chunks and returns how many neurons were handled.
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    fn integrate_simd(&mut self, inputs: &[f32]) -> usize {
+        use core::arch::wasm32::*;
+
+        let chunks = self.membrane.len() / 4;
+        let zero = f32x4_splat(0.0);
+        let one = f32x4_splat(1.0);
+
+        for c in 0..chunks {
+            let o = c * 4;
+            // SAFETY: every array has `membrane.len()` elements and `o + 4 <= len`;
+            // v128 loads and stores have no alignment requirement.
+            unsafe {
+                let load = |s: &[f32]| v128_load(s.as_ptr().add(o) as *const v128);
+                let v = load(self.membrane);
+                let refractory = load(self.refractory);
+                let v_reset = load(self.v_reset);
+
+                let active = f32x4_le(refractory, zero);
+                let refractory = f32x4_max(f32x4_sub(refractory, one), zero);
+
+                let v_inf = f32x4_add(load(self.v_rest), f32x4_mul(load(self.resistance), load(inputs)));
+                let integrated = f32x4_add(v_inf, f32x4_mul(f32x4_sub(v, v_inf), load(self.decay)));
+                let v = v128_bitselect(integrated, v_reset, active);
+
+                let spike = v128_and(active, f32x4_ge(v, load(self.v_threshold)));
+                let v = v128_bitselect(v_reset, v, spike);
+                let refractory = v128_bitselect(load(self.refractory_steps), refractory, spike);
+
+                v128_store(self.membrane.as_mut_ptr().add(o) as *mut v128, v);
+                v128_store(self.refractory.as_mut_ptr().add(o) as *mut v128, refractory);
+                v128_store(self.spiked.as_mut_ptr().add(o) as *mut v128, v128_and(spike, one));
+            }
+        }
+        chunks * 4
+    }
+
+    /// Processes whole 4-lane chunks and returns how many neurons were handled.
+    #[cfg(target_arch = "x86_64")]
+    fn integrate_simd(&mut self, inputs: &[f32]) -> usize {
+        use core::arch::x86_64::*;
+
+        let chunks = self.membrane.len() / 4;
+
+        // SAFETY: SSE2 is part of the x86_64 baseline; every array has
+        // `membrane.len()` elements and `o + 4 <= len` for each chunk.
+        unsafe {
+            let zero = _mm_set1_ps(0.0);
+            let one = _mm_set1_ps(1.0);
+            let select = |mask: __m128, a: __m128, b: __m128| {
+                _mm_or_ps(_mm_and_ps(mask, a), _mm_andnot_ps(mask, b))
+            };
+
+            for c in 0..chunks {
+                let o = c * 4;
+                let load = |s: &[f32]| _mm_loadu_ps(s.as_ptr().add(o));
+                let v = load(self.membrane);
+                let refractory = load(self.refractory);
+                let v_reset = load(self.v_reset);
+
+                let active = _mm_cmple_ps(refractory, zero);
+                let refractory = _mm_max_ps(_mm_sub_ps(refractory, one), zero);
+
+                let v_inf = _mm_add_ps(load(self.v_rest), _mm_mul_ps(load(self.resistance), load(inputs)));
+                let integrated = _mm_add_ps(v_inf, _mm_mul_ps(_mm_sub_ps(v, v_inf), load(self.decay)));
+                let v = select(active, integrated, v_reset);
+
+                let spike = _mm_and_ps(active, _mm_cmpge_ps(v, load(self.v_threshold)));
+                let v = select(spike, v_reset, v);
+                let refractory = select(spike, load(self.refractory_steps), refractory);
+
+                _mm_storeu_ps(self.membrane.as_mut_ptr().add(o), v);
+                _mm_storeu_ps(self.refractory.as_mut_ptr().add(o), refractory);
+                _mm_storeu_ps(self.spiked.as_mut_ptr().add(o), _mm_and_ps(spike, one));
+            }
+        }
+        chunks * 4
+    }
+
+    #[cfg(not(any(
+        all(target_arch = "wasm32", target_feature = "simd128"),
+        target_arch = "x86_64",
+    )))]
+    fn integrate_simd(&mut self, _inputs: &[f32]) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::XorShift64;
+
+    /// Population with varied parameters and a mix of refractory states.
+    fn varied_population(n: usize, rng: &mut XorShift64) -> NeuronPopulation {
+        let mut neurons = NeuronPopulation::new();
+        for i in 0..n {
+            neurons.push(LifParameters {
+                tau_m_ms: 5.0 + 30.0 * rng.uniform(),
+                v_threshold: -55.0 + 10.0 * rng.uniform(),
+                resistance: 5.0 + 10.0 * rng.uniform(),
+                refractory_ms: (i % 4) as f32,
+                ..LifParameters::default()
+            });
+        }
+        neurons
+    }
+
+    fn random_inputs(n: usize, rng: &mut XorShift64) -> Vec<f32> {
+        (0..n).map(|_| 3.0 * rng.uniform()).collect()
+    }
+
+    #[test]
+    fn simd_kernel_matches_scalar_reference() {
+        for n in [1, 3, 4, 5, 7, 13, 64, 103] {
+            let mut rng = XorShift64::new(n as u64);
+            let mut simd = varied_population(n, &mut rng);
+            let mut scalar = simd.clone();
+            for _ in 0..200 {
+                let inputs = random_inputs(n, &mut rng);
+                simd.kernel.lanes().integrate(&inputs);
+                scalar.kernel.lanes().integrate_scalar(&inputs, 0);
+                assert_eq!(simd.kernel.membrane, scalar.kernel.membrane, "n = {}", n);
+                assert_eq!(simd.kernel.refractory, scalar.kernel.refractory, "n = {}", n);
+                assert_eq!(simd.kernel.spiked, scalar.kernel.spiked, "n = {}", n);
+            }
+        }
+    }
+
+    /// Throughput of the SIMD kernel against the scalar reference; run with
+    /// `cargo test --release -p neuromorphic-core -- --ignored --nocapture`.
+    #[cfg(feature = "std")]
+    #[test]
+    #[ignore]
+    fn kernel_throughput() {
+        use std::time::Instant;
+
+        const NEURONS: usize = 4099;
+        const STEPS: usize = 2000;
+        let mut rng = XorShift64::new(11);
+        let mut neurons = varied_population(NEURONS, &mut rng);
+        let inputs = random_inputs(NEURONS, &mut rng);
+
+        let time = |neurons: &mut NeuronPopulation, simd: bool| {
+            let start = Instant::now();
+            for _ in 0..STEPS {
+                let mut lanes = neurons.kernel.lanes();
+                if simd {
+                    lanes.integrate(&inputs);
+                } else {
+                    lanes.integrate_scalar(&inputs, 0);
+                }
+            }
+            start.elapsed().as_secs_f64()
+        };
+        let scalar = time(&mut neurons, false);
+        let simd = time(&mut neurons, true);
+        let rate = |seconds: f64| (NEURONS * STEPS) as f64 / seconds / 1e6;
+        std::println!(
+            "scalar {:.1} M neuron-steps/s, simd {:.1} M neuron-steps/s ({:.2}x)",
+            rate(scalar), rate(simd), scalar / simd
+        );
+    }
+
+    #[test]
+    fn integration_is_exact_for_constant_input() {
+        let params = LifParameters { v_threshold: 100.0, ..LifParameters::default() };
+        let mut neurons = NeuronPopulation::new();
+        neurons.push(params);
+        let mut spikes = [false];
+        let current = 1.2;
+        let v_inf = params.v_rest + params.resistance * current;
+        for t in 1..=40 {
+            neurons.step(&[current], t, &mut spikes, 1);
+            let expected = v_inf + (params.v_rest - v_inf) * math::exp(-(t as f32) * params.dt_ms / params.tau_m_ms);
+            assert!((neurons.membrane_potential(0) - expected).abs() < 1e-3, "t = {}", t);
+        }
+    }
+
+    #[test]
+    fn refractory_period_follows_the_time_constants() {
+        let params = LifParameters { refractory_ms: 3.0, dt_ms: 0.5, ..LifParameters::default() };
+        let mut neurons = NeuronPopulation::new();
+        neurons.push(params);
+        let mut spikes = [false];
+        let spike_steps: Vec<u64> = (0..20)
+            .filter(|&t| {
+                neurons.step(&[100.0], t, &mut spikes, 1);
+                spikes[0]
+            })
+            .collect();
+        // A strong input fires on the first step out of 6 refractory steps
+        assert_eq!(spike_steps, [0, 7, 14]);
+    }
+
+    #[test]
+    fn firing_rate_windows_exclude_their_start() {
+        let params = LifParameters { refractory_ms: 3.0, dt_ms: 0.5, ..LifParameters::default() };
+        let mut neurons = NeuronPopulation::new();
+        neurons.push(params);
+        let mut spikes = [false];
+        for t in 0..20 {
+            neurons.step(&[100.0], t, &mut spikes, 1);
+        }
+        // Spikes at 0, 7 and 14: (7, 14] holds only the one at 14
+        assert_eq!(neurons.get_firing_rate(0, 7, 14), 1000.0 / 7.0);
+        assert_eq!(neurons.get_firing_rate(0, 14, 14), 2000.0 / 14.0);
+        assert_eq!(neurons.get_firing_rate(0, 7, 13), 1000.0 / 7.0);
+        assert_eq!(neurons.get_firing_rate(0, 6, 13), 0.0);
+    }
+}
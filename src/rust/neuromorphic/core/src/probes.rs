@@ -0,0 +1,205 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::population::NeuronPopulation;
+
+/// Upper bound on a single probe's buffer, whatever the caller asks for.
+pub const MAX_PROBE_CAPACITY: usize = 100_000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProbeTarget {
+    MembranePotential { neuron: usize },
+    Refractory { neuron: usize },
+    Threshold { neuron: usize },
+    SynapseWeight { pre: usize, post: usize },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct ProbeConfig {
+    pub target: ProbeTarget,
+    /// Record every `sampling_interval` timesteps.
+    #[serde(default = "default_sampling_interval")]
+    pub sampling_interval: u64,
+    /// Maximum number of samples kept; the oldest are dropped first.
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
+}
+
+fn default_sampling_interval() -> u64 {
+    1
+}
+
+fn default_capacity() -> usize {
+    1000
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Probe {
+    pub id: u32,
+    pub config: ProbeConfig,
+    times: VecDeque<u64>,
+    values: VecDeque<f32>,
+    steps_until_sample: u64,
+}
+
+impl Probe {
+    fn record(&mut self, time: u64, value: f32) {
+        if self.values.len() >= self.config.capacity {
+            self.times.pop_front();
+            self.values.pop_front();
+        }
+        self.times.push_back(time);
+        self.values.push_back(value);
+    }
+
+    pub fn values(&self) -> Vec<f32> {
+        self.values.iter().copied().collect()
+    }
+
+    /// Values in one slice, oldest first; rearranges the ring buffer in
+    /// place instead of copying it out.
+    pub fn contiguous_values(&mut self) -> &[f32] {
+        self.values.make_contiguous()
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn times(&self) -> Vec<f64> {
+        self.times.iter().map(|&t| t as f64).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.times.clear();
+        self.values.clear();
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ProbeSet {
+    probes: Vec<Probe>,
+    next_id: u32,
+}
+
+impl ProbeSet {
+    /// Attaches a probe and returns its id, or `None` if the target is out of range.
+    pub fn attach(&mut self, mut config: ProbeConfig, network_size: usize) -> Option<u32> {
+        let in_range = match config.target {
+            ProbeTarget::MembranePotential { neuron }
+            | ProbeTarget::Refractory { neuron }
+            | ProbeTarget::Threshold { neuron } => neuron < network_size,
+            ProbeTarget::SynapseWeight { pre, post } => pre < network_size && post < network_size,
+        };
+        if !in_range {
+            return None;
+        }
+
+        config.sampling_interval = config.sampling_interval.max(1);
+        config.capacity = config.capacity.clamp(1, MAX_PROBE_CAPACITY);
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.probes.push(Probe {
+            id,
+            config,
+            times: VecDeque::with_capacity(config.capacity.min(1024)),
+            values: VecDeque::with_capacity(config.capacity.min(1024)),
+            steps_until_sample: 0,
+        });
+        Some(id)
+    }
+
+    pub fn detach(&mut self, id: u32) -> bool {
+        let before = self.probes.len();
+        self.probes.retain(|probe| probe.id != id);
+        self.probes.len() != before
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Probe> {
+        self.probes.iter().find(|probe| probe.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut Probe> {
+        self.probes.iter_mut().find(|probe| probe.id == id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.probes.is_empty()
+    }
+
+    /// Called once per simulated timestep, after the neurons have been stepped.
+    pub fn sample(&mut self, neurons: &NeuronPopulation, weights: &[Vec<f32>], time: u64) {
+        for probe in &mut self.probes {
+            if probe.steps_until_sample > 0 {
+                probe.steps_until_sample -= 1;
+                continue;
+            }
+            probe.steps_until_sample = probe.config.sampling_interval - 1;
+
+            let value = match probe.config.target {
+                ProbeTarget::MembranePotential { neuron } => neurons.membrane_potential(neuron),
+                ProbeTarget::Refractory { neuron } => neurons.refractory_remaining(neuron) as f32,
+                ProbeTarget::Threshold { neuron } => neurons.threshold(neuron),
+                ProbeTarget::SynapseWeight { pre, post } => weights[pre][post],
+            };
+            probe.record(time, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LifParameters;
+    use alloc::vec;
+
+    fn config(target: ProbeTarget, sampling_interval: u64, capacity: usize) -> ProbeConfig {
+        ProbeConfig { target, sampling_interval, capacity }
+    }
+
+    #[test]
+    fn out_of_range_targets_are_rejected() {
+        let mut probes = ProbeSet::default();
+        assert!(probes.attach(config(ProbeTarget::MembranePotential { neuron: 3 }, 1, 10), 3).is_none());
+        assert!(probes.attach(config(ProbeTarget::SynapseWeight { pre: 0, post: 3 }, 1, 10), 3).is_none());
+        assert_eq!(probes.attach(config(ProbeTarget::Threshold { neuron: 2 }, 1, 10), 3), Some(0));
+    }
+
+    #[test]
+    fn samples_follow_the_interval_and_capacity() {
+        let mut neurons = NeuronPopulation::new();
+        neurons.push(LifParameters::default());
+        let weights = vec![vec![0.25]];
+        let mut probes = ProbeSet::default();
+        let id = probes.attach(config(ProbeTarget::SynapseWeight { pre: 0, post: 0 }, 3, 2), 1).unwrap();
+
+        for t in 0..10 {
+            probes.sample(&neurons, &weights, t);
+        }
+        let probe = probes.get_mut(id).unwrap();
+        // Samples at t = 0, 3, 6, 9; the capacity keeps the last two
+        assert_eq!(probe.times(), [6.0, 9.0]);
+        assert_eq!(probe.contiguous_values(), [0.25, 0.25]);
+    }
+
+    #[test]
+    fn membrane_probe_tracks_the_population() {
+        let mut neurons = NeuronPopulation::new();
+        neurons.push(LifParameters::default());
+        let mut probes = ProbeSet::default();
+        let id = probes.attach(config(ProbeTarget::MembranePotential { neuron: 0 }, 1, 100), 1).unwrap();
+
+        let mut spikes = [false];
+        for t in 0..5 {
+            neurons.step(&[1.0], t, &mut spikes, 1);
+            probes.sample(&neurons, &[vec![0.0]], t);
+        }
+        let values = probes.get(id).unwrap().values();
+        assert_eq!(values.len(), 5);
+        assert!(values.windows(2).all(|pair| pair[1] > pair[0]), "potential rises under constant input: {:?}", values);
+        assert_eq!(values[4], neurons.membrane_potential(0));
+    }
+}
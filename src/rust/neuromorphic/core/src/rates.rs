@@ -0,0 +1,204 @@
+//! Incremental firing-rate estimators. Each is updated once per step from
+//! the step's spikes and answers rate queries in O(1), so rates needed
+//! inside the recurrent loop cost nothing per synapse and do not saturate
+//! at high activity the way the bounded spike histories do.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+
+/// Longest window or bin an estimator may span, in steps; a window keeps
+/// one list of spiking neurons per step.
+pub const MAX_WINDOW_STEPS: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RateEstimatorConfig {
+    /// Exponentially decaying trace: each spike adds `1000 / tau_ms` Hz and
+    /// the trace decays with time constant `tau_ms`.
+    Exponential { tau_ms: f32 },
+    /// Spike count of the last completed bin of `bin_ms`.
+    Bins { bin_ms: f32 },
+    /// Exact spike count over the last `window_ms`, including this step.
+    Window { window_ms: f32 },
+}
+
+impl Default for RateEstimatorConfig {
+    fn default() -> Self {
+        RateEstimatorConfig::Window { window_ms: 10.0 }
+    }
+}
+
+impl RateEstimatorConfig {
+    /// Whether the config can run at a step of `dt_ms`: a positive, finite
+    /// span, and for bins and windows at most `MAX_WINDOW_STEPS` steps.
+    pub fn is_valid(&self, dt_ms: f32) -> bool {
+        let (span, stepped) = match *self {
+            RateEstimatorConfig::Exponential { tau_ms } => (tau_ms, false),
+            RateEstimatorConfig::Bins { bin_ms } => (bin_ms, true),
+            RateEstimatorConfig::Window { window_ms } => (window_ms, true),
+        };
+        span.is_finite()
+            && span > 0.0
+            && dt_ms > 0.0
+            && (!stepped || math::round(span / dt_ms) <= MAX_WINDOW_STEPS as f32)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+enum EstimatorState {
+    Exponential { decay: f32, increment: f32 },
+    Bins { steps: usize, elapsed: usize, counts: Vec<u32> },
+    /// Neurons that spiked on each of the last steps, oldest at `head`.
+    Window { counts: Vec<u32>, spikers: Vec<Vec<u32>>, head: usize },
+}
+
+/// Firing rates in Hz of a population, per `RateEstimatorConfig`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RateEstimator {
+    pub config: RateEstimatorConfig,
+    dt_ms: f32,
+    rates: Vec<f32>,
+    state: EstimatorState,
+}
+
+impl RateEstimator {
+    /// Expects a config valid at `dt_ms`; longer spans are cut to
+    /// `MAX_WINDOW_STEPS`.
+    pub fn new(config: RateEstimatorConfig, neurons: usize, dt_ms: f32) -> Self {
+        let steps = |span_ms: f32| (math::round(span_ms / dt_ms) as usize).clamp(1, MAX_WINDOW_STEPS);
+        let state = match config {
+            RateEstimatorConfig::Exponential { tau_ms } => EstimatorState::Exponential {
+                decay: math::exp(-dt_ms / tau_ms),
+                increment: 1000.0 / tau_ms,
+            },
+            RateEstimatorConfig::Bins { bin_ms } => EstimatorState::Bins {
+                steps: steps(bin_ms),
+                elapsed: 0,
+                counts: vec![0; neurons],
+            },
+            RateEstimatorConfig::Window { window_ms } => EstimatorState::Window {
+                counts: vec![0; neurons],
+                spikers: vec![Vec::new(); steps(window_ms)],
+                head: 0,
+            },
+        };
+        Self { config, dt_ms, rates: vec![0.0; neurons], state }
+    }
+
+    /// Same configuration, cleared, for a new population size or step.
+    pub fn rebuild(&self, neurons: usize, dt_ms: f32) -> Self {
+        Self::new(self.config, neurons, dt_ms)
+    }
+
+    pub fn rates(&self) -> &[f32] {
+        &self.rates
+    }
+
+    pub fn rate(&self, neuron: usize) -> f32 {
+        self.rates[neuron]
+    }
+
+    /// Advances by one step in which the neurons flagged in `spikes` fired.
+    pub fn update(&mut self, spikes: &[bool]) {
+        let dt_ms = self.dt_ms;
+        match &mut self.state {
+            EstimatorState::Exponential { decay, increment } => {
+                for (rate, &spike) in self.rates.iter_mut().zip(spikes) {
+                    *rate = *rate * *decay + if spike { *increment } else { 0.0 };
+                }
+            }
+            EstimatorState::Bins { steps, elapsed, counts } => {
+                for (count, &spike) in counts.iter_mut().zip(spikes) {
+                    *count += spike as u32;
+                }
+                *elapsed += 1;
+                if *elapsed == *steps {
+                    let scale = 1000.0 / (*steps as f32 * dt_ms);
+                    for (rate, count) in self.rates.iter_mut().zip(counts.iter_mut()) {
+                        *rate = *count as f32 * scale;
+                        *count = 0;
+                    }
+                    *elapsed = 0;
+                }
+            }
+            EstimatorState::Window { counts, spikers, head } => {
+                let scale = 1000.0 / (spikers.len() as f32 * dt_ms);
+                let slot = &mut spikers[*head];
+                for &i in slot.iter() {
+                    counts[i as usize] -= 1;
+                    self.rates[i as usize] = counts[i as usize] as f32 * scale;
+                }
+                slot.clear();
+                for (i, _) in spikes.iter().enumerate().filter(|(_, &spike)| spike) {
+                    counts[i] += 1;
+                    self.rates[i] = counts[i] as f32 * scale;
+                    slot.push(i as u32);
+                }
+                *head = (*head + 1) % spikers.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(config: RateEstimatorConfig, pattern: &[bool]) -> Vec<f32> {
+        let mut estimator = RateEstimator::new(config, 1, 1.0);
+        pattern.iter()
+            .map(|&spike| {
+                estimator.update(&[spike]);
+                estimator.rate(0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn window_counts_exactly_the_last_steps() {
+        let pattern = [true, false, true, false, false, false, false];
+        let rates = run(RateEstimatorConfig::Window { window_ms: 4.0 }, &pattern);
+        // 1 spike per 4 ms is 250 Hz
+        assert_eq!(rates, [250.0, 250.0, 500.0, 500.0, 250.0, 250.0, 0.0]);
+    }
+
+    #[test]
+    fn window_rate_does_not_saturate_at_high_activity() {
+        let rates = run(RateEstimatorConfig::Window { window_ms: 10.0 }, &[true; 50]);
+        assert_eq!(*rates.last().unwrap(), 1000.0);
+    }
+
+    #[test]
+    fn bins_report_the_last_completed_bin() {
+        let pattern = [true, true, false, false, true, false];
+        let rates = run(RateEstimatorConfig::Bins { bin_ms: 2.0 }, &pattern);
+        assert_eq!(rates, [0.0, 1000.0, 1000.0, 0.0, 0.0, 500.0]);
+    }
+
+    #[test]
+    fn exponential_trace_matches_the_steady_state_rate() {
+        // A 100 Hz train at dt = 1 ms; sampling after each increment biases
+        // the mean up by about dt / (2 tau) = 1 %
+        let pattern: Vec<bool> = (0..2000).map(|t| t % 10 == 0).collect();
+        let rates = run(RateEstimatorConfig::Exponential { tau_ms: 50.0 }, &pattern);
+        let mean = rates[1000..].iter().sum::<f32>() / 1000.0;
+        assert!((mean - 100.0).abs() < 1.5, "mean rate {}", mean);
+    }
+
+    #[test]
+    fn configs_are_validated_against_the_step() {
+        assert!(RateEstimatorConfig::default().is_valid(1.0));
+        assert!(!RateEstimatorConfig::Exponential { tau_ms: 0.0 }.is_valid(1.0));
+        assert!(!RateEstimatorConfig::Window { window_ms: f32::INFINITY }.is_valid(1.0));
+        assert!(!RateEstimatorConfig::default().is_valid(0.0));
+
+        let limit = MAX_WINDOW_STEPS as f32;
+        assert!(RateEstimatorConfig::Window { window_ms: limit }.is_valid(1.0));
+        assert!(!RateEstimatorConfig::Window { window_ms: limit }.is_valid(0.5));
+        assert!(!RateEstimatorConfig::Bins { bin_ms: 1e12 }.is_valid(1.0));
+        assert!(RateEstimatorConfig::Exponential { tau_ms: 1e12 }.is_valid(1.0));
+    }
+}
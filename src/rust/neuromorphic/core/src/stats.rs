@@ -0,0 +1,354 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::model::NetworkLayout;
+use crate::population::NeuronPopulation;
+use crate::structural::{StructuralCounters, CONNECTION_EPSILON};
+
+/// Analysis window and bin width, in timesteps (ms). Fixed so that snapshots
+/// taken at different times can be compared bin for bin.
+pub const STATS_WINDOW_MS: u64 = 100;
+pub const STATS_BIN_MS: u64 = 5;
+
+const RATE_HISTOGRAM_BINS: usize = 20;
+const RATE_HISTOGRAM_MAX_HZ: f32 = 200.0;
+const WEIGHT_HISTOGRAM_BINS: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Histogram {
+    /// `counts.len() + 1` edges; values beyond the last edge land in the last bin.
+    pub bin_edges: Vec<f32>,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    fn new(min: f32, max: f32, bins: usize) -> Self {
+        let width = (max - min) / bins as f32;
+        Self {
+            bin_edges: (0..=bins).map(|i| min + i as f32 * width).collect(),
+            counts: vec![0; bins],
+        }
+    }
+
+    fn add(&mut self, value: f32) {
+        let bins = self.counts.len();
+        let min = self.bin_edges[0];
+        let max = self.bin_edges[bins];
+        let index = math::floor((value - min) / (max - min) * bins as f32);
+        let index = (index.max(0.0) as usize).min(bins - 1);
+        self.counts[index] += 1;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RateDistribution {
+    pub mean_hz: f32,
+    pub std_hz: f32,
+    pub min_hz: f32,
+    pub max_hz: f32,
+    pub median_hz: f32,
+    pub histogram: Histogram,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectionHistogram {
+    pub projection: String,
+    pub synapse_count: usize,
+    pub mean_weight: f32,
+    pub histogram: Histogram,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConnectionCounts {
+    pub total: usize,
+    pub excitatory: usize,
+    pub inhibitory: usize,
+    pub mean_fan_in: f32,
+    pub max_fan_in: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NetworkStats {
+    pub timestamp: u64,
+    pub window_ms: u64,
+    pub bin_ms: u64,
+    pub neuron_count: usize,
+    pub avg_threshold: f32,
+    pub firing_rates: RateDistribution,
+    /// Mean inter-spike-interval CV over neurons with at least two intervals.
+    pub isi_cv: Option<f32>,
+    /// Mean spike-count Fano factor over neurons that fired in the window.
+    pub fano_factor: Option<f32>,
+    /// Population synchrony measure (chi): 0 for independent firing, 1 for full synchrony.
+    pub synchrony: Option<f32>,
+    pub connections: ConnectionCounts,
+    pub weight_histograms: Vec<ProjectionHistogram>,
+    pub structural: StructuralCounters,
+}
+
+impl NetworkStats {
+    pub fn collect(
+        neurons: &NeuronPopulation,
+        weights: &[Vec<f32>],
+        layout: &NetworkLayout,
+        structural: StructuralCounters,
+        current_time: u64,
+    ) -> Self {
+        let n = neurons.len();
+        let window_start = current_time.saturating_sub(STATS_WINDOW_MS);
+        let bins = (STATS_WINDOW_MS / STATS_BIN_MS) as usize;
+
+        // Binned spike counts per neuron over the analysis window
+        let binned: Vec<Vec<f32>> = (0..n)
+            .map(|i| {
+                let mut counts = vec![0.0; bins];
+                for t in neurons.spike_history(i) {
+                    if t > window_start && t <= current_time {
+                        let bin = ((t - window_start - 1) / STATS_BIN_MS) as usize;
+                        counts[bin.min(bins - 1)] += 1.0;
+                    }
+                }
+                counts
+            })
+            .collect();
+
+        let rates = neurons.firing_rates(STATS_WINDOW_MS, current_time);
+
+        let avg_threshold = if n > 0 {
+            (0..n).map(|i| neurons.threshold(i)).sum::<f32>() / n as f32
+        } else {
+            0.0
+        };
+
+        Self {
+            timestamp: current_time,
+            window_ms: STATS_WINDOW_MS,
+            bin_ms: STATS_BIN_MS,
+            neuron_count: n,
+            avg_threshold,
+            firing_rates: rate_distribution(&rates),
+            isi_cv: isi_cv(neurons, window_start, current_time),
+            fano_factor: fano_factor(&binned),
+            synchrony: synchrony(&binned),
+            connections: connection_counts(weights),
+            weight_histograms: weight_histograms(weights, layout),
+            structural,
+        }
+    }
+}
+
+fn mean_and_variance(values: &[f32]) -> (f32, f32) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values.iter().map(|&x| (x - mean) * (x - mean)).sum::<f32>() / values.len() as f32;
+    (mean, variance)
+}
+
+fn rate_distribution(rates: &[f32]) -> RateDistribution {
+    let (mean, variance) = mean_and_variance(rates);
+
+    let mut sorted = rates.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = match sorted.len() {
+        0 => 0.0,
+        len if len % 2 == 0 => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0,
+        len => sorted[len / 2],
+    };
+
+    let mut histogram = Histogram::new(0.0, RATE_HISTOGRAM_MAX_HZ, RATE_HISTOGRAM_BINS);
+    for &rate in rates {
+        histogram.add(rate);
+    }
+
+    RateDistribution {
+        mean_hz: mean,
+        std_hz: math::sqrt(variance),
+        min_hz: sorted.first().copied().unwrap_or(0.0),
+        max_hz: sorted.last().copied().unwrap_or(0.0),
+        median_hz: median,
+        histogram,
+    }
+}
+
+/// Uses the same window as the rate bins: spikes after `window_start` up to
+/// `current_time`.
+fn isi_cv(neurons: &NeuronPopulation, window_start: u64, current_time: u64) -> Option<f32> {
+    let cvs: Vec<f32> = (0..neurons.len())
+        .filter_map(|i| {
+            let spikes: Vec<u64> = neurons.spike_history(i)
+                .filter(|&t| t > window_start && t <= current_time)
+                .collect();
+            if spikes.len() < 3 {
+                return None;
+            }
+            let intervals: Vec<f32> = spikes.windows(2)
+                .map(|pair| (pair[1] - pair[0]) as f32)
+                .collect();
+            let (mean, variance) = mean_and_variance(&intervals);
+            (mean > 0.0).then(|| math::sqrt(variance) / mean)
+        })
+        .collect();
+
+    (!cvs.is_empty()).then(|| cvs.iter().sum::<f32>() / cvs.len() as f32)
+}
+
+fn fano_factor(binned: &[Vec<f32>]) -> Option<f32> {
+    let factors: Vec<f32> = binned.iter()
+        .filter_map(|counts| {
+            let (mean, variance) = mean_and_variance(counts);
+            (mean > 0.0).then(|| variance / mean)
+        })
+        .collect();
+
+    (!factors.is_empty()).then(|| factors.iter().sum::<f32>() / factors.len() as f32)
+}
+
+fn synchrony(binned: &[Vec<f32>]) -> Option<f32> {
+    let bins = binned.first()?.len();
+    let population: Vec<f32> = (0..bins)
+        .map(|b| binned.iter().map(|counts| counts[b]).sum::<f32>() / binned.len() as f32)
+        .collect();
+
+    let (_, population_variance) = mean_and_variance(&population);
+    let mean_individual_variance = binned.iter()
+        .map(|counts| mean_and_variance(counts).1)
+        .sum::<f32>() / binned.len() as f32;
+
+    (mean_individual_variance > 0.0)
+        .then(|| math::sqrt(population_variance / mean_individual_variance))
+}
+
+fn connection_counts(weights: &[Vec<f32>]) -> ConnectionCounts {
+    let n = weights.len();
+    let mut excitatory = 0;
+    let mut inhibitory = 0;
+    let mut fan_in = vec![0usize; n];
+
+    for row in weights {
+        for (post, &weight) in row.iter().enumerate() {
+            if weight > CONNECTION_EPSILON {
+                excitatory += 1;
+                fan_in[post] += 1;
+            } else if weight < -CONNECTION_EPSILON {
+                inhibitory += 1;
+                fan_in[post] += 1;
+            }
+        }
+    }
+
+    let total = excitatory + inhibitory;
+    ConnectionCounts {
+        total,
+        excitatory,
+        inhibitory,
+        mean_fan_in: if n > 0 { total as f32 / n as f32 } else { 0.0 },
+        max_fan_in: fan_in.into_iter().max().unwrap_or(0),
+    }
+}
+
+/// One histogram per projection of `layout`, over the synapses it owns. A
+/// single-population network has no projection structure to report, so its
+/// recurrent weights are split by sign instead.
+fn weight_histograms(weights: &[Vec<f32>], layout: &NetworkLayout) -> Vec<ProjectionHistogram> {
+    let synapses = || weights.iter()
+        .enumerate()
+        .flat_map(|(pre, row)| row.iter().enumerate().map(move |(post, &w)| (pre, post, w)))
+        .filter(|&(_, _, w)| w.abs() > CONNECTION_EPSILON);
+
+    if layout.populations.len() <= 1 {
+        let excitatory: Vec<f32> = synapses().map(|(_, _, w)| w).filter(|&w| w > 0.0).collect();
+        let inhibitory: Vec<f32> = synapses().map(|(_, _, w)| w).filter(|&w| w < 0.0).collect();
+        return vec![
+            projection_histogram("recurrent_excitatory", &excitatory),
+            projection_histogram("recurrent_inhibitory", &inhibitory),
+        ];
+    }
+
+    let mut owned = vec![Vec::new(); layout.projections.len()];
+    for (pre, post, weight) in synapses() {
+        if let Some(projection) = layout.projection_of(pre, post) {
+            owned[projection].push(weight);
+        }
+    }
+    layout.projections.iter()
+        .zip(&owned)
+        .map(|(projection, selected)| projection_histogram(&projection.id, selected))
+        .collect()
+}
+
+fn projection_histogram(name: &str, selected: &[f32]) -> ProjectionHistogram {
+    let mut histogram = Histogram::new(-1.0, 1.0, WEIGHT_HISTOGRAM_BINS);
+    for &weight in selected {
+        histogram.add(weight);
+    }
+    ProjectionHistogram {
+        projection: name.to_string(),
+        synapse_count: selected.len(),
+        mean_weight: mean_and_variance(selected).0,
+        histogram,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::NetworkDescription;
+    use crate::LifParameters;
+
+    fn population(n: usize) -> NeuronPopulation {
+        let mut neurons = NeuronPopulation::new();
+        for _ in 0..n {
+            neurons.push(LifParameters::default());
+        }
+        neurons
+    }
+
+    #[test]
+    fn histograms_follow_declared_projections() {
+        let description: NetworkDescription = serde_json::from_str(r#"{
+            "populations": [{"id": "a", "size": 2}, {"id": "b", "size": 3}],
+            "projections": [
+                {"id": "forward", "pre": "a", "post": "b", "connector": {"kind": "all_to_all"}, "weight": 0.5},
+                {"id": "feedback", "pre": "b", "post": "a", "connector": {"kind": "all_to_all"}, "weight": -0.25},
+                {"id": "lateral", "pre": "b", "post": "b", "connector": {"kind": "explicit", "connections": []}}
+            ]
+        }"#).unwrap();
+        let built = description.build().unwrap();
+
+        let stats = NetworkStats::collect(&population(5), &built.weights, &built.layout, Default::default(), 0);
+        let summary: Vec<(&str, usize, f32)> = stats.weight_histograms.iter()
+            .map(|h| (h.projection.as_str(), h.synapse_count, h.mean_weight))
+            .collect();
+        assert_eq!(summary, [("forward", 6, 0.5), ("feedback", 6, -0.25), ("lateral", 0, 0.0)]);
+    }
+
+    #[test]
+    fn single_population_histograms_split_by_sign() {
+        let mut weights = vec![vec![0.0; 3]; 3];
+        weights[0][1] = 0.4;
+        weights[1][2] = -0.2;
+        weights[2][0] = 0.0005;
+        let layout = NetworkLayout::single("network", 3);
+        let stats = NetworkStats::collect(&population(3), &weights, &layout, Default::default(), 0);
+        let names: Vec<(&str, usize)> = stats.weight_histograms.iter()
+            .map(|h| (h.projection.as_str(), h.synapse_count))
+            .collect();
+        assert_eq!(names, [("recurrent_excitatory", 1), ("recurrent_inhibitory", 1)]);
+    }
+
+    #[test]
+    fn isi_cv_excludes_the_spike_on_the_window_boundary() {
+        let mut neurons = population(1);
+        // With the boundary spike at t = 100 the intervals would be 10, 20, 20
+        for t in [100, 110, 130, 150] {
+            neurons.record_spikes(&[true], t);
+        }
+        let stats = NetworkStats::collect(&neurons, &[vec![0.0]], &NetworkLayout::single("n", 1), Default::default(), 200);
+        assert_eq!(stats.isi_cv, Some(0.0));
+    }
+}
@@ -0,0 +1,322 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::model::NetworkLayout;
+use crate::rng::XorShift64;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StimulusKind {
+    /// Constant current while the stimulus is active (a step).
+    Constant { amplitude: f32 },
+    /// Rectangular pulses of `width_steps` repeating every `period_steps`.
+    Pulse { amplitude: f32, width_steps: usize, period_steps: usize },
+    /// Linear ramp from `from` to `to` over `duration_steps`, then held at `to`.
+    Ramp { from: f32, to: f32, duration_steps: usize },
+    Sinusoid {
+        amplitude: f32,
+        frequency_hz: f32,
+        #[serde(default)]
+        phase_rad: f32,
+        #[serde(default)]
+        offset: f32,
+    },
+    /// Independent Ornstein–Uhlenbeck noise current per target neuron.
+    OrnsteinUhlenbeck { mean: f32, sigma: f32, tau_ms: f32 },
+    /// Independent Poisson spike train per target neuron; each spike injects `weight`.
+    Poisson { rate_hz: f32, weight: f32 },
+    /// Recorded current trace, one value per timestep.
+    Trace {
+        values: Vec<f32>,
+        #[serde(default)]
+        repeat: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StimulusTarget {
+    #[default]
+    All,
+    /// Every neuron of the named populations.
+    Populations { ids: Vec<String> },
+    /// Neurons `start..end`.
+    Range { start: usize, end: usize },
+    Neurons { indices: Vec<usize> },
+}
+
+impl StimulusTarget {
+    /// Neuron indices selected by the target, or `None` if it names an
+    /// unknown population.
+    fn indices(&self, layout: &NetworkLayout) -> Option<Vec<usize>> {
+        let network_size = layout.neuron_count();
+        let indices = match self {
+            StimulusTarget::All => (0..network_size).collect(),
+            StimulusTarget::Populations { ids } => {
+                let mut indices = Vec::new();
+                for id in ids {
+                    let population = layout.populations.iter().find(|p| &p.id == id)?;
+                    indices.extend(population.range());
+                }
+                indices
+            }
+            StimulusTarget::Range { start, end } => (*start..(*end).min(network_size)).collect(),
+            StimulusTarget::Neurons { indices } => indices.iter()
+                .copied()
+                .filter(|&i| i < network_size)
+                .collect(),
+        };
+        Some(indices)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StimulusConfig {
+    #[serde(flatten)]
+    pub kind: StimulusKind,
+    #[serde(default)]
+    pub target: StimulusTarget,
+    /// First timestep (relative to the start of a run) at which the stimulus is on.
+    #[serde(default)]
+    pub start_step: usize,
+    /// Timestep at which the stimulus switches off; runs to the end if absent.
+    #[serde(default)]
+    pub stop_step: Option<usize>,
+    /// Dendritic branch receiving the current; the soma if absent.
+    #[serde(default)]
+    pub compartment: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Stimulus {
+    id: u32,
+    config: StimulusConfig,
+    targets: Vec<usize>,
+    /// Per-target state for stochastic sources (OU current).
+    state: Vec<f32>,
+}
+
+/// Composable set of stimuli whose currents are summed per neuron.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StimulusSet {
+    stimuli: Vec<Stimulus>,
+    next_id: u32,
+    rng: XorShift64,
+}
+
+impl StimulusSet {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            stimuli: Vec::new(),
+            next_id: 0,
+            rng: XorShift64::new(seed),
+        }
+    }
+
+    /// Adds a stimulus and returns its id, or `None` if the config is invalid
+    /// or its target selects no neurons of `layout`.
+    pub fn add(&mut self, config: StimulusConfig, layout: &NetworkLayout) -> Option<u32> {
+        let valid = match &config.kind {
+            StimulusKind::Pulse { width_steps, period_steps, .. } => *period_steps > 0 && width_steps <= period_steps,
+            StimulusKind::OrnsteinUhlenbeck { sigma, tau_ms, .. } => *tau_ms > 0.0 && *sigma >= 0.0,
+            StimulusKind::Poisson { rate_hz, .. } => *rate_hz >= 0.0,
+            StimulusKind::Trace { values, .. } => !values.is_empty(),
+            _ => true,
+        };
+        let targets = config.target.indices(layout)?;
+        if !valid || targets.is_empty() {
+            return None;
+        }
+
+        let state = match &config.kind {
+            StimulusKind::OrnsteinUhlenbeck { mean, .. } => vec![*mean; targets.len()],
+            _ => Vec::new(),
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.stimuli.push(Stimulus { id, config, targets, state });
+        Some(id)
+    }
+
+    pub fn remove(&mut self, id: u32) -> bool {
+        let before = self.stimuli.len();
+        self.stimuli.retain(|stimulus| stimulus.id != id);
+        self.stimuli.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.stimuli.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stimuli.is_empty()
+    }
+
+    /// Adds the current of every active stimulus aimed at `compartment` at
+    /// `step` into `currents`.
+    pub fn apply(&mut self, step: usize, dt_ms: f32, compartment: Option<usize>, currents: &mut [f32]) {
+        for stimulus in &mut self.stimuli {
+            let config = &stimulus.config;
+            if config.compartment != compartment {
+                continue;
+            }
+            if step < config.start_step || config.stop_step.is_some_and(|stop| step >= stop) {
+                continue;
+            }
+            let local_step = step - config.start_step;
+            let t_ms = local_step as f32 * dt_ms;
+
+            let uniform_value = match &config.kind {
+                StimulusKind::Constant { amplitude } => Some(*amplitude),
+                StimulusKind::Pulse { amplitude, width_steps, period_steps } => {
+                    Some(if local_step % period_steps < *width_steps { *amplitude } else { 0.0 })
+                }
+                StimulusKind::Ramp { from, to, duration_steps } => {
+                    let progress = if *duration_steps == 0 {
+                        1.0
+                    } else {
+                        (local_step as f32 / *duration_steps as f32).min(1.0)
+                    };
+                    Some(from + (to - from) * progress)
+                }
+                StimulusKind::Sinusoid { amplitude, frequency_hz, phase_rad, offset } => {
+                    let angle = 2.0 * PI * frequency_hz * t_ms / 1000.0 + phase_rad;
+                    Some(offset + amplitude * math::sin(angle))
+                }
+                StimulusKind::Trace { values, repeat } => {
+                    let index = if *repeat { local_step % values.len() } else { local_step };
+                    Some(values.get(index).copied().unwrap_or(0.0))
+                }
+                StimulusKind::OrnsteinUhlenbeck { .. } | StimulusKind::Poisson { .. } => None,
+            };
+
+            if let Some(value) = uniform_value {
+                for &i in &stimulus.targets {
+                    currents[i] += value;
+                }
+                continue;
+            }
+
+            match &config.kind {
+                StimulusKind::OrnsteinUhlenbeck { mean, sigma, tau_ms } => {
+                    // Exact OU update for a step of dt_ms
+                    let decay = math::exp(-dt_ms / tau_ms);
+                    let spread = sigma * math::sqrt(1.0 - decay * decay);
+                    for (x, &i) in stimulus.state.iter_mut().zip(&stimulus.targets) {
+                        // Box–Muller
+                        let u1 = self.rng.uniform().max(f32::MIN_POSITIVE);
+                        let u2 = self.rng.uniform();
+                        let normal = math::sqrt(-2.0 * math::ln(u1)) * math::cos(2.0 * PI * u2);
+                        *x = mean + (*x - mean) * decay + spread * normal;
+                        currents[i] += *x;
+                    }
+                }
+                StimulusKind::Poisson { rate_hz, weight } => {
+                    let probability = rate_hz * dt_ms / 1000.0;
+                    for &i in &stimulus.targets {
+                        if self.rng.uniform() < probability {
+                            currents[i] += weight;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{PopulationInfo, ProjectionInfo};
+    use alloc::string::ToString;
+
+    fn stimulus(json: &str) -> StimulusConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn single(network_size: usize) -> NetworkLayout {
+        NetworkLayout::single("network", network_size)
+    }
+
+    fn run(set: &mut StimulusSet, steps: usize, neuron: usize, network_size: usize) -> Vec<f32> {
+        (0..steps)
+            .map(|step| {
+                let mut currents = vec![0.0; network_size];
+                set.apply(step, 1.0, None, &mut currents);
+                currents[neuron]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deterministic_waveforms_respect_start_and_stop() {
+        let mut set = StimulusSet::new(1);
+        set.add(stimulus(r#"{"kind": "pulse", "amplitude": 2.0, "width_steps": 1, "period_steps": 3,
+                              "start_step": 2, "stop_step": 8}"#), &single(1)).unwrap();
+        set.add(stimulus(r#"{"kind": "ramp", "from": 0.0, "to": 1.0, "duration_steps": 4}"#), &single(1)).unwrap();
+        let values = run(&mut set, 10, 0, 1);
+        assert_eq!(values, [0.0, 0.25, 2.5, 0.75, 1.0, 3.0, 1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn targets_limit_the_neurons_driven() {
+        let mut set = StimulusSet::new(1);
+        set.add(stimulus(r#"{"kind": "constant", "amplitude": 1.0, "target": {"kind": "range", "start": 1, "end": 3}}"#), &single(4))
+            .unwrap();
+        let mut currents = vec![0.0; 4];
+        set.apply(0, 1.0, None, &mut currents);
+        assert_eq!(currents, [0.0, 1.0, 1.0, 0.0]);
+        assert!(set.add(stimulus(r#"{"kind": "constant", "amplitude": 1.0, "target": {"kind": "neurons", "indices": [9]}}"#), &single(4))
+            .is_none());
+    }
+
+    #[test]
+    fn population_targets_resolve_against_the_layout() {
+        let populations = vec![
+            PopulationInfo { id: "input".to_string(), start: 0, size: 2 },
+            PopulationInfo { id: "hidden".to_string(), start: 2, size: 3 },
+        ];
+        let layout = NetworkLayout::new(String::new(), populations, Vec::<ProjectionInfo>::new());
+        let mut set = StimulusSet::new(1);
+        set.add(stimulus(r#"{"kind": "constant", "amplitude": 1.0, "target": {"kind": "populations", "ids": ["hidden"]}}"#), &layout)
+            .unwrap();
+        let mut currents = vec![0.0; 5];
+        set.apply(0, 1.0, None, &mut currents);
+        assert_eq!(currents, [0.0, 0.0, 1.0, 1.0, 1.0]);
+
+        let unknown = r#"{"kind": "constant", "amplitude": 1.0, "target": {"kind": "populations", "ids": ["hidden", "output"]}}"#;
+        assert!(set.add(stimulus(unknown), &layout).is_none());
+    }
+
+    #[test]
+    fn stochastic_sources_match_their_statistics() {
+        let mut set = StimulusSet::new(7);
+        set.add(stimulus(r#"{"kind": "poisson", "rate_hz": 100.0, "weight": 1.0}"#), &single(1)).unwrap();
+        let spikes: f32 = run(&mut set, 20_000, 0, 1).iter().sum();
+        assert!((spikes / 20.0 - 100.0).abs() < 10.0, "rate {} Hz", spikes / 20.0);
+
+        let mut set = StimulusSet::new(7);
+        set.add(stimulus(r#"{"kind": "ornstein_uhlenbeck", "mean": 0.5, "sigma": 0.2, "tau_ms": 5.0}"#), &single(1)).unwrap();
+        let values = run(&mut set, 20_000, 0, 1);
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32;
+        assert!((mean - 0.5).abs() < 0.02, "mean {}", mean);
+        assert!((math::sqrt(variance) - 0.2).abs() < 0.02, "std {}", math::sqrt(variance));
+    }
+
+    #[test]
+    fn the_same_seed_reproduces_the_noise() {
+        let config = r#"{"kind": "poisson", "rate_hz": 50.0, "weight": 1.0}"#;
+        let mut a = StimulusSet::new(3);
+        let mut b = StimulusSet::new(3);
+        a.add(stimulus(config), &single(8)).unwrap();
+        b.add(stimulus(config), &single(8)).unwrap();
+        assert_eq!(run(&mut a, 500, 5, 8), run(&mut b, 500, 5, 8));
+    }
+}
@@ -0,0 +1,228 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::model::NetworkLayout;
+
+/// Weights below this magnitude are treated as "no synapse", matching the
+/// connectivity convention used throughout the processor.
+pub const CONNECTION_EPSILON: f32 = 0.001;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct StructuralPlasticityConfig {
+    pub enabled: bool,
+    /// Synapses whose |weight| stays below this value are candidates for pruning.
+    pub prune_threshold: f32,
+    /// Number of consecutive updates a synapse must stay weak before it is removed.
+    pub prune_patience: u32,
+    /// Firing rate (Hz) above which two neurons count as co-active.
+    pub coactivity_rate_hz: f32,
+    /// Probability of creating a synapse between an unconnected co-active pair per update.
+    pub growth_probability: f32,
+    /// Weight given to a freshly grown synapse.
+    pub initial_weight: f32,
+    /// Maximum number of incoming synapses per neuron; growth stops once reached.
+    pub max_fan_in: usize,
+}
+
+impl StructuralPlasticityConfig {
+    pub fn is_valid(&self) -> bool {
+        self.prune_threshold.is_finite()
+            && self.prune_threshold >= 0.0
+            && self.prune_patience > 0
+            && self.coactivity_rate_hz.is_finite()
+            && (0.0..=1.0).contains(&self.growth_probability)
+            && self.initial_weight.is_finite()
+            && self.max_fan_in > 0
+    }
+}
+
+impl Default for StructuralPlasticityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            prune_threshold: 0.01,
+            prune_patience: 10,
+            coactivity_rate_hz: 1.0,
+            growth_probability: 0.05,
+            initial_weight: 0.05,
+            max_fan_in: 16,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct StructuralCounters {
+    pub synapses_pruned: u64,
+    pub synapses_grown: u64,
+}
+
+/// Prunes persistently weak synapses and grows new ones between co-active
+/// neurons. Weights are indexed `weights[pre][post]`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StructuralPlasticity {
+    pub config: StructuralPlasticityConfig,
+    pub counters: StructuralCounters,
+    weak_streak: Vec<Vec<u32>>,
+}
+
+impl StructuralPlasticity {
+    pub fn new(network_size: usize) -> Self {
+        Self {
+            config: StructuralPlasticityConfig::default(),
+            counters: StructuralCounters::default(),
+            weak_streak: vec![vec![0; network_size]; network_size],
+        }
+    }
+
+    /// Runs one pruning/growth pass. `rates` holds the current firing rate of
+    /// every neuron, `random` yields uniform samples in [0, 1). Synapses in
+    /// static projections are left alone, and new ones only grow inside
+    /// plastic projections of `layout`.
+    pub fn update(
+        &mut self,
+        weights: &mut [Vec<f32>],
+        layout: &NetworkLayout,
+        rates: &[f32],
+        mut random: impl FnMut() -> f32,
+    ) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let n = weights.len();
+
+        // Pruning: remove synapses that have stayed weak for long enough
+        for (pre, (row, streaks)) in weights.iter_mut().zip(self.weak_streak.iter_mut()).enumerate() {
+            for (post, (weight, streak)) in row.iter_mut().zip(streaks.iter_mut()).enumerate() {
+                if weight.abs() <= CONNECTION_EPSILON || !layout.is_plastic(pre, post) {
+                    *streak = 0;
+                    continue;
+                }
+
+                if weight.abs() < self.config.prune_threshold {
+                    *streak += 1;
+                    if *streak >= self.config.prune_patience {
+                        *weight = 0.0;
+                        *streak = 0;
+                        self.counters.synapses_pruned += 1;
+                    }
+                } else {
+                    *streak = 0;
+                }
+            }
+        }
+
+        // Growth: connect co-active pairs while the target has fan-in budget left
+        let active: Vec<usize> = rates.iter()
+            .enumerate()
+            .filter(|(_, &rate)| rate > self.config.coactivity_rate_hz)
+            .map(|(i, _)| i)
+            .collect();
+
+        for &post in &active {
+            let mut fan_in = (0..n)
+                .filter(|&pre| weights[pre][post].abs() > CONNECTION_EPSILON)
+                .count();
+
+            for &pre in &active {
+                if fan_in >= self.config.max_fan_in {
+                    break;
+                }
+                if pre == post
+                    || weights[pre][post].abs() > CONNECTION_EPSILON
+                    || !layout.can_grow(pre, post)
+                {
+                    continue;
+                }
+                if random() < self.config.growth_probability {
+                    weights[pre][post] = self.config.initial_weight;
+                    fan_in += 1;
+                    self.counters.synapses_grown += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{PopulationInfo, ProjectionInfo, SynapsePlasticity};
+    use alloc::string::{String, ToString};
+
+    fn enabled(config: StructuralPlasticityConfig) -> StructuralPlasticity {
+        let mut plasticity = StructuralPlasticity::new(4);
+        plasticity.config = StructuralPlasticityConfig { enabled: true, ..config };
+        plasticity
+    }
+
+    #[test]
+    fn weak_synapses_are_pruned_after_patience_updates() {
+        let mut plasticity = enabled(StructuralPlasticityConfig { prune_patience: 3, ..Default::default() });
+        let mut weights = vec![vec![0.0; 4]; 4];
+        let layout = NetworkLayout::single("network", 4);
+        weights[0][1] = 0.005;
+        weights[1][2] = 0.5;
+        for _ in 0..2 {
+            plasticity.update(&mut weights, &layout, &[0.0; 4], || 0.0);
+        }
+        assert_eq!(weights[0][1], 0.005);
+        plasticity.update(&mut weights, &layout, &[0.0; 4], || 0.0);
+        assert_eq!(weights[0][1], 0.0);
+        assert_eq!(weights[1][2], 0.5);
+        assert_eq!(plasticity.counters.synapses_pruned, 1);
+    }
+
+    #[test]
+    fn growth_connects_coactive_pairs_up_to_the_fan_in_limit() {
+        let mut plasticity = enabled(StructuralPlasticityConfig { max_fan_in: 2, ..Default::default() });
+        let mut weights = vec![vec![0.0; 4]; 4];
+        let layout = NetworkLayout::single("network", 4);
+        plasticity.update(&mut weights, &layout, &[5.0, 5.0, 5.0, 5.0], || 0.0);
+        for post in 0..4 {
+            let fan_in = weights.iter().filter(|row| row[post] != 0.0).count();
+            assert_eq!(fan_in, 2);
+        }
+        assert!((0..4).all(|i| weights[i][i] == 0.0));
+        assert_eq!(plasticity.counters.synapses_grown, 8);
+    }
+
+    #[test]
+    fn only_plastic_projections_are_pruned_or_grown() {
+        // p0 -> p1 is static, p1 -> p1 plastic, p1 -> p0 and p0 -> p0 undeclared
+        let populations = vec![
+            PopulationInfo { id: "p0".to_string(), start: 0, size: 2 },
+            PopulationInfo { id: "p1".to_string(), start: 2, size: 2 },
+        ];
+        let projections = vec![
+            ProjectionInfo { id: "static".to_string(), pre: 0, post: 1, plasticity: SynapsePlasticity::Static },
+            ProjectionInfo { id: "plastic".to_string(), pre: 1, post: 1, plasticity: SynapsePlasticity::Hebbian },
+        ];
+        let layout = NetworkLayout::new(String::new(), populations, projections);
+
+        let mut plasticity = enabled(StructuralPlasticityConfig { prune_patience: 1, ..Default::default() });
+        let mut weights = vec![vec![0.0; 4]; 4];
+        weights[0][2] = 0.005;
+        weights[2][3] = 0.005;
+        plasticity.update(&mut weights, &layout, &[0.0; 4], || 0.0);
+        assert_eq!(weights[0][2], 0.005);
+        assert_eq!(weights[2][3], 0.0);
+
+        plasticity.update(&mut weights, &layout, &[5.0; 4], || 0.0);
+        let grown: Vec<(usize, usize)> = (0..4)
+            .flat_map(|pre| (0..4).map(move |post| (pre, post)))
+            .filter(|&(pre, post)| weights[pre][post] == plasticity.config.initial_weight)
+            .collect();
+        assert_eq!(grown, [(2, 3), (3, 2)]);
+    }
+
+    #[test]
+    fn invalid_configs_are_detected() {
+        assert!(StructuralPlasticityConfig::default().is_valid());
+        assert!(!StructuralPlasticityConfig { max_fan_in: 0, ..Default::default() }.is_valid());
+        assert!(!StructuralPlasticityConfig { prune_patience: 0, ..Default::default() }.is_valid());
+        assert!(!StructuralPlasticityConfig { prune_threshold: f32::NAN, ..Default::default() }.is_valid());
+    }
+}
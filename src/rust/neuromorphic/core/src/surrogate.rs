@@ -0,0 +1,483 @@
+//! Offline supervised training with surrogate gradients. The network of a
+//! `NetworkDescription` is unrolled over each sample exactly as the
+//! processor's `run_frames` steps it (frames mapped with `currents_at`,
+//! recurrent input from windowed presynaptic rates, LIF integration with
+//! refractory clamping), and backpropagation through time treats each spike
+//! as differentiable through a fast sigmoid (Zenke & Ganguli 2018) while
+//! resets are not differentiated. The loss is the softmax cross-entropy of
+//! the output population's spike counts; weights present in the model are
+//! trained with Adam and clamped to [-1, 1] as `build` does, so the exported
+//! description reproduces the trained network.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+use serde::{Deserialize, Serialize};
+
+use crate::datasets::Dataset;
+use crate::encoding::InputFrames;
+use crate::gap::GapJunctions;
+use crate::math;
+use crate::model::{ModelError, NetworkDescription, NetworkLayout, PlasticityDescription, RecurrenceDescription};
+use crate::population::NeuronPopulation;
+use crate::rates::{RateEstimator, RateEstimatorConfig};
+use crate::structural::CONNECTION_EPSILON;
+use crate::LifParameters;
+
+const ADAM_BETA1: f32 = 0.9;
+const ADAM_BETA2: f32 = 0.999;
+const ADAM_EPSILON: f32 = 1e-8;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SurrogateConfig {
+    /// Population whose spike counts are the class scores.
+    pub output_population: String,
+    pub epochs: usize,
+    /// Samples per Adam step.
+    pub batch_size: usize,
+    pub learning_rate: f32,
+    /// Steepness of the fast sigmoid, per (threshold − rest) of distance
+    /// from threshold.
+    pub surrogate_slope: f32,
+    /// Logits are the output spike counts times this.
+    pub logit_scale: f32,
+    /// The processor's recurrent gain and recurrent rate window; `export`
+    /// writes them into the description so `from_model` applies them.
+    pub recurrent_gain: f32,
+    pub rate_window_ms: f32,
+    /// Seed of the per-epoch sample order.
+    pub seed: u64,
+}
+
+impl Default for SurrogateConfig {
+    fn default() -> Self {
+        Self {
+            output_population: "output".to_string(),
+            epochs: 10,
+            batch_size: 16,
+            learning_rate: 1e-3,
+            surrogate_slope: 10.0,
+            logit_scale: 0.5,
+            recurrent_gain: 0.01,
+            rate_window_ms: 10.0,
+            seed: 1,
+        }
+    }
+}
+
+impl SurrogateConfig {
+    pub fn is_valid(&self) -> bool {
+        self.batch_size > 0
+            && self.learning_rate.is_finite()
+            && self.learning_rate > 0.0
+            && self.surrogate_slope.is_finite()
+            && self.surrogate_slope > 0.0
+            && self.logit_scale.is_finite()
+            && self.logit_scale > 0.0
+            && self.recurrent_gain.is_finite()
+            && self.rate_window_ms.is_finite()
+            && self.rate_window_ms > 0.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SurrogateError {
+    Model(ModelError),
+    InvalidConfig,
+    UnknownOutputPopulation(String),
+    /// Gap junctions are not part of the unrolled model.
+    GapJunctions,
+    LabelOutOfRange { label: usize, classes: usize },
+}
+
+impl fmt::Display for SurrogateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SurrogateError::Model(error) => write!(f, "{}", error),
+            SurrogateError::InvalidConfig => write!(f, "invalid surrogate training configuration"),
+            SurrogateError::UnknownOutputPopulation(id) => write!(f, "output population '{}' does not exist", id),
+            SurrogateError::GapJunctions => write!(f, "networks with electrical projections cannot be trained"),
+            SurrogateError::LabelOutOfRange { label, classes } => {
+                write!(f, "label {} is out of range for {} output neurons", label, classes)
+            }
+        }
+    }
+}
+
+impl From<ModelError> for SurrogateError {
+    fn from(error: ModelError) -> Self {
+        SurrogateError::Model(error)
+    }
+}
+
+/// Mean loss and accuracy over a set of samples.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrainingStats {
+    pub samples: usize,
+    pub loss: f32,
+    pub accuracy: f32,
+}
+
+/// State recorded by the forward pass for backpropagation, step-major.
+struct Unrolled {
+    steps: usize,
+    spikes: Vec<bool>,
+    /// Integrated potential before the threshold test.
+    potentials: Vec<f32>,
+    /// False while refractory (potential clamped, no gradient).
+    active: Vec<bool>,
+    /// Presynaptic rates (Hz) driving the recurrent input of each step.
+    rates: Vec<f32>,
+    counts: Vec<u32>,
+}
+
+/// Network under training: cell parameters, trainable weights and Adam state.
+pub struct SurrogateTrainer {
+    pub config: SurrogateConfig,
+    params: Vec<LifParameters>,
+    decay: Vec<f32>,
+    refractory_steps: Vec<u32>,
+    /// Indexed `[pre][post]`.
+    weights: Vec<Vec<f32>>,
+    /// Synapses of the model; only these are trained.
+    trainable: Vec<Vec<bool>>,
+    outputs: Range<usize>,
+    layout: NetworkLayout,
+    dt_ms: f32,
+    /// Seed of the model description, carried into `export`.
+    seed: u64,
+    plasticity: PlasticityDescription,
+    gradient: Vec<Vec<f32>>,
+    first_moment: Vec<Vec<f32>>,
+    second_moment: Vec<Vec<f32>>,
+    adam_steps: i32,
+    pending_samples: usize,
+}
+
+impl SurrogateTrainer {
+    pub fn new(description: &NetworkDescription, config: SurrogateConfig) -> Result<Self, SurrogateError> {
+        if !config.is_valid() {
+            return Err(SurrogateError::InvalidConfig);
+        }
+        let built = description.build()?;
+        let rates = RateEstimatorConfig::Window { window_ms: config.rate_window_ms };
+        if !rates.is_valid(built.dt_ms) {
+            return Err(SurrogateError::InvalidConfig);
+        }
+        if !built.gap_junctions.is_empty() {
+            return Err(SurrogateError::GapJunctions);
+        }
+        let outputs = built.layout.populations.iter()
+            .find(|population| population.id == config.output_population)
+            .map(|population| population.range())
+            .ok_or_else(|| SurrogateError::UnknownOutputPopulation(config.output_population.clone()))?;
+
+        let n = built.params.len();
+        let trainable: Vec<Vec<bool>> = built.weights.iter()
+            .enumerate()
+            .map(|(pre, row)| row.iter().enumerate().map(|(post, w)| post != pre && w.abs() > CONNECTION_EPSILON).collect())
+            .collect();
+        Ok(Self {
+            decay: built.params.iter().map(|p| math::exp(-p.dt_ms / p.tau_m_ms)).collect(),
+            refractory_steps: built.params.iter().map(|p| math::round(p.refractory_ms / p.dt_ms) as u32).collect(),
+            params: built.params,
+            weights: built.weights,
+            trainable,
+            outputs,
+            layout: built.layout,
+            dt_ms: built.dt_ms,
+            seed: description.seed,
+            plasticity: built.plasticity,
+            gradient: vec![vec![0.0; n]; n],
+            first_moment: vec![vec![0.0; n]; n],
+            second_moment: vec![vec![0.0; n]; n],
+            adam_steps: 0,
+            pending_samples: 0,
+            config,
+        })
+    }
+
+    pub fn neuron_count(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn class_count(&self) -> usize {
+        self.outputs.len()
+    }
+
+    pub fn weights(&self) -> &[Vec<f32>] {
+        &self.weights
+    }
+
+    /// Spike count of every output neuron for `frames`, starting from rest.
+    pub fn predict(&self, frames: &InputFrames) -> Vec<u32> {
+        self.forward(frames).counts[self.outputs.clone()].to_vec()
+    }
+
+    /// Output neuron with the most spikes; the lowest index wins ties.
+    pub fn classify(&self, frames: &InputFrames) -> usize {
+        classify(&self.predict(frames))
+    }
+
+    /// Accumulates the gradient of one labelled sample and takes an Adam
+    /// step once `batch_size` samples are pending. Returns the sample's loss
+    /// and predicted class.
+    pub fn train_sample(&mut self, frames: &InputFrames, label: usize) -> Result<(f32, usize), SurrogateError> {
+        if label >= self.class_count() {
+            return Err(SurrogateError::LabelOutOfRange { label, classes: self.class_count() });
+        }
+        let unrolled = self.forward(frames);
+        let counts = &unrolled.counts[self.outputs.clone()];
+        let probabilities = self.softmax(counts);
+        let loss = -math::ln(probabilities[label].max(f32::MIN_POSITIVE));
+        let predicted = classify(counts);
+
+        let output_gradient: Vec<f32> = probabilities.iter()
+            .enumerate()
+            .map(|(k, &p)| self.config.logit_scale * (p - if k == label { 1.0 } else { 0.0 }))
+            .collect();
+        self.backward(&unrolled, &output_gradient);
+        self.pending_samples += 1;
+        if self.pending_samples == self.config.batch_size {
+            self.apply_gradient();
+        }
+        Ok((loss, predicted))
+    }
+
+    /// Takes an Adam step with any gradient still pending.
+    pub fn apply_gradient(&mut self) {
+        if self.pending_samples == 0 {
+            return;
+        }
+        self.adam_steps += 1;
+        let batch = self.pending_samples as f32;
+        let step_size = self.config.learning_rate * math::sqrt(1.0 - math::powf(ADAM_BETA2, self.adam_steps as f32))
+            / (1.0 - math::powf(ADAM_BETA1, self.adam_steps as f32));
+        for pre in 0..self.weights.len() {
+            for post in 0..self.weights.len() {
+                if !self.trainable[pre][post] {
+                    continue;
+                }
+                let g = self.gradient[pre][post] / batch;
+                let m = &mut self.first_moment[pre][post];
+                *m = ADAM_BETA1 * *m + (1.0 - ADAM_BETA1) * g;
+                let v = &mut self.second_moment[pre][post];
+                *v = ADAM_BETA2 * *v + (1.0 - ADAM_BETA2) * g * g;
+                let update = step_size * *m / (math::sqrt(*v) + ADAM_EPSILON);
+                self.weights[pre][post] = (self.weights[pre][post] - update).clamp(-1.0, 1.0);
+                self.gradient[pre][post] = 0.0;
+            }
+        }
+        self.pending_samples = 0;
+    }
+
+    /// One pass over `dataset` in an order shuffled by the seed and `epoch`.
+    pub fn train_epoch(&mut self, dataset: &Dataset<InputFrames>, epoch: usize) -> Result<TrainingStats, SurrogateError> {
+        let mut stats = StatsAccumulator::default();
+        for (frames, label) in dataset.shuffled(self.config.seed ^ epoch as u64) {
+            let (loss, predicted) = self.train_sample(frames, label)?;
+            stats.add(loss, predicted == label);
+        }
+        self.apply_gradient();
+        Ok(stats.finish())
+    }
+
+    pub fn evaluate(&self, dataset: &Dataset<InputFrames>) -> Result<TrainingStats, SurrogateError> {
+        let mut stats = StatsAccumulator::default();
+        for (frames, label) in dataset.iter() {
+            if label >= self.class_count() {
+                return Err(SurrogateError::LabelOutOfRange { label, classes: self.class_count() });
+            }
+            let counts = self.predict(frames);
+            let loss = -math::ln(self.softmax(&counts)[label].max(f32::MIN_POSITIVE));
+            stats.add(loss, classify(&counts) == label);
+        }
+        Ok(stats.finish())
+    }
+
+    /// The trained network in the format accepted by
+    /// `NeuromorphicProcessor::from_model`.
+    pub fn export(&self) -> NetworkDescription {
+        let mut neurons = NeuronPopulation::new();
+        for &params in &self.params {
+            neurons.push(params);
+        }
+        NetworkDescription {
+            recurrence: RecurrenceDescription {
+                gain: self.config.recurrent_gain,
+                rates: RateEstimatorConfig::Window { window_ms: self.config.rate_window_ms },
+            },
+            ..NetworkDescription::from_network(
+                &self.layout, &neurons, &self.weights, &GapJunctions::default(), self.dt_ms, self.seed, self.plasticity,
+            )
+        }
+    }
+
+    fn softmax(&self, counts: &[u32]) -> Vec<f32> {
+        let logits: Vec<f32> = counts.iter().map(|&c| c as f32 * self.config.logit_scale).collect();
+        let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let exps: Vec<f32> = logits.iter().map(|&l| math::exp(l - max)).collect();
+        let sum: f32 = exps.iter().sum();
+        exps.iter().map(|&e| e / sum).collect()
+    }
+
+    fn rate_estimator(&self) -> RateEstimator {
+        RateEstimator::new(RateEstimatorConfig::Window { window_ms: self.config.rate_window_ms }, self.params.len(), self.dt_ms)
+    }
+
+    /// Steps the network from rest through `frames`, in the same operation
+    /// order as the processor so spikes match bit for bit.
+    fn forward(&self, frames: &InputFrames) -> Unrolled {
+        let n = self.params.len();
+        let steps = frames.steps();
+        let mut unrolled = Unrolled {
+            steps,
+            spikes: vec![false; steps * n],
+            potentials: vec![0.0; steps * n],
+            active: vec![false; steps * n],
+            rates: vec![0.0; steps * n],
+            counts: vec![0; n],
+        };
+        let mut membrane: Vec<f32> = self.params.iter().map(|p| p.v_rest).collect();
+        let mut refractory = vec![0u32; n];
+        let mut rates = self.rate_estimator();
+
+        for t in 0..steps {
+            let row = t * n..(t + 1) * n;
+            let mut currents = vec![0.0; n];
+            frames.currents_at(t, &mut currents);
+            for (pre, &rate) in rates.rates().iter().enumerate() {
+                if rate == 0.0 {
+                    continue;
+                }
+                for (post, (current, &weight)) in currents.iter_mut().zip(&self.weights[pre]).enumerate() {
+                    if post != pre && weight.abs() > CONNECTION_EPSILON {
+                        *current += weight * rate * self.config.recurrent_gain;
+                    }
+                }
+            }
+            unrolled.rates[row.clone()].copy_from_slice(rates.rates());
+
+            let spikes = &mut unrolled.spikes[row.clone()];
+            for i in 0..n {
+                let params = &self.params[i];
+                if refractory[i] > 0 {
+                    refractory[i] -= 1;
+                    membrane[i] = params.v_reset;
+                    continue;
+                }
+                let v_inf = params.v_rest + params.resistance * currents[i];
+                let v = v_inf + (membrane[i] - v_inf) * self.decay[i];
+                unrolled.active[t * n + i] = true;
+                unrolled.potentials[t * n + i] = v;
+                if v >= params.v_threshold {
+                    membrane[i] = params.v_reset;
+                    refractory[i] = self.refractory_steps[i];
+                    spikes[i] = true;
+                    unrolled.counts[i] += 1;
+                } else {
+                    membrane[i] = v;
+                }
+            }
+            rates.update(spikes);
+        }
+        unrolled
+    }
+
+    /// Backpropagation through time, adding dL/dw into `gradient`.
+    /// `output_gradient` is dL/d(spike) of each output neuron at every step.
+    fn backward(&mut self, unrolled: &Unrolled, output_gradient: &[f32]) {
+        let n = self.params.len();
+        let gain = self.config.recurrent_gain;
+        // The rate estimator's window: a spike raises the presynaptic rate
+        // by `rate_per_spike` for the next `window` steps
+        let window = (math::round(self.config.rate_window_ms / self.dt_ms) as usize).max(1);
+        let rate_per_spike = 1000.0 / (window as f32 * self.dt_ms);
+
+        // dL/dv after each step, dL/dI of the last `window` steps (ring)
+        // and their sum
+        let mut membrane_gradient = vec![0.0f32; n];
+        let mut current_history = vec![vec![0.0f32; n]; window];
+        let mut future_currents = vec![0.0f32; n];
+        let mut current_gradient = vec![0.0f32; n];
+
+        for t in (0..unrolled.steps).rev() {
+            let row = t * n;
+            for i in 0..n {
+                let mut spike_gradient = 0.0;
+                if self.outputs.contains(&i) {
+                    spike_gradient += output_gradient[i - self.outputs.start];
+                }
+                for (post, &g) in future_currents.iter().enumerate() {
+                    let weight = self.weights[i][post];
+                    if g != 0.0 && post != i && weight.abs() > CONNECTION_EPSILON {
+                        spike_gradient += g * weight * gain * rate_per_spike;
+                    }
+                }
+
+                if !unrolled.active[row + i] {
+                    current_gradient[i] = 0.0;
+                    membrane_gradient[i] = 0.0;
+                    continue;
+                }
+                let params = &self.params[i];
+                let span = params.v_threshold - params.v_rest;
+                let distance = (unrolled.potentials[row + i] - params.v_threshold) / span;
+                let falloff = 1.0 + self.config.surrogate_slope * distance.abs();
+                let pseudo_derivative = 1.0 / (span * falloff * falloff);
+                let reset = if unrolled.spikes[row + i] { 0.0 } else { 1.0 };
+                let potential_gradient = spike_gradient * pseudo_derivative + membrane_gradient[i] * reset;
+                current_gradient[i] = potential_gradient * (1.0 - self.decay[i]) * params.resistance;
+                membrane_gradient[i] = potential_gradient * self.decay[i];
+            }
+
+            for pre in 0..n {
+                let rate = unrolled.rates[row + pre];
+                if rate == 0.0 {
+                    continue;
+                }
+                for (post, &g) in current_gradient.iter().enumerate() {
+                    if self.trainable[pre][post] {
+                        self.gradient[pre][post] += g * rate * gain;
+                    }
+                }
+            }
+
+            // Slide the window: step t joins, step t + window leaves
+            let slot = &mut current_history[t % window];
+            for ((sum, old), &new) in future_currents.iter_mut().zip(slot.iter_mut()).zip(&current_gradient) {
+                *sum += new - *old;
+                *old = new;
+            }
+        }
+    }
+}
+
+fn classify(counts: &[u32]) -> usize {
+    counts.iter()
+        .enumerate()
+        .fold(0, |best, (k, &count)| if count > counts[best] { k } else { best })
+}
+
+#[derive(Default)]
+struct StatsAccumulator {
+    samples: usize,
+    loss: f32,
+    correct: usize,
+}
+
+impl StatsAccumulator {
+    fn add(&mut self, loss: f32, correct: bool) {
+        self.samples += 1;
+        self.loss += loss;
+        self.correct += correct as usize;
+    }
+
+    fn finish(&self) -> TrainingStats {
+        let samples = self.samples.max(1) as f32;
+        TrainingStats { samples: self.samples, loss: self.loss / samples, accuracy: self.correct as f32 / samples }
+    }
+}
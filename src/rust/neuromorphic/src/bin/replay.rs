@@ -0,0 +1,33 @@
+//! Replays a recording saved from `NeuromorphicProcessor::stop_recording`
+//! natively and prints the replay report as JSON. Exits with status 1 if
+//! the replay diverged and 2 if the recording could not be replayed.
+//!
+//!     cargo run --bin replay -- session.json
+
+use std::{env, fs, process};
+
+use neuromorphic::{replay, Recording};
+
+fn main() {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("usage: replay <recording.json>");
+        process::exit(2);
+    };
+
+    let report = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<Recording>(&json).map_err(|e| e.to_string()))
+        .and_then(|recording| replay(&recording).map_err(|e| e.to_string()));
+    match report {
+        Ok(report) => {
+            println!("{}", serde_json::to_string_pretty(&report).expect("report serializes"));
+            if !report.identical {
+                process::exit(1);
+            }
+        }
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Trains the weights of a network description offline with surrogate
+//! gradients and writes the trained description, loadable with
+//! `NeuromorphicProcessor::from_model`. Progress goes to stderr as one JSON
+//! line per epoch. Exits with status 2 on error.
+//!
+//!     cargo run --release --bin train -- model.yaml run.json \
+//!         --idx train-images-idx3-ubyte train-labels-idx1-ubyte --out trained.json
+//!     cargo run --release --bin train -- model.json run.json --csv samples.csv label
+//!
+//! `run.json` holds `training` (`SurrogateConfig`), `encoder` (a
+//! `StaticEncoder` turning each sample into frames) and `test_fraction`.
+//! The first population of the model is the input layer: each sample must
+//! have one value per input neuron, and is fed on one channel per neuron of
+//! the network with the other channels silent. At inference, call
+//! `reset_state` before each sample and pass frames laid out the same way
+//! to `run_frames`.
+
+use std::{env, fs, process};
+
+use serde::Deserialize;
+
+use neuromorphic_core::datasets::{self, Dataset};
+use neuromorphic_core::encoding::{InputFrames, StaticEncoder};
+use neuromorphic_core::model::NetworkDescription;
+use neuromorphic_core::surrogate::{SurrogateConfig, SurrogateTrainer};
+
+#[derive(Deserialize)]
+struct TrainingRun {
+    #[serde(default)]
+    training: SurrogateConfig,
+    encoder: StaticEncoder,
+    #[serde(default = "default_test_fraction")]
+    test_fraction: f32,
+}
+
+fn default_test_fraction() -> f32 {
+    0.2
+}
+
+const USAGE: &str = "usage: train <model.json|model.yaml> <run.json> \
+                     (--idx <images> <labels> | --csv <file> <label-column>) [--out <trained.json>]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(error) = run(&args) {
+        eprintln!("{}", error);
+        process::exit(2);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let [model_path, run_path, source, rest @ ..] = args else {
+        return Err(USAGE.to_string());
+    };
+    let (first, second, rest) = match rest {
+        [first, second, rest @ ..] => (first, second, rest),
+        _ => return Err(USAGE.to_string()),
+    };
+    let out_path = match rest {
+        [] => None,
+        [flag, path] if flag == "--out" => Some(path),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let model_text = fs::read_to_string(model_path).map_err(|e| format!("{}: {}", model_path, e))?;
+    let description: NetworkDescription = if model_text.trim_start().starts_with('{') {
+        serde_json::from_str(&model_text).map_err(|e| format!("{}: {}", model_path, e))?
+    } else {
+        serde_yaml::from_str(&model_text).map_err(|e| format!("{}: {}", model_path, e))?
+    };
+    let run_text = fs::read_to_string(run_path).map_err(|e| format!("{}: {}", run_path, e))?;
+    let run: TrainingRun = serde_json::from_str(&run_text).map_err(|e| format!("{}: {}", run_path, e))?;
+
+    let samples = match source.as_str() {
+        "--idx" => datasets::load_idx_images(first.as_ref(), second.as_ref()).map_err(|e| e.to_string())?,
+        "--csv" => load_labelled_csv(first, second)?,
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let mut trainer = SurrogateTrainer::new(&description, run.training.clone()).map_err(|e| e.to_string())?;
+    let input = description.populations.first().map_or(0, |population| population.size);
+    if let Some(width) = samples.samples.iter().map(Vec::len).find(|&width| width != input) {
+        return Err(format!("samples have {} values but the input population has {} neurons", width, input));
+    }
+    let network_size = trainer.neuron_count();
+    let frames = samples.map(|sample| network_frames(&run.encoder.encode(&sample), network_size));
+    let (train, test) = frames.split(run.test_fraction, run.training.seed);
+
+    for epoch in 0..run.training.epochs {
+        let stats = trainer.train_epoch(&train, epoch).map_err(|e| e.to_string())?;
+        let test_stats = trainer.evaluate(&test).map_err(|e| e.to_string())?;
+        eprintln!("{}", serde_json::json!({ "epoch": epoch, "train": stats, "test": test_stats }));
+    }
+
+    let trained = serde_json::to_string_pretty(&trainer.export()).map_err(|e| e.to_string())?;
+    match out_path {
+        Some(path) => fs::write(path, trained).map_err(|e| format!("{}: {}", path, e)),
+        None => {
+            println!("{}", trained);
+            Ok(())
+        }
+    }
+}
+
+/// One sample per row, labelled by the integer column `label_column`.
+fn load_labelled_csv(path: &str, label_column: &str) -> Result<Dataset<Vec<f32>>, String> {
+    let mut table = datasets::load_csv(path.as_ref()).map_err(|e| format!("{}: {}", path, e))?;
+    let index = table.column_index(label_column)
+        .ok_or_else(|| format!("{}: no column '{}'", path, label_column))?;
+    let labels = table.take_column(index)
+        .ok_or_else(|| format!("{}: column '{}' is missing from some rows", path, label_column))?
+        .into_iter()
+        .map(|label| {
+            if label >= 0.0 && label.fract() == 0.0 {
+                Ok(label as usize)
+            } else {
+                Err(format!("{}: label {} is not a class index", path, label))
+            }
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+    Dataset::new(table.rows, labels).map_err(|e| e.to_string())
+}
+
+/// Places the channels of `frames` on the first neurons of the network, one
+/// neuron per channel.
+fn network_frames(frames: &InputFrames, network_size: usize) -> InputFrames {
+    let mut padded = InputFrames::new(network_size, frames.steps());
+    for step in 0..frames.steps() {
+        padded.frame_mut(step)[..frames.channels].copy_from_slice(frames.frame(step));
+    }
+    padded
+}
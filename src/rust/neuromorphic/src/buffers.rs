@@ -0,0 +1,232 @@
+//! Zero-copy data exchange with JavaScript. Instead of passing arrays by
+//! value (copied across the boundary) or as JSON, the processor owns input
+//! and output buffers in wasm linear memory and hands out pointers to them;
+//! JavaScript wraps those in typed-array views:
+//!
+//! ```js
+//! const input = new Float32Array(memory.buffer, processor.input_buffer(n), n);
+//! input.set(samples);                      // or fill it in place
+//! processor.process_input_buffer();
+//! const activity = new Float32Array(memory.buffer,
+//!     processor.activity_buffer(), processor.activity_len());
+//! ```
+//!
+//! Buffer lifecycle:
+//!
+//! 1. `input_buffer(len)` sizes the input buffer (zero-filled) and returns
+//!    its address. The same address is returned while `len` does not grow,
+//!    so a view can be written and processed repeatedly.
+//! 2. `process_input_buffer` and `run_frames_buffer` consume the input
+//!    buffer, which keeps its contents, and overwrite the activity buffer
+//!    and (with `set_raster_capture(true)`) the spike raster of the call.
+//!    The raster keeps at most `RASTER_BYTE_LIMIT` bytes per call; later
+//!    steps of a longer call are not captured.
+//! 3. Output views are valid until the next call into the processor.
+//! 4. Any call that allocates may grow wasm memory, which detaches every
+//!    existing view of `memory.buffer`. Recreate views from fresh
+//!    pointers after each processing call rather than caching them, and
+//!    never use a view after `free()`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{to_json, NeuromorphicError};
+use crate::{NeuromorphicProcessor, NeuromorphicResult};
+
+/// Upper bound on the spike raster of one call (64 MiB), so long
+/// `run_frames` calls cannot grow it without limit.
+pub const RASTER_BYTE_LIMIT: usize = 64 << 20;
+
+/// Buffers shared with JavaScript; not part of the processor snapshot.
+#[derive(Default)]
+pub(crate) struct IoBuffers {
+    input: Vec<f32>,
+    /// Population activity per step of the last buffered call.
+    activity: Vec<f32>,
+    /// Step-major spikes (0 or 1 per neuron) of the last input call.
+    pub(crate) raster: Vec<u8>,
+    pub(crate) capture_raster: bool,
+    /// Full result of the last `process_input_buffer` call.
+    last_result: Option<NeuromorphicResult>,
+}
+
+impl IoBuffers {
+    /// Appends a step to the raster when capturing and the step fits
+    /// within `RASTER_BYTE_LIMIT`.
+    pub(crate) fn capture(&mut self, spikes: &[bool]) {
+        if self.capture_raster && self.raster.len() + spikes.len() <= RASTER_BYTE_LIMIT {
+            self.raster.extend(spikes.iter().map(|&spike| spike as u8));
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl NeuromorphicProcessor {
+    /// Address of an input buffer of `len` floats, zero-filled.
+    #[wasm_bindgen]
+    pub fn input_buffer(&mut self, len: usize) -> *mut f32 {
+        self.io.input.clear();
+        self.io.input.resize(len, 0.0);
+        self.io.input.as_mut_ptr()
+    }
+
+    #[wasm_bindgen]
+    pub fn input_len(&self) -> usize {
+        self.io.input.len()
+    }
+
+    /// `process_input` on the input buffer. The activity per step goes to
+    /// the activity buffer and the mean activation is returned; the rest
+    /// of the result (pattern recognition, learning delta, timing,
+    /// operation counts and energy) is kept for `buffer_result_json`.
+    #[wasm_bindgen]
+    pub fn process_input_buffer(&mut self) -> Result<f32, NeuromorphicError> {
+        let input = std::mem::take(&mut self.io.input);
+        let result = self.run_input(&input);
+        self.io.input = input;
+        let result = result?;
+        self.io.activity.clear();
+        self.io.activity.extend_from_slice(&result.pattern.spikes);
+        let activation = result.pattern.activation_strength;
+        self.io.last_result = Some(result);
+        Ok(activation)
+    }
+
+    /// The `process_input` result of the last successful
+    /// `process_input_buffer` call, as JSON.
+    #[wasm_bindgen]
+    pub fn buffer_result_json(&self) -> Result<String, NeuromorphicError> {
+        let result = self.io.last_result.as_ref()
+            .ok_or_else(|| NeuromorphicError::NotFound("process_input_buffer result".to_string()))?;
+        to_json(result)
+    }
+
+    /// `run_frames` on the input buffer, holding `channels` currents per
+    /// step. The activity per step goes to the activity buffer; returns the
+    /// number of steps run.
+    #[wasm_bindgen]
+    pub fn run_frames_buffer(&mut self, channels: usize, learn: bool) -> Result<usize, NeuromorphicError> {
+        let input = std::mem::take(&mut self.io.input);
+        let activity = self.run_frames(&input, channels, learn);
+        self.io.input = input;
+        self.io.activity = activity?;
+        Ok(self.io.activity.len())
+    }
+
+    #[wasm_bindgen]
+    pub fn activity_buffer(&self) -> *const f32 {
+        self.io.activity.as_ptr()
+    }
+
+    #[wasm_bindgen]
+    pub fn activity_len(&self) -> usize {
+        self.io.activity.len()
+    }
+
+    /// Records the spikes of every step of each input call for
+    /// `raster_buffer` (one byte per neuron and step). Off by default.
+    #[wasm_bindgen]
+    pub fn set_raster_capture(&mut self, enabled: bool) {
+        self.io.capture_raster = enabled;
+        self.io.raster.clear();
+    }
+
+    /// Spike raster of the last input call: `raster_steps()` rows of
+    /// `network_size` bytes. Fewer rows than steps run means the call
+    /// exceeded `RASTER_BYTE_LIMIT`.
+    #[wasm_bindgen]
+    pub fn raster_buffer(&self) -> *const u8 {
+        self.io.raster.as_ptr()
+    }
+
+    #[wasm_bindgen]
+    pub fn raster_len(&self) -> usize {
+        self.io.raster.len()
+    }
+
+    #[wasm_bindgen]
+    pub fn raster_steps(&self) -> usize {
+        self.io.raster.len() / self.network_size
+    }
+
+    /// Address of the samples of a probe, oldest first; `probe_len` gives
+    /// the count. The next sample taken may move them.
+    #[wasm_bindgen]
+    pub fn probe_buffer(&mut self, probe_id: u32) -> Result<*const f32, NeuromorphicError> {
+        self.probes.get_mut(probe_id)
+            .map(|probe| probe.contiguous_values().as_ptr())
+            .ok_or_else(|| NeuromorphicError::NotFound(format!("probe {}", probe_id)))
+    }
+
+    #[wasm_bindgen]
+    pub fn probe_len(&self, probe_id: u32) -> Result<usize, NeuromorphicError> {
+        self.probe(probe_id).map(|probe| probe.sample_count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_input(processor: &mut NeuromorphicProcessor, values: &[f32]) {
+        let pointer = processor.input_buffer(values.len());
+        // SAFETY: `input_buffer` returned a buffer of `values.len()` floats
+        unsafe { std::slice::from_raw_parts_mut(pointer, values.len()) }.copy_from_slice(values);
+    }
+
+    fn activity(processor: &NeuromorphicProcessor) -> Vec<f32> {
+        // SAFETY: the activity buffer holds `activity_len()` floats until the next call
+        unsafe { std::slice::from_raw_parts(processor.activity_buffer(), processor.activity_len()) }.to_vec()
+    }
+
+    #[test]
+    fn buffered_calls_match_the_copying_api() {
+        let mut buffered = NeuromorphicProcessor::new(16).unwrap();
+        let mut copying = NeuromorphicProcessor::new(16).unwrap();
+        let input: Vec<f32> = (0..30).map(|i| (i % 4) as f32).collect();
+
+        assert!(matches!(buffered.buffer_result_json(), Err(NeuromorphicError::NotFound(_))));
+        write_input(&mut buffered, &input);
+        let activation = buffered.process_input_buffer().unwrap();
+        let expected: NeuromorphicResult = serde_json::from_str(&copying.process_input(&input).unwrap()).unwrap();
+        let result: NeuromorphicResult = serde_json::from_str(&buffered.buffer_result_json().unwrap()).unwrap();
+        assert_eq!(activation, expected.pattern.activation_strength);
+        assert_eq!(activity(&buffered), expected.pattern.spikes);
+        assert_eq!(result.pattern.spikes, expected.pattern.spikes);
+        assert_eq!(result.learning_delta, expected.learning_delta);
+        assert_eq!(result.pattern_recognition, expected.pattern_recognition);
+        assert_eq!(result.operations, expected.operations);
+        assert_eq!(result.energy, expected.energy);
+
+        let frames: Vec<f32> = (0..16 * 12).map(|i| ((i * 5) % 7) as f32).collect();
+        write_input(&mut buffered, &frames);
+        assert_eq!(buffered.run_frames_buffer(16, true).unwrap(), 12);
+        assert_eq!(activity(&buffered), copying.run_frames(&frames, 16, true).unwrap());
+        assert_eq!(buffered.input_len(), frames.len());
+    }
+
+    #[test]
+    fn raster_matches_the_activity_of_the_call() {
+        let mut processor = NeuromorphicProcessor::new(8).unwrap();
+        processor.set_raster_capture(true);
+        write_input(&mut processor, &[40.0; 8 * 10]);
+        processor.run_frames_buffer(8, false).unwrap();
+        assert_eq!(processor.raster_steps(), 10);
+        // SAFETY: the raster holds `raster_len()` bytes until the next call
+        let raster = unsafe { std::slice::from_raw_parts(processor.raster_buffer(), processor.raster_len()) };
+        let counts: Vec<f32> = raster.chunks(8).map(|row| row.iter().map(|&s| s as f32).sum::<f32>() / 8.0).collect();
+        assert_eq!(counts, activity(&processor));
+
+        processor.set_raster_capture(false);
+        processor.run_frames(&[40.0; 8], 8, false).unwrap();
+        assert_eq!(processor.raster_len(), 0);
+    }
+
+    #[test]
+    fn raster_stops_at_the_byte_limit() {
+        let mut io = IoBuffers { capture_raster: true, ..IoBuffers::default() };
+        io.raster.resize(RASTER_BYTE_LIMIT - 12, 0);
+        io.capture(&[true; 8]);
+        io.capture(&[true; 8]);
+        assert_eq!(io.raster.len(), RASTER_BYTE_LIMIT - 4);
+    }
+}
@@ -0,0 +1,129 @@
+use wasm_bindgen::prelude::*;
+
+use neuromorphic_core::competitive::{CompetitiveConfig, CompetitiveNetwork, LabelAssignment};
+
+use crate::error::{parse_json, to_json, NeuromorphicError};
+use crate::log;
+
+/// Diehl & Cook-style unsupervised learner: train on unlabelled samples,
+/// then present labelled samples to assign classes to neurons and classify.
+#[wasm_bindgen]
+pub struct CompetitiveLearner {
+    network: CompetitiveNetwork,
+    labels: LabelAssignment,
+}
+
+#[wasm_bindgen]
+impl CompetitiveLearner {
+    /// `config_json` holds `CompetitiveConfig` fields; missing fields take
+    /// their defaults (784 inputs, 100 excitatory neurons, soft WTA).
+    #[wasm_bindgen(constructor)]
+    pub fn new(config_json: &str, class_count: usize) -> Result<CompetitiveLearner, NeuromorphicError> {
+        let config: CompetitiveConfig = parse_json(config_json)?;
+        if !config.is_valid() {
+            return Err(NeuromorphicError::InvalidConfig("invalid competitive learning configuration".to_string()));
+        }
+        if class_count == 0 {
+            return Err(NeuromorphicError::InvalidInput("class_count must be at least 1".to_string()));
+        }
+
+        console_log!("🏆 Competitive learner: {} inputs -> {} excitatory neurons",
+                     config.input_size, config.excitatory_size);
+        Ok(CompetitiveLearner {
+            labels: LabelAssignment::new(class_count, config.excitatory_size),
+            network: CompetitiveNetwork::new(config),
+        })
+    }
+
+    /// Presents an unlabelled sample with STDP and threshold adaptation on
+    /// and returns the excitatory spike counts.
+    #[wasm_bindgen]
+    pub fn train(&mut self, input: &[f32]) -> Result<Vec<u32>, NeuromorphicError> {
+        self.check_input(input)?;
+        Ok(self.network.present(input, true))
+    }
+
+    /// Presents a sample with learning off and accumulates its response for
+    /// class `label`; call `assign_labels` afterwards.
+    #[wasm_bindgen]
+    pub fn record_label(&mut self, input: &[f32], label: usize) -> Result<Vec<u32>, NeuromorphicError> {
+        self.check_input(input)?;
+        if label >= self.labels.class_count() {
+            return Err(NeuromorphicError::InvalidInput(format!(
+                "label {} is out of range for {} classes", label, self.labels.class_count()
+            )));
+        }
+        let counts = self.network.present(input, false);
+        self.labels.record(&counts, label);
+        Ok(counts)
+    }
+
+    /// Assigns each neuron its most responsive class; -1 for neurons that
+    /// never fired during labelling.
+    #[wasm_bindgen]
+    pub fn assign_labels(&mut self) -> Vec<i32> {
+        self.labels.assign().iter().map(|label| label.map_or(-1, |class| class as i32)).collect()
+    }
+
+    /// Predicted class of a sample, or -1 if no labelled neuron responded.
+    #[wasm_bindgen]
+    pub fn classify(&mut self, input: &[f32]) -> Result<i32, NeuromorphicError> {
+        self.check_input(input)?;
+        let counts = self.network.present(input, false);
+        Ok(self.labels.classify(&counts).map_or(-1, |class| class as i32))
+    }
+
+    /// Input weights onto excitatory neuron `neuron` (its receptive field).
+    #[wasm_bindgen]
+    pub fn get_receptive_field(&self, neuron: usize) -> Result<Vec<f32>, NeuromorphicError> {
+        if neuron >= self.network.config.excitatory_size {
+            return Err(NeuromorphicError::NotFound(format!("excitatory neuron {}", neuron)));
+        }
+        Ok(self.network.weights().iter().map(|row| row[neuron]).collect())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_thresholds(&self) -> Vec<f32> {
+        self.network.theta().to_vec()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_config(&self) -> Result<String, NeuromorphicError> {
+        to_json(&self.network.config)
+    }
+
+    fn check_input(&self, input: &[f32]) -> Result<(), NeuromorphicError> {
+        if input.len() != self.network.config.input_size {
+            return Err(NeuromorphicError::InvalidInput(format!(
+                "expected {} inputs, got {}", self.network.config.input_size, input.len()
+            )));
+        }
+        if input.iter().any(|x| !x.is_finite()) {
+            return Err(NeuromorphicError::InvalidInput("input contains non-finite values".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn out_of_range_labels_leave_the_network_untouched() {
+        let config = r#"{"input_size": 8, "excitatory_size": 3, "presentation_steps": 20}"#;
+        let mut learner = CompetitiveLearner::new(config, 2).unwrap();
+        let mut reference = CompetitiveLearner::new(config, 2).unwrap();
+        let input = [1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0];
+
+        let error = learner.record_label(&input, 2).unwrap_err();
+        assert!(matches!(error, NeuromorphicError::InvalidInput(_)));
+        assert!(matches!(learner.record_label(&input[..4], 0), Err(NeuromorphicError::InvalidInput(_))));
+
+        // The rejected calls must not have advanced the network or its
+        // generator, so both learners respond identically from here on
+        assert_eq!(learner.record_label(&input, 1).unwrap(), reference.record_label(&input, 1).unwrap());
+        assert_eq!(learner.train(&input).unwrap(), reference.train(&input).unwrap());
+        assert_eq!(learner.get_thresholds(), reference.get_thresholds());
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+
+use neuromorphic_core::energy::OperationCounts;
+use neuromorphic_core::link::{Link, LinkConfig};
+
+use crate::error::{parse_json, to_json, NeuromorphicError};
+use crate::{log, now_ms, NeuromorphicProcessor};
+
+/// Network of networks: named processors whose populations feed each other
+/// through delayed links, stepped together in lockstep. Processors are
+/// moved in with `add_network` (configure stimuli, probes and modulators
+/// first) and handed back by `take_network`.
+#[wasm_bindgen]
+pub struct NetworkCoordinator {
+    networks: Vec<(String, NeuromorphicProcessor)>,
+    links: Vec<Link>,
+}
+
+impl Default for NetworkCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl NetworkCoordinator {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> NetworkCoordinator {
+        NetworkCoordinator { networks: Vec::new(), links: Vec::new() }
+    }
+
+    #[wasm_bindgen]
+    pub fn add_network(&mut self, name: &str, processor: NeuromorphicProcessor) -> Result<(), NeuromorphicError> {
+        if self.index_of(name).is_some() {
+            return Err(NeuromorphicError::InvalidConfig(format!("duplicate network '{}'", name)));
+        }
+        console_log!("🕸️ Coordinator: network '{}' joined with {} neurons", name, processor.network_size);
+        self.networks.push((name.to_string(), processor));
+        Ok(())
+    }
+
+    /// Removes a network, and every link to or from it, and returns it.
+    #[wasm_bindgen]
+    pub fn take_network(&mut self, name: &str) -> Result<NeuromorphicProcessor, NeuromorphicError> {
+        let index = self.find(name)?;
+        self.links.retain(|link| link.config.source != name && link.config.target != name);
+        Ok(self.networks.remove(index).1)
+    }
+
+    #[wasm_bindgen]
+    pub fn get_network_names(&self) -> Result<String, NeuromorphicError> {
+        to_json(&self.networks.iter().map(|(name, _)| name).collect::<Vec<_>>())
+    }
+
+    /// Links a population of one network onto a population of another (or
+    /// the same) network: `LinkConfig` with a model connector, weight and
+    /// `delay_ms`. Returns the number of synapses.
+    #[wasm_bindgen]
+    pub fn link(&mut self, config_json: &str) -> Result<usize, NeuromorphicError> {
+        let config: LinkConfig = parse_json(config_json)?;
+        if self.links.iter().any(|link| link.config.id == config.id) {
+            return Err(NeuromorphicError::InvalidConfig(format!("duplicate link '{}'", config.id)));
+        }
+        if !config.delay_ms.is_finite() || config.delay_ms < 0.0 {
+            return Err(NeuromorphicError::InvalidConfig("link delay_ms must be finite and non-negative".to_string()));
+        }
+
+        let source = &self.networks[self.find(&config.source)?].1;
+        let target = &self.networks[self.find(&config.target)?].1;
+        let link = Link::new(config, &source.layout, &target.layout, source.dt_ms)?;
+        let synapses = link.synapse_count();
+        console_log!("🔗 Link '{}': {}.{} -> {}.{}, {} synapses, {} step delay",
+                     link.config.id, link.config.source, link.config.source_population,
+                     link.config.target, link.config.target_population, synapses, link.delay_steps());
+        self.links.push(link);
+        Ok(synapses)
+    }
+
+    #[wasm_bindgen]
+    pub fn unlink(&mut self, link_id: &str) -> bool {
+        let before = self.links.len();
+        self.links.retain(|link| link.config.id != link_id);
+        self.links.len() != before
+    }
+
+    /// Steps every network `steps` times in lockstep: each step delivers
+    /// the link currents due, advances all networks (with their own
+    /// stimuli and recurrence), then sends the new spikes down the links.
+    /// With `learn` each network's plasticity runs once at the end. Returns
+    /// the population activity per step of each network, by name. Linked
+    /// runs are not recorded, so they are rejected while any member network
+    /// is recording.
+    #[wasm_bindgen]
+    pub fn run(&mut self, steps: usize, learn: bool) -> Result<String, NeuromorphicError> {
+        let Some(dt_ms) = self.networks.first().map(|(_, network)| network.dt_ms) else {
+            return Err(NeuromorphicError::InvalidInput("coordinator has no networks".to_string()));
+        };
+        if self.networks.iter().any(|(_, network)| network.dt_ms != dt_ms) {
+            return Err(NeuromorphicError::InvalidConfig("linked networks must share dt_ms".to_string()));
+        }
+        if let Some((name, _)) = self.networks.iter().find(|(_, network)| network.is_recording()) {
+            return Err(NeuromorphicError::InvalidConfig(format!(
+                "network '{}' is recording; stop the recording before a linked run", name
+            )));
+        }
+        let routes: Vec<(usize, usize)> = self.links.iter()
+            .map(|link| Ok((self.find(&link.config.source)?, self.find(&link.config.target)?)))
+            .collect::<Result<_, NeuromorphicError>>()?;
+
+        let start_time = now_ms();
+        for (_, network) in &mut self.networks {
+            network.call_operations = OperationCounts::default();
+            network.call_novelty.clear();
+            network.io.raster.clear();
+        }
+        let mut activity = vec![Vec::with_capacity(steps); self.networks.len()];
+        for step in 0..steps {
+            let mut currents: Vec<Vec<f32>> = self.networks.iter()
+                .map(|(_, network)| vec![0.0; network.network_size])
+                .collect();
+            for (link, &(_, target)) in self.links.iter_mut().zip(&routes) {
+                link.deliver(&mut currents[target]);
+            }
+
+            let spikes: Vec<Vec<bool>> = self.networks.iter_mut()
+                .zip(currents)
+                .map(|((_, network), currents)| network.step_linked(start_time, step, currents))
+                .collect();
+            for (index, spikes) in spikes.iter().enumerate() {
+                activity[index].push(spikes.iter().filter(|&&s| s).count() as f32 / spikes.len() as f32);
+            }
+
+            for (link, &(source, _)) in self.links.iter_mut().zip(&routes) {
+                link.transmit(&spikes[source]);
+            }
+        }
+
+        if learn && steps > 0 {
+            for ((_, network), activity) in self.networks.iter_mut().zip(&activity) {
+                network.apply_learning(activity.iter().sum::<f32>() / steps as f32);
+                network.apply_structural_plasticity();
+            }
+        }
+
+        let by_name: BTreeMap<&str, &Vec<f32>> = self.networks.iter()
+            .map(|(name, _)| name.as_str())
+            .zip(&activity)
+            .collect();
+        to_json(&by_name)
+    }
+
+    /// Drops the spikes still travelling along the links.
+    #[wasm_bindgen]
+    pub fn reset_links(&mut self) {
+        for link in &mut self.links {
+            link.reset();
+        }
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.networks.iter().position(|(network, _)| network == name)
+    }
+
+    fn find(&self, name: &str) -> Result<usize, NeuromorphicError> {
+        self.index_of(name).ok_or_else(|| NeuromorphicError::NotFound(format!("network '{}'", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coordinator() -> NetworkCoordinator {
+        let mut coordinator = NetworkCoordinator::new();
+        coordinator.add_network("a", NeuromorphicProcessor::new(8).unwrap()).unwrap();
+        coordinator.add_network("b", NeuromorphicProcessor::new(8).unwrap()).unwrap();
+        let link = r#"{"id": "ab", "source": "a", "target": "b", "source_population": "network",
+                       "target_population": "network", "connector": {"kind": "one_to_one"}, "delay_ms": 2}"#;
+        assert_eq!(coordinator.link(link).unwrap(), 8);
+        coordinator
+    }
+
+    #[test]
+    fn runs_are_rejected_while_a_member_is_recording() {
+        let mut coordinator = coordinator();
+        let mut network = coordinator.take_network("b").unwrap();
+        network.start_recording().unwrap();
+        coordinator.add_network("b", network).unwrap();
+        assert!(matches!(coordinator.run(5, false), Err(NeuromorphicError::InvalidConfig(_))));
+
+        let mut network = coordinator.take_network("b").unwrap();
+        network.stop_recording().unwrap();
+        coordinator.add_network("b", network).unwrap();
+        let activity: BTreeMap<String, Vec<f32>> = serde_json::from_str(&coordinator.run(5, false).unwrap()).unwrap();
+        assert_eq!(activity.keys().collect::<Vec<_>>(), ["a", "b"]);
+        assert!(activity.values().all(|steps| steps.len() == 5));
+    }
+
+    #[test]
+    fn links_require_known_networks_and_unique_ids() {
+        let mut coordinator = coordinator();
+        let duplicate = r#"{"id": "ab", "source": "b", "target": "a", "source_population": "network",
+                            "target_population": "network", "connector": {"kind": "one_to_one"}}"#;
+        assert!(matches!(coordinator.link(duplicate), Err(NeuromorphicError::InvalidConfig(_))));
+        assert!(matches!(coordinator.link(&duplicate.replace(r#""ab""#, r#""ca""#).replace(r#""b""#, r#""c""#)), Err(NeuromorphicError::NotFound(_))));
+
+        // Taking a network drops its links
+        coordinator.take_network("a").unwrap();
+        assert!(!coordinator.unlink("ab"));
+        assert!(matches!(NetworkCoordinator::new().run(1, false), Err(NeuromorphicError::InvalidInput(_))));
+    }
+}
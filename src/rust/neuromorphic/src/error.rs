@@ -0,0 +1,68 @@
+use thiserror::Error;
+use wasm_bindgen::JsValue;
+
+use neuromorphic_core::model::ModelError;
+
+/// Errors surfaced to JavaScript. Each becomes an `Error` whose `name` is
+/// `NeuromorphicError`, with a `code` naming the variant and a `category` of
+/// `"input"` for caller mistakes or `"internal"` for failures inside the
+/// processor.
+#[derive(Error, Debug)]
+pub enum NeuromorphicError {
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Parse error: {0}")]
+    ParseError(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+}
+
+impl NeuromorphicError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            NeuromorphicError::InvalidInput(_) => "INVALID_INPUT",
+            NeuromorphicError::InvalidConfig(_) => "INVALID_CONFIG",
+            NeuromorphicError::ParseError(_) => "PARSE_ERROR",
+            NeuromorphicError::NotFound(_) => "NOT_FOUND",
+            NeuromorphicError::SerializationError(_) => "SERIALIZATION_ERROR",
+        }
+    }
+
+    pub fn category(&self) -> &'static str {
+        match self {
+            NeuromorphicError::SerializationError(_) => "internal",
+            _ => "input",
+        }
+    }
+}
+
+impl From<ModelError> for NeuromorphicError {
+    fn from(error: ModelError) -> Self {
+        NeuromorphicError::InvalidConfig(error.to_string())
+    }
+}
+
+impl From<NeuromorphicError> for JsValue {
+    fn from(error: NeuromorphicError) -> Self {
+        let js_error = js_sys::Error::new(&error.to_string());
+        js_error.set_name("NeuromorphicError");
+        // Setting properties on a fresh Error object cannot fail
+        let _ = js_sys::Reflect::set(&js_error, &"code".into(), &error.code().into());
+        let _ = js_sys::Reflect::set(&js_error, &"category".into(), &error.category().into());
+        js_error.into()
+    }
+}
+
+/// Parsing of caller-supplied JSON.
+pub(crate) fn parse_json<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, NeuromorphicError> {
+    serde_json::from_str(json).map_err(|e| NeuromorphicError::ParseError(e.to_string()))
+}
+
+/// Serialisation of processor output.
+pub(crate) fn to_json<T: serde::Serialize>(value: &T) -> Result<String, NeuromorphicError> {
+    serde_json::to_string(value).map_err(|e| NeuromorphicError::SerializationError(e.to_string()))
+}
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use neuromorphic_core::forecast::{ErrorAccumulator, ErrorMetrics, ForecastConfig, Forecaster, RidgeAccumulator};
+
+use crate::error::{parse_json, to_json, NeuromorphicError};
+use crate::recording::RecordedCall;
+use crate::{log, NeuromorphicProcessor};
+
+#[derive(Serialize, Deserialize)]
+pub struct ForecastFitReport {
+    pub training_samples: usize,
+    pub features: usize,
+    /// Errors per lead on the held-out tail of the series.
+    pub validation: Vec<ErrorMetrics>,
+}
+
+#[wasm_bindgen]
+impl NeuromorphicProcessor {
+    /// Trains readouts predicting `series` 1..=`horizon` steps ahead from
+    /// the reservoir state (`ForecastConfig` fields in `config_json`;
+    /// missing fields take their defaults). The series drives the network
+    /// one value per step; the tail set by `validation_fraction` is held out
+    /// and its MAE/RMSE per lead returned. Afterwards the reservoir has seen
+    /// the whole series, so `forecast` continues from its end.
+    #[wasm_bindgen]
+    pub fn fit(&mut self, series: &[f32], config_json: &str) -> Result<String, NeuromorphicError> {
+        let config: ForecastConfig = parse_json(config_json)?;
+        if !config.is_valid() {
+            return Err(NeuromorphicError::InvalidConfig("invalid forecast configuration".to_string()));
+        }
+        check_series(series)?;
+        let split = series.len() - (series.len() as f32 * config.validation_fraction) as usize;
+        if split <= config.washout + config.horizon {
+            return Err(NeuromorphicError::InvalidInput(format!(
+                "{} training values do not cover washout {} plus horizon {}", split, config.washout, config.horizon
+            )));
+        }
+
+        let start_time = self.begin_call();
+        let mut forecaster = Forecaster::new(config, self.network_size, self.dt_ms, &series[..split]);
+        let mut ridge = RidgeAccumulator::new(forecaster.feature_count(), config.horizon);
+        self.drive_forecaster(&mut forecaster, &series[..split], start_time, 0, |t, forecaster| {
+            if t >= config.washout && t + config.horizon < split {
+                let targets: Vec<f64> = (1..=config.horizon)
+                    .map(|lead| forecaster.normalize(series[t + lead]) as f64)
+                    .collect();
+                ridge.add(forecaster.features(), &targets);
+            }
+        });
+        if !forecaster.train(&ridge) {
+            return Err(NeuromorphicError::InvalidConfig("readout is singular; increase ridge".to_string()));
+        }
+
+        let mut errors = ErrorAccumulator::new(config.horizon);
+        self.drive_forecaster(&mut forecaster, &series[split..], start_time, split, |t, forecaster| {
+            accumulate_errors(&mut errors, forecaster, &series[split..], t);
+        });
+        self.forecaster = Some(forecaster);
+
+        let report = ForecastFitReport {
+            training_samples: ridge.samples,
+            features: ridge.dim(),
+            validation: errors.metrics(),
+        };
+        console_log!("📈 Forecaster fitted on {} samples, horizon {}", report.training_samples, config.horizon);
+        self.record(|| RecordedCall::Fit { series: series.to_vec(), config_json: config_json.to_string() },
+                    start_time, &report);
+        to_json(&report)
+    }
+
+    /// Predicted next `horizon` values (at most the trained horizon) after
+    /// the last value seen by `fit`, `observe` or `evaluate`.
+    #[wasm_bindgen]
+    pub fn forecast(&self, horizon: usize) -> Result<Vec<f32>, NeuromorphicError> {
+        let forecaster = self.trained_forecaster()?;
+        if horizon == 0 || horizon > forecaster.config.horizon {
+            return Err(NeuromorphicError::InvalidInput(format!(
+                "horizon must be between 1 and the trained horizon {}", forecaster.config.horizon
+            )));
+        }
+        let mut predictions = forecaster.predict();
+        predictions.truncate(horizon);
+        Ok(predictions)
+    }
+
+    /// Feeds new values of the stream to the reservoir without training.
+    #[wasm_bindgen]
+    pub fn observe(&mut self, values: &[f32]) -> Result<(), NeuromorphicError> {
+        self.trained_forecaster()?;
+        check_series(values)?;
+        let start_time = self.begin_call();
+        let mut forecaster = self.forecaster.take().expect("checked above");
+        self.drive_forecaster(&mut forecaster, values, start_time, 0, |_, _| {});
+        self.forecaster = Some(forecaster);
+        self.record(|| RecordedCall::Observe { values: values.to_vec() }, start_time, &());
+        Ok(())
+    }
+
+    /// Feeds `series` like `observe` while scoring the forecasts made at
+    /// each step against the later values, and returns MAE/RMSE per lead.
+    #[wasm_bindgen]
+    pub fn evaluate(&mut self, series: &[f32]) -> Result<String, NeuromorphicError> {
+        let horizon = self.trained_forecaster()?.config.horizon;
+        check_series(series)?;
+        let start_time = self.begin_call();
+        let mut forecaster = self.forecaster.take().expect("checked above");
+        let mut errors = ErrorAccumulator::new(horizon);
+        self.drive_forecaster(&mut forecaster, series, start_time, 0, |t, forecaster| {
+            accumulate_errors(&mut errors, forecaster, series, t);
+        });
+        self.forecaster = Some(forecaster);
+
+        let metrics = errors.metrics();
+        self.record(|| RecordedCall::Evaluate { series: series.to_vec() }, start_time, &metrics);
+        to_json(&metrics)
+    }
+}
+
+impl NeuromorphicProcessor {
+    fn trained_forecaster(&self) -> Result<&Forecaster, NeuromorphicError> {
+        self.forecaster.as_ref()
+            .filter(|forecaster| forecaster.is_trained())
+            .ok_or_else(|| NeuromorphicError::NotFound("trained forecaster; call fit first".to_string()))
+    }
+
+    /// Presents `values` one per step with recurrent input on and learning
+    /// off, updating the forecaster's features and calling `visit` after
+    /// each step.
+    fn drive_forecaster(
+        &mut self,
+        forecaster: &mut Forecaster,
+        values: &[f32],
+        start_time: u64,
+        first_step: usize,
+        mut visit: impl FnMut(usize, &Forecaster),
+    ) {
+        for (t, &value) in values.iter().enumerate() {
+            self.current_time = self.time_at_step(start_time, first_step + t);
+            let mut input_currents = vec![0.0; self.network_size];
+            forecaster.input_currents(value, &mut input_currents);
+
+            self.update_modulation();
+            let mut spikes = vec![false; self.network_size];
+            self.step_network(&mut input_currents, &mut spikes, true);
+            self.sample_probes();
+            forecaster.observe(value, &spikes, &self.neurons);
+            visit(t, forecaster);
+        }
+    }
+}
+
+/// Scores the forecast made after `series[t]` against the values that
+/// followed it in `series`.
+fn accumulate_errors(errors: &mut ErrorAccumulator, forecaster: &Forecaster, series: &[f32], t: usize) {
+    for (lead, predicted) in (1..).zip(forecaster.predict()) {
+        if let Some(&actual) = series.get(t + lead) {
+            errors.add(lead, predicted, actual);
+        }
+    }
+}
+
+fn check_series(series: &[f32]) -> Result<(), NeuromorphicError> {
+    if series.is_empty() {
+        return Err(NeuromorphicError::InvalidInput("series is empty".to_string()));
+    }
+    if let Some(index) = series.iter().position(|x| !x.is_finite()) {
+        return Err(NeuromorphicError::InvalidInput(format!("series[{}] is not finite", index)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(len: usize) -> Vec<f32> {
+        (0..len).map(|t| (t as f32 * 0.2).sin()).collect()
+    }
+
+    #[test]
+    fn fitted_forecaster_predicts_a_sine_wave() {
+        let mut processor = NeuromorphicProcessor::new(32).unwrap();
+        let report: ForecastFitReport = serde_json::from_str(
+            &processor.fit(&sine(500), r#"{"horizon": 3, "washout": 20}"#).unwrap()
+        ).unwrap();
+        assert_eq!(report.features, 34);
+        assert_eq!(report.validation.len(), 3);
+        // Repeating the last value would be off by about 0.13 per step ahead
+        assert!(report.validation.iter().all(|m| m.mae < 0.05 * m.lead as f32), "{:?}", report.validation);
+
+        assert_eq!(processor.forecast(2).unwrap().len(), 2);
+        assert!(matches!(processor.forecast(4), Err(NeuromorphicError::InvalidInput(_))));
+        processor.observe(&sine(3)).unwrap();
+        let metrics: Vec<ErrorMetrics> = serde_json::from_str(&processor.evaluate(&sine(50)).unwrap()).unwrap();
+        assert_eq!(metrics[0].samples, 49);
+    }
+
+    #[test]
+    fn forecasting_requires_a_fit_and_a_usable_series() {
+        let mut processor = NeuromorphicProcessor::new(8).unwrap();
+        assert!(matches!(processor.forecast(1), Err(NeuromorphicError::NotFound(_))));
+        assert!(matches!(processor.observe(&[1.0]), Err(NeuromorphicError::NotFound(_))));
+        assert!(matches!(processor.fit(&[1.0, f32::NAN], "{}"), Err(NeuromorphicError::InvalidInput(_))));
+        assert!(matches!(processor.fit(&sine(40), r#"{"washout": 50}"#), Err(NeuromorphicError::InvalidInput(_))));
+        assert!(matches!(processor.fit(&sine(40), r#"{"horizon": 0}"#), Err(NeuromorphicError::InvalidConfig(_))));
+    }
+}
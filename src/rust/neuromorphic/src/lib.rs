@@ -1,17 +1,73 @@
+use std::collections::VecDeque;
+
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
 
+mod error;
+
+pub use error::NeuromorphicError;
+use buffers::IoBuffers;
+use error::{parse_json, to_json};
+
+use neuromorphic_core::anomaly::{AnomalyConfig, NoveltyDetector, NoveltyScore};
+use neuromorphic_core::criticality::{CriticalityReport, CriticalityTuning};
+use neuromorphic_core::dendrite::{DendriteConfig, DendriticTree};
+use neuromorphic_core::encoding::InputFrames;
+use neuromorphic_core::energy::{EnergyEstimate, HardwareProfile, OperationCounts};
+use neuromorphic_core::fixed::{self, FixedLifArrays, FixedPointNetwork};
+use neuromorphic_core::forecast::Forecaster;
+use neuromorphic_core::gap::GapJunctions;
+use neuromorphic_core::model::{
+    NetworkDescription, NetworkLayout, PlasticityDescription, ProjectionDescription, RecurrenceDescription, SynapseKind,
+};
+use neuromorphic_core::modulation::{ModulationEffects, ModulationSet, ModulatorBinding, ModulatorConfig};
+use neuromorphic_core::parallel;
+use neuromorphic_core::population::NeuronPopulation;
+use neuromorphic_core::probes::{Probe, ProbeConfig, ProbeSet};
+use neuromorphic_core::rates::{RateEstimator, RateEstimatorConfig, MAX_WINDOW_STEPS};
+use neuromorphic_core::rng::XorShift64;
+use neuromorphic_core::stats::NetworkStats;
+use neuromorphic_core::stimulus::{StimulusConfig, StimulusSet};
+use neuromorphic_core::structural::{StructuralPlasticity, StructuralPlasticityConfig, CONNECTION_EPSILON};
+use neuromorphic_core::LifParameters;
+
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
     fn log(s: &str);
 }
 
+/// Native builds (the replay tool) run silently.
+#[cfg(not(target_arch = "wasm32"))]
+fn log(_s: &str) {}
+
+/// Wall-clock time in milliseconds.
+fn now_ms() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    return js_sys::Date::now() as u64;
+    #[cfg(not(target_arch = "wasm32"))]
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+}
+
 macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
+mod buffers;
+mod competitive;
+mod coordinator;
+mod forecasting;
+mod recording;
+
+pub use buffers::RASTER_BYTE_LIMIT;
+pub use competitive::CompetitiveLearner;
+pub use coordinator::NetworkCoordinator;
+pub use forecasting::ForecastFitReport;
+pub use recording::{replay, replay_recording, RecordedCall, Recording, ReplayReport};
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SpikePattern {
     pub spikes: Vec<f32>,
@@ -28,102 +84,217 @@ pub struct NeuromorphicResult {
     pub network_state: String,
     pub learning_delta: f32,
     pub pattern_recognition: Option<String>,
+    pub operations: OperationCounts,
+    pub energy: EnergyEstimate,
+    /// Windows completed during the call, when anomaly detection is on.
+    pub novelty: Vec<NoveltyScore>,
+    pub anomaly: bool,
 }
 
-pub struct LeakyIntegrateFireNeuron {
-    membrane_potential: f32,
-    threshold: f32,
-    leak_rate: f32,
-    refractory_period: u32,
-    refractory_counter: u32,
-    spike_history: VecDeque<u64>,
+#[derive(Serialize, Deserialize)]
+pub struct CriticalityTuningResult {
+    pub recurrent_gain: f32,
+    pub branching_ratio: Option<f32>,
+    pub converged: bool,
+    /// Branching-ratio estimate of each iteration, before its gain update.
+    pub history: Vec<Option<f32>>,
 }
 
-impl LeakyIntegrateFireNeuron {
-    pub fn new(threshold: f32, leak_rate: f32) -> Self {
-        Self {
-            membrane_potential: 0.0,
-            threshold,
-            leak_rate,
-            refractory_period: 5,
-            refractory_counter: 0,
-            spike_history: VecDeque::with_capacity(100),
-        }
-    }
-    
-    pub fn step(&mut self, input_current: f32, timestep: u64) -> bool {
-        // Refractory period handling
-        if self.refractory_counter > 0 {
-            self.refractory_counter -= 1;
-            self.membrane_potential = 0.0;
-            return false;
-        }
-        
-        // Leak current
-        self.membrane_potential *= 1.0 - self.leak_rate;
-        
-        // Add input current
-        self.membrane_potential += input_current;
-        
-        // Check for spike
-        if self.membrane_potential >= self.threshold {
-            self.membrane_potential = 0.0;
-            self.refractory_counter = self.refractory_period;
-            self.spike_history.push_back(timestep);
-            
-            // Keep history manageable
-            if self.spike_history.len() > 100 {
-                self.spike_history.pop_front();
-            }
-            
-            true
-        } else {
-            false
-        }
-    }
-    
-    pub fn get_firing_rate(&self, window_ms: u64, current_time: u64) -> f32 {
-        let cutoff_time = current_time.saturating_sub(window_ms);
-        let recent_spikes = self.spike_history.iter()
-            .filter(|&&spike_time| spike_time >= cutoff_time)
-            .count();
-        
-        (recent_spikes as f32 / window_ms as f32) * 1000.0 // spikes per second
-    }
+#[derive(Serialize)]
+struct OperationReport<'a> {
+    profile: &'a HardwareProfile,
+    last_call: OperationCounts,
+    last_call_energy: EnergyEstimate,
+    total: OperationCounts,
+    total_energy: EnergyEstimate,
 }
 
+/// Steps of population spike counts kept for criticality analysis.
+const ACTIVITY_RECORD_LIMIT: usize = 100_000;
+
+/// Anomaly events kept until taken; the oldest are dropped first.
+const ANOMALY_EVENT_LIMIT: usize = 1000;
+
+/// Largest network `from_model` will build; the dense weight matrix alone
+/// is 64 MiB at this size.
+const MAX_MODEL_NEURONS: usize = 4096;
+
 #[wasm_bindgen]
+#[derive(Serialize, Deserialize)]
 pub struct NeuromorphicProcessor {
-    neurons: Vec<LeakyIntegrateFireNeuron>,
+    neurons: NeuronPopulation,
     network_size: usize,
     current_time: u64,
+    dt_ms: f32,
+    threads: usize,
     learning_rate: f32,
+    /// Scale from presynaptic firing rate (Hz) times weight to input current.
+    recurrent_gain: f32,
+    /// Presynaptic rates driving recurrent input, and the rates seen by the
+    /// Hebbian and structural rules.
+    recurrent_rates: RateEstimator,
+    learning_rates: RateEstimator,
     synaptic_weights: Vec<Vec<f32>>,
+    gap_junctions: GapJunctions,
+    layout: NetworkLayout,
     pattern_memory: Vec<SpikePattern>,
+    structural_plasticity: StructuralPlasticity,
+    probes: ProbeSet,
+    stimuli: StimulusSet,
+    modulation: ModulationSet,
+    dendrites: Option<DendriticTree>,
+    /// Branch-major dendritic input currents for the next step.
+    dendrite_inputs: Vec<f32>,
+    /// Per-neuron modulation for the current step; `None` when unmodulated.
+    modulation_effects: Option<ModulationEffects>,
+    fixed_point: Option<FixedPointNetwork>,
+    /// Population spike count of each recent step, oldest first.
+    activity_record: Vec<u32>,
+    /// Reservoir readouts trained by `fit`.
+    forecaster: Option<Forecaster>,
+    anomaly_detector: Option<NoveltyDetector>,
+    /// Novelty scores of the current (or last) input call, and anomalous
+    /// windows not yet taken.
+    call_novelty: Vec<NoveltyScore>,
+    anomaly_events: VecDeque<NoveltyScore>,
+    /// Operations of the current (or last) input call, and since creation.
+    call_operations: OperationCounts,
+    total_operations: OperationCounts,
+    hardware: HardwareProfile,
+    /// Model seed, kept so exported descriptions rebuild the same network.
+    seed: u64,
+    rng_state: u32,
     initialized: bool,
+    #[serde(skip)]
+    io: IoBuffers,
+    #[serde(skip)]
+    recording: Option<Recording>,
+    /// Start time for the next input call, injected during replay.
+    #[serde(skip)]
+    replay_start: Option<u64>,
 }
 
 #[wasm_bindgen]
 impl NeuromorphicProcessor {
     #[wasm_bindgen(constructor)]
-    pub fn new(network_size: usize) -> NeuromorphicProcessor {
+    pub fn new(network_size: usize) -> Result<NeuromorphicProcessor, NeuromorphicError> {
+        if network_size == 0 {
+            return Err(NeuromorphicError::InvalidInput("network_size must be at least 1".to_string()));
+        }
+        
         console_log!("⚡ Neuromorphic Processor: Initializing REAL spike network with {} neurons", network_size);
         
         let mut processor = NeuromorphicProcessor {
-            neurons: Vec::new(),
+            neurons: NeuronPopulation::new(),
             network_size,
             current_time: 0,
+            dt_ms: 1.0,
+            threads: 1,
             learning_rate: 0.01,
+            recurrent_gain: 0.01,
+            recurrent_rates: RateEstimator::new(RateEstimatorConfig::Window { window_ms: 10.0 }, network_size, 1.0),
+            learning_rates: RateEstimator::new(RateEstimatorConfig::Window { window_ms: 20.0 }, network_size, 1.0),
             synaptic_weights: Vec::new(),
+            gap_junctions: GapJunctions::default(),
+            layout: NetworkLayout::single("network", network_size),
             pattern_memory: Vec::new(),
+            structural_plasticity: StructuralPlasticity::new(network_size),
+            probes: ProbeSet::default(),
+            stimuli: StimulusSet::new(network_size as u64),
+            modulation: ModulationSet::default(),
+            dendrites: None,
+            dendrite_inputs: Vec::new(),
+            modulation_effects: None,
+            fixed_point: None,
+            activity_record: Vec::new(),
+            forecaster: None,
+            anomaly_detector: None,
+            call_novelty: Vec::new(),
+            anomaly_events: VecDeque::new(),
+            call_operations: OperationCounts::default(),
+            total_operations: OperationCounts::default(),
+            hardware: HardwareProfile::default(),
+            seed: network_size as u64,
+            rng_state: network_size as u32,
             initialized: false,
+            io: IoBuffers::default(),
+            recording: None,
+            replay_start: None,
         };
         
         processor.initialize_network();
         processor.initialized = true;
         
         console_log!("✅ Neuromorphic Processor: REAL spike network ready with {} neurons", network_size);
-        processor
+        Ok(processor)
+    }
+    
+    /// Builds a processor from a declarative network description (see
+    /// `neuromorphic_core::model`), given as JSON or YAML.
+    #[wasm_bindgen]
+    pub fn from_model(description: &str) -> Result<NeuromorphicProcessor, NeuromorphicError> {
+        let description: NetworkDescription = if description.trim_start().starts_with('{') {
+            parse_json(description)?
+        } else {
+            serde_yaml::from_str(description).map_err(|e| NeuromorphicError::ParseError(e.to_string()))?
+        };
+        let neuron_count = description.populations.iter()
+            .try_fold(0usize, |total, population| total.checked_add(population.size));
+        if neuron_count.is_none_or(|count| count > MAX_MODEL_NEURONS) {
+            return Err(NeuromorphicError::InvalidConfig(format!("models are limited to {} neurons", MAX_MODEL_NEURONS)));
+        }
+        let built = description.build()?;
+        let network_size = built.params.len();
+        
+        let mut neurons = NeuronPopulation::new();
+        for params in built.params {
+            neurons.push(params);
+        }
+        
+        let mut structural_plasticity = StructuralPlasticity::new(network_size);
+        structural_plasticity.config = built.plasticity.structural;
+        
+        let processor = NeuromorphicProcessor {
+            neurons,
+            network_size,
+            current_time: 0,
+            dt_ms: built.dt_ms,
+            threads: 1,
+            learning_rate: built.plasticity.learning_rate,
+            recurrent_gain: built.recurrence.gain,
+            recurrent_rates: RateEstimator::new(built.recurrence.rates, network_size, built.dt_ms),
+            learning_rates: RateEstimator::new(RateEstimatorConfig::Window { window_ms: 20.0 }, network_size, built.dt_ms),
+            synaptic_weights: built.weights,
+            gap_junctions: built.gap_junctions,
+            layout: built.layout,
+            pattern_memory: Vec::new(),
+            structural_plasticity,
+            probes: ProbeSet::default(),
+            stimuli: StimulusSet::new(description.seed ^ network_size as u64),
+            modulation: ModulationSet::default(),
+            dendrites: None,
+            dendrite_inputs: Vec::new(),
+            modulation_effects: None,
+            fixed_point: None,
+            activity_record: Vec::new(),
+            forecaster: None,
+            anomaly_detector: None,
+            call_novelty: Vec::new(),
+            anomaly_events: VecDeque::new(),
+            call_operations: OperationCounts::default(),
+            total_operations: OperationCounts::default(),
+            hardware: HardwareProfile::default(),
+            seed: description.seed,
+            rng_state: (description.seed as u32) | 1,
+            initialized: true,
+            io: IoBuffers::default(),
+            recording: None,
+            replay_start: None,
+        };
+        
+        console_log!("📥 Neuromorphic Processor: Loaded model '{}' with {} neurons in {} populations",
+                     processor.layout.name, network_size, processor.layout.populations.len());
+        Ok(processor)
     }
     
     fn initialize_network(&mut self) {
@@ -131,7 +302,7 @@ impl NeuromorphicProcessor {
         for i in 0..self.network_size {
             let threshold = 1.0 + (i as f32 * 0.1) % 0.5; // Varying thresholds
             let leak_rate = 0.1 + (i as f32 * 0.01) % 0.05; // Varying leak rates
-            self.neurons.push(LeakyIntegrateFireNeuron::new(threshold, leak_rate));
+            self.neurons.push(LifParameters::from_dimensionless(threshold, leak_rate));
         }
         
         // Initialize synaptic weights (small-world network topology)
@@ -144,8 +315,8 @@ impl NeuromorphicProcessor {
                     let distance = ((i as f32 - j as f32).abs()) / self.network_size as f32;
                     let connection_prob = 0.1 * (1.0 - distance).max(0.0);
                     
-                    if self.random_f32() < connection_prob {
-                        self.synaptic_weights[i][j] = (self.random_f32() - 0.5) * 0.2; // Small random weights
+                    if self.draw_random() < connection_prob {
+                        self.synaptic_weights[i][j] = (self.draw_random() - 0.5) * 0.2; // Small random weights
                     }
                 }
             }
@@ -158,113 +329,122 @@ impl NeuromorphicProcessor {
     fn count_connections(&self) -> usize {
         self.synaptic_weights.iter()
             .flat_map(|row| row.iter())
-            .filter(|&&weight| weight.abs() > 0.001)
+            .filter(|&&weight| weight.abs() > CONNECTION_EPSILON)
             .count()
     }
     
-    fn random_f32(&self) -> f32 {
-        // Simple PRNG for WASM (using current time as seed)
-        let seed = (self.current_time + self.network_size as u64) as u32;
+    /// Uniform sample in [0, 1) from the LCG state, advanced on every draw.
+    fn draw_random(&mut self) -> f32 {
+        self.rng_state = Self::next_random(self.rng_state);
+        (self.rng_state as f32) / (u32::MAX as f32)
+    }
+
+    fn next_random(state: u32) -> u32 {
         let a = 1664525_u32;
         let c = 1013904223_u32;
-        let result = a.wrapping_mul(seed).wrapping_add(c);
-        (result as f32) / (u32::MAX as f32)
+        a.wrapping_mul(state).wrapping_add(c)
     }
 
     #[wasm_bindgen]
     pub fn generate_spikes(&mut self, pattern_length: usize) -> Vec<f32> {
         console_log!("⚡ Generating REAL spike pattern with {} timesteps", pattern_length);
         
-        let start_time = js_sys::Date::now() as u64;
+        let start_time = self.begin_call();
         let mut spike_pattern = Vec::with_capacity(pattern_length);
         
-        // Generate input stimulus
+        // Default input stimulus, used when no stimuli are configured
         let stimulus_strength = 0.5;
         let stimulus_duration = pattern_length / 3;
         
         for timestep in 0..pattern_length {
-            self.current_time = start_time + timestep as u64;
+            self.current_time = self.time_at_step(start_time, timestep);
             
             // Create input current for each neuron
             let mut input_currents = vec![0.0; self.network_size];
             
-            // Apply external stimulus
-            if timestep < stimulus_duration {
+            // Apply external stimulus: configured stimuli, or the default ramp
+            if !self.stimuli.is_empty() {
+                self.stimuli.apply(timestep, self.dt_ms, None, &mut input_currents);
+                for (b, branch_inputs) in self.dendrite_inputs.chunks_mut(self.network_size).enumerate() {
+                    self.stimuli.apply(timestep, self.dt_ms, Some(b), branch_inputs);
+                }
+            } else if timestep < stimulus_duration {
                 let stimulus = stimulus_strength * (1.0 - (timestep as f32 / stimulus_duration as f32));
-                for i in 0..self.network_size {
-                    input_currents[i] += stimulus * (0.5 + 0.5 * ((i as f32 * 0.1).sin()));
+                for (i, current) in input_currents.iter_mut().enumerate() {
+                    *current += stimulus * (0.5 + 0.5 * libm::sinf(i as f32 * 0.1));
                 }
             }
             
-            // Process network recurrent connections
+            // Add recurrent input and step all neurons synchronously
+            self.update_modulation();
             let mut network_spikes = vec![false; self.network_size];
-            for i in 0..self.network_size {
-                // Add recurrent input from other neurons
-                for j in 0..self.network_size {
-                    if i != j && self.synaptic_weights[j][i].abs() > 0.001 {
-                        let firing_rate = self.neurons[j].get_firing_rate(10, self.current_time);
-                        input_currents[i] += self.synaptic_weights[j][i] * firing_rate * 0.01;
-                    }
-                }
-                
-                // Step the neuron
-                network_spikes[i] = self.neurons[i].step(input_currents[i], self.current_time);
-            }
+            let spike_count = self.step_network(&mut input_currents, &mut network_spikes, true);
+            
+            self.sample_probes();
             
             // Calculate population activity
-            let spike_count = network_spikes.iter().filter(|&&spike| spike).count();
             let population_activity = spike_count as f32 / self.network_size as f32;
             
             spike_pattern.push(population_activity);
         }
         
-        let processing_time = js_sys::Date::now() as u64 - start_time;
+        let processing_time = now_ms().saturating_sub(start_time);
         console_log!("✅ REAL spike pattern generated in {}ms", processing_time);
         
+        self.record(|| RecordedCall::GenerateSpikes { pattern_length }, start_time, &spike_pattern);
         spike_pattern
     }
 
     #[wasm_bindgen]
-    pub fn process_input(&mut self, input_data: &[f32]) -> String {
+    pub fn process_input(&mut self, input_data: &[f32]) -> Result<String, NeuromorphicError> {
+        to_json(&self.run_input(input_data)?)
+    }
+    
+    fn run_input(&mut self, input_data: &[f32]) -> Result<NeuromorphicResult, NeuromorphicError> {
+        if input_data.is_empty() {
+            return Err(NeuromorphicError::InvalidInput("input_data is empty".to_string()));
+        }
+        if let Some(index) = input_data.iter().position(|x| !x.is_finite()) {
+            return Err(NeuromorphicError::InvalidInput(format!("input_data[{}] is not finite", index)));
+        }
+        
         console_log!("🧠 Processing REAL input through spike network: {} samples", input_data.len());
         
-        let start_time = js_sys::Date::now() as u64;
+        let start_time = self.begin_call();
         let pattern_length = input_data.len().min(100); // Limit pattern length
         
         let mut spike_pattern = Vec::new();
         let mut total_activation = 0.0;
         
         for (timestep, &input_value) in input_data.iter().take(pattern_length).enumerate() {
-            self.current_time = start_time + timestep as u64;
+            self.current_time = self.time_at_step(start_time, timestep);
             
             // Convert input to neural currents
-            let mut input_currents = vec![0.0; self.network_size];
             let scaled_input = input_value * 2.0; // Scale input appropriately
             
-            for i in 0..self.network_size {
-                // Distribute input across neurons with some variability
-                let neuron_input = scaled_input * (0.8 + 0.4 * ((i as f32 * 0.2).sin()));
-                input_currents[i] = neuron_input;
-            }
+            // Distribute input across neurons with some variability
+            let mut input_currents: Vec<f32> = (0..self.network_size)
+                .map(|i| scaled_input * (0.8 + 0.4 * libm::sinf(i as f32 * 0.2)))
+                .collect();
             
             // Process one timestep
-            let mut spike_count = 0;
-            for i in 0..self.network_size {
-                if self.neurons[i].step(input_currents[i], self.current_time) {
-                    spike_count += 1;
-                }
-            }
+            self.update_modulation();
+            let mut spikes = vec![false; self.network_size];
+            let spike_count = self.step_network(&mut input_currents, &mut spikes, false);
+            
+            self.sample_probes();
             
             let activation = spike_count as f32 / self.network_size as f32;
             spike_pattern.push(activation);
             total_activation += activation;
         }
         
-        let processing_time = js_sys::Date::now() as u64 - start_time;
+        let processing_time = now_ms().saturating_sub(start_time);
         let avg_activation = total_activation / pattern_length as f32;
         
         // Apply learning (simple STDP-like rule)
         self.apply_learning(avg_activation);
+        self.apply_structural_plasticity();
         
         // Recognize patterns
         let pattern_recognition = self.recognize_pattern(&spike_pattern);
@@ -281,26 +461,213 @@ impl NeuromorphicProcessor {
             network_state: format!("Active neurons: {:.1}%", avg_activation * 100.0),
             learning_delta: self.learning_rate * avg_activation,
             pattern_recognition,
+            operations: self.call_operations,
+            energy: self.hardware.estimate(&self.call_operations),
+            novelty: self.call_novelty.clone(),
+            anomaly: self.call_novelty.iter().any(|score| score.anomaly),
         };
         
         console_log!("✅ REAL neuromorphic processing complete: {:.3} avg activation", avg_activation);
         
-        serde_json::to_string(&result).unwrap_or_default()
+        self.record(|| RecordedCall::ProcessInput { input_data: input_data.to_vec() }, start_time, &result);
+        Ok(result)
+    }
+    
+    /// Drives the network with encoded input: `frames` holds `channels`
+    /// currents per timestep (step-major, as produced by the encoders in
+    /// `neuromorphic_core::encoding`), mapped onto the neurons in blocks.
+    /// Recurrent input is on; with `learn` the Hebbian and structural rules
+    /// run once at the end. Returns the population activity per step.
+    #[wasm_bindgen]
+    pub fn run_frames(&mut self, frames: &[f32], channels: usize, learn: bool) -> Result<Vec<f32>, NeuromorphicError> {
+        if channels == 0 || frames.is_empty() || !frames.len().is_multiple_of(channels) {
+            return Err(NeuromorphicError::InvalidInput(format!(
+                "{} values do not form whole frames of {} channels", frames.len(), channels
+            )));
+        }
+        if frames.iter().any(|x| !x.is_finite()) {
+            return Err(NeuromorphicError::InvalidInput("frames contain non-finite values".to_string()));
+        }
+        
+        let frames = InputFrames { channels, data: frames.to_vec() };
+        let start_time = self.begin_call();
+        let mut activity = Vec::with_capacity(frames.steps());
+        
+        for step in 0..frames.steps() {
+            self.current_time = self.time_at_step(start_time, step);
+            let mut input_currents = vec![0.0; self.network_size];
+            frames.currents_at(step, &mut input_currents);
+            
+            self.update_modulation();
+            let mut spikes = vec![false; self.network_size];
+            let spike_count = self.step_network(&mut input_currents, &mut spikes, true);
+            self.sample_probes();
+            activity.push(spike_count as f32 / self.network_size as f32);
+        }
+        
+        if learn {
+            let mean_activity = activity.iter().sum::<f32>() / activity.len() as f32;
+            self.apply_learning(mean_activity);
+            self.apply_structural_plasticity();
+        }
+        self.record(|| RecordedCall::RunFrames { frames: frames.data.clone(), channels, learn }, start_time, &activity);
+        Ok(activity)
+    }
+    
+    /// One step of a linked run (see `NetworkCoordinator`): configured
+    /// stimuli plus the currents arriving over links, with recurrent input.
+    /// Returns the spikes of the step.
+    fn step_linked(&mut self, start_time: u64, timestep: usize, mut input_currents: Vec<f32>) -> Vec<bool> {
+        self.current_time = self.time_at_step(start_time, timestep);
+        if !self.stimuli.is_empty() {
+            self.stimuli.apply(timestep, self.dt_ms, None, &mut input_currents);
+            for (b, branch_inputs) in self.dendrite_inputs.chunks_mut(self.network_size).enumerate() {
+                self.stimuli.apply(timestep, self.dt_ms, Some(b), branch_inputs);
+            }
+        }
+        
+        self.update_modulation();
+        let mut spikes = vec![false; self.network_size];
+        self.step_network(&mut input_currents, &mut spikes, true);
+        self.sample_probes();
+        spikes
+    }
+    
+    /// Advances the network one timestep from the external `input_currents`,
+    /// optionally adding recurrent input first, using either the f32 or the
+    /// fixed-point model.
+    fn step_network(&mut self, input_currents: &mut [f32], spikes: &mut [bool], recurrent: bool) -> usize {
+        if let Some(tree) = &mut self.dendrites {
+            tree.step(&self.dendrite_inputs, &self.neurons, input_currents);
+            self.dendrite_inputs.fill(0.0);
+        }
+        let mut synaptic_ops = 0;
+        if !self.gap_junctions.is_empty() {
+            self.gap_junctions.accumulate(&self.neurons, input_currents);
+            synaptic_ops += self.gap_junctions.junction_count() as u64;
+        }
+        
+        let Some(network) = &mut self.fixed_point else {
+            if recurrent {
+                synaptic_ops += parallel::accumulate_recurrent(
+                    &self.synaptic_weights,
+                    self.recurrent_rates.rates(),
+                    self.recurrent_gain,
+                    CONNECTION_EPSILON,
+                    input_currents,
+                    self.threads,
+                );
+            }
+            if let Some(effects) = &self.modulation_effects {
+                for (current, &gain) in input_currents.iter_mut().zip(&effects.gain) {
+                    *current *= gain;
+                }
+            }
+            let spike_count = self.neurons.step(input_currents, self.current_time, spikes, self.threads);
+            self.finish_step(spikes, spike_count, synaptic_ops);
+            return spike_count;
+        };
+        
+        let mut inputs: Vec<i32> = input_currents.iter().map(|&c| fixed::to_fixed(c)).collect();
+        if recurrent {
+            let rates: Vec<i32> = self.recurrent_rates.rates().iter().map(|&r| fixed::to_fixed(r)).collect();
+            synaptic_ops += network.accumulate_recurrent(
+                &rates,
+                fixed::to_fixed(self.recurrent_gain),
+                fixed::to_weight(CONNECTION_EPSILON),
+                &mut inputs,
+            );
+        }
+        if let Some(effects) = &self.modulation_effects {
+            for (input, &gain) in inputs.iter_mut().zip(&effects.gain) {
+                let product = (*input as i64 * fixed::to_fixed(gain) as i64) >> fixed::FRAC_BITS;
+                *input = product.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+            }
+        }
+        network.neurons.step(&inputs, spikes);
+        
+        // Mirror the integer state so probes, stats and rates keep working
+        for i in 0..self.network_size {
+            self.neurons.set_state(
+                i,
+                fixed::from_fixed(network.neurons.membrane[i]),
+                network.neurons.refractory[i] as u32,
+            );
+        }
+        let spike_count = self.neurons.record_spikes(spikes, self.current_time);
+        self.finish_step(spikes, spike_count, synaptic_ops);
+        spike_count
+    }
+    
+    /// Updates the rate estimators and the anomaly detector and records the
+    /// step's activity and operation counts.
+    fn finish_step(&mut self, spikes: &[bool], spike_count: usize, synaptic_ops: u64) {
+        self.recurrent_rates.update(spikes);
+        self.learning_rates.update(spikes);
+        self.io.capture(spikes);
+        
+        if let Some(score) = self.anomaly_detector.as_mut().and_then(|d| d.observe(spikes, self.current_time)) {
+            if score.anomaly {
+                console_log!("🚨 Anomalous spike pattern at {}ms: novelty {:.2}", score.time_ms, score.score);
+                if self.anomaly_events.len() == ANOMALY_EVENT_LIMIT {
+                    self.anomaly_events.pop_front();
+                }
+                self.anomaly_events.push_back(score);
+            }
+            self.call_novelty.push(score);
+        }
+        
+        if self.activity_record.len() == ACTIVITY_RECORD_LIMIT {
+            self.activity_record.drain(..ACTIVITY_RECORD_LIMIT / 2);
+        }
+        self.activity_record.push(spike_count as u32);
+        
+        let dendrite_updates = self.dendrites.as_ref().map_or(0, |tree| tree.branches() * tree.somas().len());
+        let step = OperationCounts {
+            steps: 1,
+            neuron_updates: (self.network_size + dendrite_updates) as u64,
+            synaptic_ops,
+            spikes: spike_count as u64,
+        };
+        self.call_operations += step;
+        self.total_operations += step;
     }
     
     fn apply_learning(&mut self, activation_strength: f32) {
         // Simple learning rule: strengthen connections that contributed to strong activation
         let learning_factor = self.learning_rate * activation_strength;
+        let firing_rates = self.learning_rates.rates();
+        let increments: Vec<f32> = match &self.modulation_effects {
+            Some(effects) => effects.learning_rate.iter().map(|&m| learning_factor * m * 0.1).collect(),
+            None => vec![learning_factor * 0.1; self.network_size],
+        };
+        
+        if let Some(network) = &mut self.fixed_point {
+            let rates: Vec<i32> = firing_rates.iter().map(|&r| fixed::to_fixed(r)).collect();
+            let increments: Vec<i16> = increments.iter().map(|&inc| fixed::to_weight(inc)).collect();
+            network.apply_learning(
+                &rates,
+                fixed::to_fixed(1.0),
+                &increments,
+                fixed::to_weight(CONNECTION_EPSILON),
+                |i, j| self.layout.is_plastic(i, j),
+            );
+            network.store_weights(&mut self.synaptic_weights);
+            return;
+        }
         
         for i in 0..self.network_size {
             for j in 0..self.network_size {
-                if i != j && self.synaptic_weights[i][j].abs() > 0.001 {
-                    let firing_rate_i = self.neurons[i].get_firing_rate(20, self.current_time);
-                    let firing_rate_j = self.neurons[j].get_firing_rate(20, self.current_time);
+                if i != j
+                    && self.synaptic_weights[i][j].abs() > CONNECTION_EPSILON
+                    && self.layout.is_plastic(i, j)
+                {
+                    let firing_rate_i = firing_rates[i];
+                    let firing_rate_j = firing_rates[j];
                     
                     // Hebbian-like learning: neurons that fire together, wire together
                     if firing_rate_i > 1.0 && firing_rate_j > 1.0 {
-                        self.synaptic_weights[i][j] += learning_factor * 0.1;
+                        self.synaptic_weights[i][j] += increments[j];
                         self.synaptic_weights[i][j] = self.synaptic_weights[i][j].clamp(-1.0, 1.0);
                     }
                 }
@@ -308,6 +675,59 @@ impl NeuromorphicProcessor {
         }
     }
     
+    /// Advances the modulators by one step and applies their threshold
+    /// offsets; gain and learning-rate factors are applied where used.
+    fn update_modulation(&mut self) {
+        self.modulation.advance(self.dt_ms);
+        if self.modulation.is_empty() && self.modulation_effects.is_none() {
+            return;
+        }
+        
+        let effects = self.modulation.effects(self.network_size);
+        for (i, &offset) in effects.threshold_offset.iter().enumerate() {
+            let threshold = self.neurons.parameters(i).v_threshold + offset;
+            self.neurons.set_threshold(i, threshold);
+            if let Some(network) = &mut self.fixed_point {
+                network.neurons.v_threshold[i] = fixed::to_fixed(threshold);
+            }
+        }
+        // One neutral pass restores the thresholds after the last unbind
+        self.modulation_effects = if self.modulation.is_empty() { None } else { Some(effects) };
+    }
+    
+    /// Simulation clock in whole milliseconds; with sub-millisecond steps
+    /// several steps share a timestamp.
+    fn time_at_step(&self, start_time: u64, timestep: usize) -> u64 {
+        start_time + (timestep as f64 * self.dt_ms as f64) as u64
+    }
+    
+    fn sample_probes(&mut self) {
+        if !self.probes.is_empty() {
+            self.probes.sample(&self.neurons, &self.synaptic_weights, self.current_time);
+        }
+    }
+    
+    fn apply_structural_plasticity(&mut self) {
+        if !self.structural_plasticity.config.enabled {
+            return;
+        }
+        
+        let rates = self.learning_rates.rates();
+        
+        let mut rng_state = self.rng_state;
+        self.structural_plasticity.update(&mut self.synaptic_weights, &self.layout, rates, || {
+            rng_state = Self::next_random(rng_state);
+            (rng_state as f32) / (u32::MAX as f32)
+        });
+        self.rng_state = rng_state;
+        
+        // Keep the f32 weights exactly representable in the fixed-point model
+        if let Some(network) = &mut self.fixed_point {
+            network.load_weights(&self.synaptic_weights);
+            network.store_weights(&mut self.synaptic_weights);
+        }
+    }
+    
     fn recognize_pattern(&mut self, spike_pattern: &[f32]) -> Option<String> {
         // Simple pattern recognition based on activation signature
         let pattern_sum: f32 = spike_pattern.iter().sum();
@@ -333,16 +753,659 @@ impl NeuromorphicProcessor {
     #[wasm_bindgen]
     pub fn get_network_stats(&self) -> String {
         let connections = self.count_connections();
-        let avg_threshold: f32 = self.neurons.iter()
-            .map(|n| n.threshold)
+        let avg_threshold: f32 = (0..self.neurons.len())
+            .map(|i| self.neurons.threshold(i))
             .sum::<f32>() / self.network_size as f32;
         
-        let recent_activity: f32 = self.neurons.iter()
-            .map(|n| n.get_firing_rate(100, self.current_time))
+        let recent_activity: f32 = self.neurons.firing_rates(100, self.current_time)
+            .iter()
             .sum::<f32>() / self.network_size as f32;
         
-        format!("Neurons: {} | Connections: {} | Avg Threshold: {:.3} | Recent Activity: {:.1} Hz", 
-                self.network_size, connections, avg_threshold, recent_activity)
+        let counters = self.structural_plasticity.counters;
+        
+        format!("Neurons: {} | Connections: {} | Avg Threshold: {:.3} | Recent Activity: {:.1} Hz | Pruned: {} | Grown: {}", 
+                self.network_size, connections, avg_threshold, recent_activity,
+                counters.synapses_pruned, counters.synapses_grown)
+    }
+
+    /// Adds a stimulus used by `generate_spikes` in place of the default ramp
+    /// and returns its id. Stimuli are summed, so several can target the same
+    /// neurons.
+    #[wasm_bindgen]
+    pub fn add_stimulus(&mut self, config_json: &str) -> Result<u32, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let config: StimulusConfig = parse_json(config_json)?;
+        let branches = self.dendrites.as_ref().map_or(0, |tree| tree.branches());
+        if config.compartment.is_some_and(|b| b >= branches) {
+            return Err(NeuromorphicError::InvalidConfig("stimulus targets a missing dendritic branch".to_string()));
+        }
+        
+        let id = self.stimuli.add(config, &self.layout).ok_or_else(|| {
+            NeuromorphicError::InvalidConfig("stimulus targets no neurons or an unknown population".to_string())
+        })?;
+        console_log!("📡 Stimulus {} added", id);
+        Ok(id)
+    }
+
+    /// Gives the neurons selected by `target` (every neuron by default, or
+    /// `{"kind": "populations", "ids": [...]}` etc. as for modulator
+    /// bindings) `branches` dendritic compartments coupled to their soma,
+    /// turning them into multi-compartment neurons; 0 branches returns the
+    /// whole network to point neurons. Stimuli reach a branch through their
+    /// `compartment` field.
+    ///
+    /// Replaces any earlier dendrites.
+    #[wasm_bindgen]
+    pub fn set_dendrites(&mut self, config_json: &str) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let mut config: DendriteConfig = parse_json(config_json)?;
+        config.params.dt_ms = self.dt_ms;
+        if !config.params.is_valid() {
+            return Err(NeuromorphicError::InvalidConfig(
+                "dendrites need tau_ms > 0, resistance > 0, coupling >= 0 and spike_threshold > 0".to_string(),
+            ));
+        }
+        
+        if config.branches == 0 {
+            self.dendrites = None;
+            self.dendrite_inputs.clear();
+            return Ok(());
+        }
+        let (branches, coupling) = (config.branches, config.params.coupling);
+        let tree = DendriticTree::new(config, &self.layout).ok_or_else(|| {
+            NeuromorphicError::InvalidConfig("dendrites target no neurons or an unknown population".to_string())
+        })?;
+        console_log!("🌿 Dendrites: {} branches on {} neurons, coupling {:.3}", branches, tree.somas().len(), coupling);
+        self.dendrites = Some(tree);
+        self.dendrite_inputs = vec![0.0; branches * self.network_size];
+        Ok(())
+    }
+    
+    /// Depolarisation (relative to rest) of dendritic branch `branch` of
+    /// every neuron; 0 for neurons without dendrites.
+    #[wasm_bindgen]
+    pub fn get_dendrite_potentials(&self, branch: usize) -> Result<Vec<f32>, NeuromorphicError> {
+        match &self.dendrites {
+            Some(tree) if branch < tree.branches() => Ok(tree.potentials(branch)),
+            _ => Err(NeuromorphicError::NotFound(format!("dendritic branch {}", branch))),
+        }
+    }
+    
+    /// Dendritic spikes (plateau onsets) since the dendrites were created.
+    #[wasm_bindgen]
+    pub fn get_dendritic_spike_count(&self) -> u64 {
+        self.dendrites.as_ref().map_or(0, |tree| tree.spike_count())
+    }
+
+    /// Adds bidirectional gap junctions between two populations (or within
+    /// one), described like a model projection: `{"id", "pre", "post",
+    /// "connector", "weight"}` where `weight` is the coupling conductance.
+    /// Returns the number of junctions created.
+    #[wasm_bindgen]
+    pub fn add_gap_junctions(&mut self, projection_json: &str) -> Result<usize, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let mut projection: ProjectionDescription = parse_json(projection_json)?;
+        projection.synapse = SynapseKind::Electrical;
+        
+        let mut rng = XorShift64::new(Self::next_random(self.rng_state) as u64);
+        self.rng_state = Self::next_random(self.rng_state);
+        let connected = projection.connect(&self.layout.populations, &mut rng)?;
+        if self.layout.projections.iter().any(|p| p.id == projection.id)
+            || !self.gap_junctions.add(projection.id.clone(), connected.pre, connected.post, &connected.connections)
+        {
+            return Err(NeuromorphicError::InvalidConfig(format!("duplicate projection id '{}'", projection.id)));
+        }
+        
+        let count = self.gap_junctions.projections.last().map_or(0, |p| p.junctions.len());
+        console_log!("⚡ Gap junctions '{}': {} electrical synapses", projection.id, count);
+        Ok(count)
+    }
+    
+    /// Sets the conductance of every gap junction in projection `projection_id`.
+    #[wasm_bindgen]
+    pub fn set_gap_conductance(&mut self, projection_id: &str, conductance: f32) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        if !(conductance.is_finite() && conductance >= 0.0) {
+            return Err(NeuromorphicError::InvalidInput("conductance must be finite and non-negative".to_string()));
+        }
+        if !self.gap_junctions.set_conductance(projection_id, conductance) {
+            return Err(NeuromorphicError::NotFound(format!("gap junction projection '{}'", projection_id)));
+        }
+        Ok(())
+    }
+    
+    #[wasm_bindgen]
+    pub fn remove_gap_junctions(&mut self, projection_id: &str) -> Result<bool, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        Ok(self.gap_junctions.remove(projection_id))
+    }
+
+    #[wasm_bindgen]
+    pub fn remove_stimulus(&mut self, stimulus_id: u32) -> Result<bool, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        Ok(self.stimuli.remove(stimulus_id))
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_stimuli(&mut self) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        self.stimuli.clear();
+        Ok(())
+    }
+
+    /// Returns every neuron to rest, out of refractoriness, and clears the
+    /// firing-rate estimators, e.g. between independent samples. Weights,
+    /// stimuli and recorded data are kept.
+    #[wasm_bindgen]
+    pub fn reset_state(&mut self) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        for i in 0..self.network_size {
+            let v_rest = self.neurons.parameters(i).v_rest;
+            self.neurons.set_state(i, v_rest, 0);
+        }
+        if let Some(network) = &mut self.fixed_point {
+            network.neurons.membrane.clone_from(&network.neurons.v_rest);
+            network.neurons.refractory.fill(0);
+        }
+        self.recurrent_rates = self.recurrent_rates.rebuild(self.network_size, self.dt_ms);
+        self.learning_rates = self.learning_rates.rebuild(self.network_size, self.dt_ms);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_network_stats_json(&self) -> Result<String, NeuromorphicError> {
+        let stats = NetworkStats::collect(
+            &self.neurons,
+            &self.synaptic_weights,
+            &self.layout,
+            self.structural_plasticity.counters,
+            self.current_time,
+        );
+        to_json(&stats)
+    }
+
+    /// Applies the same physical LIF parameters to every neuron and returns
+    /// them to rest. Fields missing from `params_json` take their defaults.
+    #[wasm_bindgen]
+    pub fn set_lif_parameters(&mut self, params_json: &str) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let params: LifParameters = parse_json(params_json)?;
+        if !params.is_valid() {
+            return Err(NeuromorphicError::InvalidConfig(
+                "LIF parameters need tau_m_ms > 0, dt_ms > 0, refractory_ms >= 0 and v_reset < v_threshold".to_string(),
+            ));
+        }
+        if !(self.recurrent_rates.config.is_valid(params.dt_ms) && self.learning_rates.config.is_valid(params.dt_ms)) {
+            return Err(NeuromorphicError::InvalidConfig(format!(
+                "rate estimator windows would exceed {} steps at dt_ms {}", MAX_WINDOW_STEPS, params.dt_ms
+            )));
+        }
+        
+        // Potentials from the old parameters can sit above the new threshold,
+        // so every neuron restarts at the new resting potential
+        for i in 0..self.neurons.len() {
+            self.neurons.set_parameters(i, params);
+            self.neurons.set_state(i, params.v_rest, 0);
+        }
+        self.dt_ms = params.dt_ms;
+        self.recurrent_rates = self.recurrent_rates.rebuild(self.network_size, params.dt_ms);
+        self.learning_rates = self.learning_rates.rebuild(self.network_size, params.dt_ms);
+        if let Some(tree) = &mut self.dendrites {
+            tree.params.dt_ms = params.dt_ms;
+        }
+        if let Some(network) = &mut self.fixed_point {
+            network.neurons = FixedLifArrays::from_population(&self.neurons);
+        }
+        
+        console_log!("🔧 LIF parameters: tau_m {:.1} ms, V_th {:.1} mV, dt {:.3} ms",
+                    params.tau_m_ms, params.v_threshold, params.dt_ms);
+        Ok(())
+    }
+
+    /// Sets the number of worker threads used by the native backend (0 for
+    /// all cores) and returns the count actually used. Results do not depend
+    /// on the thread count; in the browser this is always 1.
+    #[wasm_bindgen]
+    pub fn set_thread_count(&mut self, threads: usize) -> Result<usize, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        self.threads = parallel::resolve_thread_count(threads);
+        Ok(self.threads)
+    }
+
+    /// Switches between the f32 model and the Q16.16 fixed-point model used
+    /// on the swarm nodes. Entering fixed-point mode quantises the current
+    /// state; weights stay quantised when switching back.
+    #[wasm_bindgen]
+    pub fn set_fixed_point_mode(&mut self, enabled: bool) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        if enabled && self.fixed_point.is_none() {
+            let network = FixedPointNetwork::quantize(&self.neurons, &self.synaptic_weights);
+            network.store_weights(&mut self.synaptic_weights);
+            self.fixed_point = Some(network);
+            console_log!("🔢 Fixed-point mode enabled (Q{}.{} state, Q1.{} weights)",
+                        32 - fixed::FRAC_BITS, fixed::FRAC_BITS, fixed::WEIGHT_FRAC_BITS);
+        } else if !enabled {
+            self.fixed_point = None;
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn is_fixed_point_mode(&self) -> bool {
+        self.fixed_point.is_some()
+    }
+
+    /// Largest membrane deviation between the fixed-point and f32 models over
+    /// all neurons for inputs bounded by `max_abs_input`.
+    #[wasm_bindgen]
+    pub fn get_fixed_point_error_bound(&self, max_abs_input: f32) -> Result<f32, NeuromorphicError> {
+        if !(max_abs_input.is_finite() && max_abs_input >= 0.0) {
+            return Err(NeuromorphicError::InvalidInput("max_abs_input must be finite and non-negative".to_string()));
+        }
+        
+        let bound = (0..self.neurons.len())
+            .map(|i| {
+                let params = self.neurons.parameters(i);
+                let deviation = (params.v_threshold - params.v_reset.min(params.v_rest)).abs()
+                    + 2.0 * params.resistance.abs() * max_abs_input;
+                fixed::membrane_error_bound(params, max_abs_input, deviation)
+            })
+            .fold(0.0, f32::max);
+        Ok(bound)
+    }
+
+    /// Quantised network (neuron parameters, state and Q1.14 weights) as JSON
+    /// for loading onto the swarm nodes.
+    #[wasm_bindgen]
+    pub fn export_fixed_point_network(&self) -> Result<String, NeuromorphicError> {
+        let network = match &self.fixed_point {
+            Some(network) => network.clone(),
+            None => FixedPointNetwork::quantize(&self.neurons, &self.synaptic_weights),
+        };
+        to_json(&network)
+    }
+
+    #[wasm_bindgen]
+    pub fn set_structural_plasticity(&mut self, config_json: &str) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let config: StructuralPlasticityConfig = parse_json(config_json)?;
+        if !config.is_valid() {
+            return Err(NeuromorphicError::InvalidConfig("invalid structural plasticity configuration".to_string()));
+        }
+        console_log!("🌱 Structural plasticity {}: prune < {:.3}, max fan-in {}",
+                    if config.enabled { "enabled" } else { "disabled" },
+                    config.prune_threshold, config.max_fan_in);
+        self.structural_plasticity.config = config;
+        Ok(())
+    }
+
+    /// Attaches a state probe described by `config_json` and returns its id.
+    #[wasm_bindgen]
+    pub fn add_probe(&mut self, config_json: &str) -> Result<u32, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let config: ProbeConfig = parse_json(config_json)?;
+        
+        let id = self.probes.attach(config, self.network_size).ok_or_else(|| {
+            NeuromorphicError::InvalidConfig("probe targets a neuron outside the network".to_string())
+        })?;
+        console_log!("🔬 Probe {} attached: {:?}", id, config.target);
+        Ok(id)
+    }
+
+    #[wasm_bindgen]
+    pub fn remove_probe(&mut self, probe_id: u32) -> Result<bool, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        Ok(self.probes.detach(probe_id))
+    }
+
+    #[wasm_bindgen]
+    pub fn get_probe_values(&self, probe_id: u32) -> Result<Vec<f32>, NeuromorphicError> {
+        self.probe(probe_id).map(|probe| probe.values())
+    }
+
+    #[wasm_bindgen]
+    pub fn get_probe_times(&self, probe_id: u32) -> Result<Vec<f64>, NeuromorphicError> {
+        self.probe(probe_id).map(|probe| probe.times())
+    }
+    
+    fn probe(&self, probe_id: u32) -> Result<&Probe, NeuromorphicError> {
+        self.probes.get(probe_id).ok_or_else(|| NeuromorphicError::NotFound(format!("probe {}", probe_id)))
+    }
+
+    #[wasm_bindgen]
+    pub fn clear_probe(&mut self, probe_id: u32) -> Result<bool, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        match self.probes.get_mut(probe_id) {
+            Some(probe) => {
+                probe.clear();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Defines (or redefines and resets) a global modulator such as
+    /// "arousal" or "attention": `{"name", "baseline", "tau_ms"}`. Levels
+    /// relax towards the baseline with time constant `tau_ms` (0 holds them).
+    #[wasm_bindgen]
+    pub fn define_modulator(&mut self, config_json: &str) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let config: ModulatorConfig = parse_json(config_json)?;
+        let name = config.name.clone();
+        if !self.modulation.define(config) {
+            return Err(NeuromorphicError::InvalidConfig("modulator tau_ms must be non-negative".to_string()));
+        }
+        console_log!("🎚️ Modulator '{}' defined", name);
+        Ok(())
+    }
+    
+    #[wasm_bindgen]
+    pub fn remove_modulator(&mut self, name: &str) -> Result<bool, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        Ok(self.modulation.remove(name))
+    }
+    
+    /// Drives a modulator to `level`; it then relaxes towards its baseline.
+    #[wasm_bindgen]
+    pub fn set_modulator_level(&mut self, name: &str, level: f32) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        if !level.is_finite() {
+            return Err(NeuromorphicError::InvalidInput("modulator level must be finite".to_string()));
+        }
+        if !self.modulation.set_level(name, level) {
+            return Err(NeuromorphicError::NotFound(format!("modulator '{}'", name)));
+        }
+        Ok(())
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_modulator_level(&self, name: &str) -> Result<f32, NeuromorphicError> {
+        self.modulation.level(name).ok_or_else(|| NeuromorphicError::NotFound(format!("modulator '{}'", name)))
+    }
+    
+    /// Binds a modulator to the gain, threshold or learning rate of a set of
+    /// neurons or populations through a transfer function, and returns the
+    /// binding id.
+    #[wasm_bindgen]
+    pub fn bind_modulator(&mut self, binding_json: &str) -> Result<u32, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let binding: ModulatorBinding = parse_json(binding_json)?;
+        if self.modulation.level(&binding.modulator).is_none() {
+            return Err(NeuromorphicError::NotFound(format!("modulator '{}'", binding.modulator)));
+        }
+        
+        let (modulator, parameter) = (binding.modulator.clone(), binding.parameter);
+        let id = self.modulation.bind(binding, &self.layout).ok_or_else(|| {
+            NeuromorphicError::InvalidConfig("binding targets no neurons or an unknown population".to_string())
+        })?;
+        console_log!("🎚️ Modulator '{}' bound to {:?} (binding {})", modulator, parameter, id);
+        Ok(id)
+    }
+    
+    #[wasm_bindgen]
+    pub fn unbind_modulator(&mut self, binding_id: u32) -> Result<bool, NeuromorphicError> {
+        self.ensure_not_recording()?;
+        Ok(self.modulation.unbind(binding_id))
+    }
+
+    /// Branching ratio and avalanche statistics of the recorded population
+    /// activity (every simulated step since the last clear). Avalanches are
+    /// found in bins of `bin_steps`; the regression estimator uses lags up to
+    /// `max_lag`.
+    #[wasm_bindgen]
+    pub fn analyze_criticality(&self, bin_steps: usize, max_lag: usize) -> Result<String, NeuromorphicError> {
+        if bin_steps == 0 || max_lag == 0 {
+            return Err(NeuromorphicError::InvalidInput("bin_steps and max_lag must be at least 1".to_string()));
+        }
+        to_json(&CriticalityReport::analyze(&self.activity_record, bin_steps, max_lag))
+    }
+    
+    #[wasm_bindgen]
+    pub fn clear_activity_record(&mut self) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        self.activity_record.clear();
+        Ok(())
+    }
+    
+    #[wasm_bindgen]
+    pub fn get_recurrent_gain(&self) -> f32 {
+        self.recurrent_gain
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_recurrent_gain(&mut self, gain: f32) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        if !gain.is_finite() || gain < 0.0 {
+            return Err(NeuromorphicError::InvalidInput("recurrent gain must be finite and non-negative".to_string()));
+        }
+        self.recurrent_gain = gain;
+        Ok(())
+    }
+    
+    /// Scales the recurrent gain until the branching ratio of the network,
+    /// under weak Poisson drive and without learning, reaches the target
+    /// (≈1 puts a reservoir at the edge of chaos). `config_json` holds
+    /// `CriticalityTuning` fields; missing fields take their defaults.
+    /// Neuron state is advanced by the calibration runs.
+    #[wasm_bindgen]
+    pub fn tune_criticality(&mut self, config_json: &str) -> Result<String, NeuromorphicError> {
+        let config: CriticalityTuning = parse_json(config_json)?;
+        if config.target_branching_ratio.is_nan() || config.target_branching_ratio <= 0.0
+            || config.steps_per_iteration < 2
+            || config.max_lag == 0
+            || !(0.0..=1.0).contains(&config.drive_probability)
+        {
+            return Err(NeuromorphicError::InvalidConfig("invalid criticality tuning configuration".to_string()));
+        }
+        
+        let mut history = Vec::with_capacity(config.max_iterations);
+        let mut converged = false;
+        let start_time = self.begin_call();
+        for iteration in 0..config.max_iterations {
+            let mut counts = Vec::with_capacity(config.steps_per_iteration);
+            for step in 0..config.steps_per_iteration {
+                self.current_time = self.time_at_step(start_time, iteration * config.steps_per_iteration + step);
+                let mut input_currents = vec![0.0; self.network_size];
+                for current in input_currents.iter_mut() {
+                    if self.draw_random() < config.drive_probability {
+                        *current = config.drive_current;
+                    }
+                }
+                
+                self.update_modulation();
+                let mut spikes = vec![false; self.network_size];
+                counts.push(self.step_network(&mut input_currents, &mut spikes, true) as u32);
+                self.sample_probes();
+            }
+            
+            let branching_ratio = CriticalityReport::analyze(&counts, 1, config.max_lag).branching_ratio();
+            history.push(branching_ratio);
+            if config.converged(branching_ratio) {
+                converged = true;
+                break;
+            }
+            self.recurrent_gain = config.next_gain(self.recurrent_gain, branching_ratio);
+        }
+        
+        console_log!("🌋 Recurrent gain tuned to {:.5} ({})", self.recurrent_gain,
+                     if converged { "converged" } else { "not converged" });
+        let result = CriticalityTuningResult {
+            recurrent_gain: self.recurrent_gain,
+            branching_ratio: history.last().copied().flatten(),
+            converged,
+            history,
+        };
+        self.record(|| RecordedCall::TuneCriticality { config_json: config_json.to_string() }, start_time, &result);
+        to_json(&result)
+    }
+
+    /// Selects how firing rates are estimated for `purpose`: "recurrent"
+    /// (presynaptic drive, default a 10 ms window) or "learning" (Hebbian
+    /// and structural plasticity, default 20 ms). `config_json` is an
+    /// exponential trace, fixed bins or a sliding window; the estimate
+    /// restarts from zero.
+    #[wasm_bindgen]
+    pub fn set_rate_estimator(&mut self, purpose: &str, config_json: &str) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let config: RateEstimatorConfig = parse_json(config_json)?;
+        if !config.is_valid(self.dt_ms) {
+            return Err(NeuromorphicError::InvalidConfig(format!(
+                "rate estimator spans must be positive and at most {} steps", MAX_WINDOW_STEPS
+            )));
+        }
+        *self.rate_estimator_mut(purpose)? = RateEstimator::new(config, self.network_size, self.dt_ms);
+        Ok(())
+    }
+    
+    /// Current rate estimates in Hz for `purpose` ("recurrent" or "learning").
+    #[wasm_bindgen]
+    pub fn get_firing_rates(&self, purpose: &str) -> Result<Vec<f32>, NeuromorphicError> {
+        let estimator = match purpose {
+            "recurrent" => &self.recurrent_rates,
+            "learning" => &self.learning_rates,
+            _ => return Err(NeuromorphicError::NotFound(format!("rate estimator '{}'", purpose))),
+        };
+        Ok(estimator.rates().to_vec())
+    }
+    
+    fn rate_estimator_mut(&mut self, purpose: &str) -> Result<&mut RateEstimator, NeuromorphicError> {
+        match purpose {
+            "recurrent" => Ok(&mut self.recurrent_rates),
+            "learning" => Ok(&mut self.learning_rates),
+            _ => Err(NeuromorphicError::NotFound(format!("rate estimator '{}'", purpose))),
+        }
+    }
+
+    /// Scores every window of `window_steps` steps against a running model
+    /// of typical spike counts and reports windows whose novelty exceeds an
+    /// adaptive threshold (`AnomalyConfig` fields in `config_json`; missing
+    /// fields take their defaults). Applies to every input call; replaces
+    /// any previous model.
+    #[wasm_bindgen]
+    pub fn set_anomaly_detection(&mut self, config_json: &str) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let config: AnomalyConfig = parse_json(config_json)?;
+        if !config.is_valid() {
+            return Err(NeuromorphicError::InvalidConfig("invalid anomaly detection configuration".to_string()));
+        }
+        self.anomaly_detector = Some(NoveltyDetector::new(config, self.network_size));
+        console_log!("🔎 Anomaly detection on: {}-step windows", config.window_steps);
+        Ok(())
+    }
+    
+    #[wasm_bindgen]
+    pub fn disable_anomaly_detection(&mut self) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        self.anomaly_detector = None;
+        self.anomaly_events.clear();
+        Ok(())
+    }
+    
+    /// Relearns typical activity from scratch, e.g. after an intended change
+    /// of regime; the warm-up starts again.
+    #[wasm_bindgen]
+    pub fn reset_anomaly_model(&mut self) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let detector = self.anomaly_detector.as_mut()
+            .ok_or_else(|| NeuromorphicError::NotFound("anomaly detection is off".to_string()))?;
+        detector.reset();
+        Ok(())
+    }
+    
+    /// Novelty scores of the windows completed during the last input call.
+    #[wasm_bindgen]
+    pub fn get_novelty_scores(&self) -> Result<String, NeuromorphicError> {
+        to_json(&self.call_novelty)
+    }
+    
+    /// Anomalous windows since the last take, oldest first (at most the
+    /// last 1000 are kept).
+    #[wasm_bindgen]
+    pub fn take_anomaly_events(&mut self) -> Result<String, NeuromorphicError> {
+        let events: Vec<NoveltyScore> = self.anomaly_events.drain(..).collect();
+        to_json(&events)
+    }
+    
+    /// Neuron updates, synaptic operations and spikes of the last input call
+    /// and since creation (or the last reset), with their energy under the
+    /// current hardware profile.
+    #[wasm_bindgen]
+    pub fn get_operation_counts(&self) -> Result<String, NeuromorphicError> {
+        to_json(&OperationReport {
+            profile: &self.hardware,
+            last_call: self.call_operations,
+            last_call_energy: self.hardware.estimate(&self.call_operations),
+            total: self.total_operations,
+            total_energy: self.hardware.estimate(&self.total_operations),
+        })
+    }
+    
+    #[wasm_bindgen]
+    pub fn reset_operation_counts(&mut self) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        self.call_operations = OperationCounts::default();
+        self.total_operations = OperationCounts::default();
+        Ok(())
+    }
+    
+    /// Selects the energy model: a preset name ("loihi", the default, or
+    /// "cpu_45nm") or a `HardwareProfile` as JSON with energies in pJ.
+    #[wasm_bindgen]
+    pub fn set_hardware_profile(&mut self, profile: &str) -> Result<(), NeuromorphicError> {
+        self.ensure_not_recording()?;
+        let profile = match HardwareProfile::preset(profile) {
+            Some(preset) => preset,
+            None if profile.trim_start().starts_with('{') => parse_json(profile)?,
+            None => return Err(NeuromorphicError::NotFound(format!("hardware profile '{}'", profile))),
+        };
+        if !profile.is_valid() {
+            return Err(NeuromorphicError::InvalidConfig("energies must be finite and non-negative".to_string()));
+        }
+        self.hardware = profile;
+        Ok(())
+    }
+    
+    /// Energy in pJ of generating `tokens` tokens with a transformer of
+    /// `parameters` weights (e.g. 1.1e9 for TinyLlama) at the current
+    /// profile's MAC energy, for comparison with `get_operation_counts`.
+    #[wasm_bindgen]
+    pub fn estimate_transformer_energy_pj(&self, parameters: f64, tokens: f64) -> f64 {
+        self.hardware.transformer_energy_pj(parameters, tokens)
+    }
+
+    /// Describes the current network, including learned weights, in the
+    /// format accepted by `from_model`.
+    #[wasm_bindgen]
+    pub fn export_model(&self) -> Result<String, NeuromorphicError> {
+        serde_json::to_string_pretty(&self.describe_model())
+            .map_err(|e| NeuromorphicError::SerializationError(e.to_string()))
+    }
+    
+    #[wasm_bindgen]
+    pub fn export_model_yaml(&self) -> Result<String, NeuromorphicError> {
+        serde_yaml::to_string(&self.describe_model())
+            .map_err(|e| NeuromorphicError::SerializationError(e.to_string()))
+    }
+    
+    fn describe_model(&self) -> NetworkDescription {
+        NetworkDescription {
+            recurrence: RecurrenceDescription {
+                gain: self.recurrent_gain,
+                rates: self.recurrent_rates.config,
+            },
+            ..NetworkDescription::from_network(
+                &self.layout,
+                &self.neurons,
+                &self.synaptic_weights,
+                &self.gap_junctions,
+                self.dt_ms,
+                self.seed,
+                PlasticityDescription {
+                    learning_rate: self.learning_rate,
+                    structural: self.structural_plasticity.config,
+                },
+            )
+        }
     }
 
     #[wasm_bindgen]
@@ -361,3 +1424,233 @@ pub fn main() {
     console_log!("⚡ REAL Neuromorphic WASM module loaded!");
     console_log!("🧠 Ready for REAL spike processing - NO MORE SIMULATIONS!");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initial_topology_is_sparse_distance_weighted_and_seeded() {
+        let processor = NeuromorphicProcessor::new(40).unwrap();
+        let connected: Vec<(usize, usize)> = (0..40)
+            .flat_map(|i| (0..40).map(move |j| (i, j)))
+            .filter(|&(i, j)| processor.synaptic_weights[i][j] != 0.0)
+            .collect();
+
+        // Expected count is sum over i != j of 0.1 (1 - |i - j| / 40) ≈ 103
+        assert!((70..140).contains(&connected.len()), "{} connections", connected.len());
+        assert!(connected.iter().all(|&(i, j)| i != j));
+        let near = connected.iter().filter(|&&(i, j)| i.abs_diff(j) < 20).count();
+        assert!(near > 2 * (connected.len() - near));
+        assert!(processor.synaptic_weights.iter().flatten().all(|w| w.abs() <= 0.1));
+
+        let again = NeuromorphicProcessor::new(40).unwrap();
+        assert_eq!(again.synaptic_weights, processor.synaptic_weights);
+    }
+
+    #[test]
+    fn structural_plasticity_rejects_invalid_configs() {
+        let mut processor = NeuromorphicProcessor::new(8).unwrap();
+        for config in [r#"{"max_fan_in": 0}"#, r#"{"prune_patience": 0}"#, r#"{"growth_probability": 2.0}"#] {
+            assert!(matches!(processor.set_structural_plasticity(config), Err(NeuromorphicError::InvalidConfig(_))));
+        }
+        processor.set_structural_plasticity(r#"{"enabled": true, "max_fan_in": 4}"#).unwrap();
+        assert_eq!(processor.structural_plasticity.config.max_fan_in, 4);
+    }
+
+    #[test]
+    fn invalid_calls_return_typed_errors_and_leave_state_alone() {
+        assert!(matches!(NeuromorphicProcessor::new(0), Err(NeuromorphicError::InvalidInput(_))));
+
+        let mut processor = NeuromorphicProcessor::new(8).unwrap();
+        let before = processor.current_time;
+        let cases = [
+            processor.process_input(&[]).err(),
+            processor.process_input(&[0.1, f32::NAN]).err(),
+            processor.run_frames(&[0.0; 5], 2, false).err(),
+            processor.add_probe("{not json").err(),
+            processor.set_lif_parameters(r#"{"tau_m_ms": -1.0}"#).err(),
+            processor.get_probe_values(99).err(),
+        ];
+        let codes: Vec<&str> = cases.iter().map(|error| error.as_ref().map_or("ok", |e| e.code())).collect();
+        assert_eq!(codes, ["INVALID_INPUT", "INVALID_INPUT", "INVALID_INPUT", "PARSE_ERROR", "INVALID_CONFIG", "NOT_FOUND"]);
+        assert!(cases.iter().flatten().all(|error| error.category() == "input"));
+        assert_eq!(processor.current_time, before);
+    }
+
+    #[test]
+    fn exported_models_load_back_unchanged() {
+        let model = r#"{
+            "seed": 7,
+            "populations": [{"id": "in", "size": 5}, {"id": "out", "size": 3}],
+            "projections": [{"id": "ff", "pre": "in", "post": "out",
+                             "connector": {"kind": "fixed_probability", "p": 0.6}, "weight": {"low": 0.1, "high": 0.9}}]
+        }"#;
+        let processor = NeuromorphicProcessor::from_model(model).unwrap();
+        let exported = processor.export_model().unwrap();
+        let reloaded = NeuromorphicProcessor::from_model(&exported).unwrap();
+        assert_eq!(reloaded.seed, 7);
+        assert_eq!(reloaded.synaptic_weights, processor.synaptic_weights);
+        assert_eq!(reloaded.export_model().unwrap(), exported);
+        let yaml = NeuromorphicProcessor::from_model(&processor.export_model_yaml().unwrap()).unwrap();
+        assert_eq!(yaml.synaptic_weights, processor.synaptic_weights);
+    }
+
+    #[test]
+    fn oversized_models_are_rejected_before_building() {
+        let model = r#"{"populations": [{"id": "huge", "size": 1000000}]}"#;
+        assert!(matches!(NeuromorphicProcessor::from_model(model), Err(NeuromorphicError::InvalidConfig(_))));
+        let overflow = format!(r#"{{"populations": [{{"id": "a", "size": {}}}, {{"id": "b", "size": 2}}]}}"#, usize::MAX);
+        assert!(matches!(NeuromorphicProcessor::from_model(&overflow), Err(NeuromorphicError::InvalidConfig(_))));
+        let at_limit = format!(r#"{{"populations": [{{"id": "a", "size": {}}}]}}"#, MAX_MODEL_NEURONS);
+        assert_eq!(NeuromorphicProcessor::from_model(&at_limit).unwrap().network_size, MAX_MODEL_NEURONS);
+    }
+
+    #[test]
+    fn dendrites_attach_only_to_the_targeted_population() {
+        let model = r#"{"populations": [{"id": "in", "size": 3}, {"id": "out", "size": 2}]}"#;
+        let mut processor = NeuromorphicProcessor::from_model(model).unwrap();
+        let unknown = r#"{"branches": 2, "target": {"kind": "populations", "ids": ["hidden"]}}"#;
+        assert!(matches!(processor.set_dendrites(unknown), Err(NeuromorphicError::InvalidConfig(_))));
+        assert!(processor.dendrites.is_none());
+
+        processor.set_dendrites(r#"{"branches": 2, "target": {"kind": "populations", "ids": ["out"]}}"#).unwrap();
+        assert_eq!(processor.dendrites.as_ref().unwrap().somas(), [3, 4]);
+        processor.add_stimulus(r#"{"kind": "constant", "amplitude": 20.0, "compartment": 1}"#).unwrap();
+        processor.generate_spikes(5);
+        let potentials = processor.get_dendrite_potentials(1).unwrap();
+        assert_eq!(&potentials[..3], [0.0; 3]);
+        assert!(potentials[3..].iter().all(|&v| v > 0.0));
+        assert_eq!(processor.call_operations.neuron_updates, 5 * (5 + 4));
+    }
+
+    #[test]
+    fn oversized_rate_windows_are_rejected() {
+        let mut processor = NeuromorphicProcessor::new(8).unwrap();
+        for config in [r#"{"kind": "window", "window_ms": 1e12}"#, r#"{"kind": "bins", "bin_ms": 20000}"#] {
+            assert!(matches!(processor.set_rate_estimator("recurrent", config), Err(NeuromorphicError::InvalidConfig(_))));
+        }
+        assert_eq!(processor.recurrent_rates.config, RateEstimatorConfig::Window { window_ms: 10.0 });
+
+        // The 20 ms learning window would need 20 000 steps at 1 µs
+        let tiny_step = r#"{"dt_ms": 0.001}"#;
+        assert!(matches!(processor.set_lif_parameters(tiny_step), Err(NeuromorphicError::InvalidConfig(_))));
+        assert_eq!(processor.dt_ms, 1.0);
+
+        let model = r#"{"populations": [{"id": "a", "size": 2}],
+                        "recurrence": {"rates": {"kind": "window", "window_ms": 1e12}}}"#;
+        assert!(matches!(NeuromorphicProcessor::from_model(model), Err(NeuromorphicError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn new_lif_parameters_start_from_rest() {
+        let mut processor = NeuromorphicProcessor::new(16).unwrap();
+        processor.process_input(&[0.4; 20]).unwrap();
+        processor.set_lif_parameters("{}").unwrap();
+        for i in 0..16 {
+            assert_eq!(processor.neurons.membrane_potential(i), -65.0);
+            assert_eq!(processor.neurons.refractory_remaining(i), 0);
+        }
+        let result = processor.run_input(&[0.0; 10]).unwrap();
+        assert_eq!(result.pattern.activation_strength, 0.0);
+    }
+
+    #[test]
+    fn criticality_tuning_samples_probes_and_escapes_zero_gain() {
+        let mut processor = NeuromorphicProcessor::new(16).unwrap();
+        processor.recurrent_gain = 0.0;
+        let probe = processor.add_probe(r#"{"target": {"kind": "membrane_potential", "neuron": 0}}"#).unwrap();
+        let config = r#"{"max_iterations": 2, "steps_per_iteration": 20, "target_branching_ratio": 100.0}"#;
+        processor.tune_criticality(config).unwrap();
+        assert_eq!(processor.get_probe_values(probe).unwrap().len(), 40);
+        assert!(processor.recurrent_gain > 0.0);
+    }
+
+    #[test]
+    fn operation_counts_follow_the_simulation() {
+        let mut processor = NeuromorphicProcessor::new(16).unwrap();
+        processor.run_frames(&[0.0; 16 * 5], 16, false).unwrap();
+        let silent = processor.call_operations;
+        assert_eq!(silent, OperationCounts { steps: 5, neuron_updates: 80, synaptic_ops: 0, spikes: 0 });
+
+        let activity = processor.run_frames(&[50.0; 16 * 8], 16, false).unwrap();
+        let driven = processor.call_operations;
+        assert_eq!((driven.steps, driven.neuron_updates), (8, 128));
+        assert_eq!(driven.spikes, activity.iter().map(|a| (a * 16.0).round() as u64).sum::<u64>());
+        assert!(driven.spikes > 0);
+
+        let report: serde_json::Value = serde_json::from_str(&processor.get_operation_counts().unwrap()).unwrap();
+        assert_eq!(report["total"]["steps"], 13);
+        let energy = processor.hardware.estimate(&driven).total_pj;
+        assert_eq!(report["last_call_energy"]["total_pj"].as_f64(), Some(energy));
+
+        assert!(matches!(processor.set_hardware_profile("gpu"), Err(NeuromorphicError::NotFound(_))));
+        assert!(matches!(
+            processor.set_hardware_profile(r#"{"name": "x", "pj_per_synaptic_op": -1, "pj_per_neuron_update": 1,
+                                               "pj_per_spike": 1, "pj_per_mac": 1}"#),
+            Err(NeuromorphicError::InvalidConfig(_))
+        ));
+        processor.reset_operation_counts().unwrap();
+        assert_eq!(processor.total_operations, OperationCounts::default());
+    }
+
+    #[test]
+    fn bursts_after_a_quiet_baseline_are_reported_as_anomalies() {
+        let mut processor = NeuromorphicProcessor::new(16).unwrap();
+        assert!(matches!(processor.set_anomaly_detection(r#"{"window_steps": 0}"#), Err(NeuromorphicError::InvalidConfig(_))));
+        processor.set_anomaly_detection(r#"{"window_steps": 10, "warmup_windows": 3}"#).unwrap();
+        processor.run_frames(&[0.0; 16 * 100], 16, false).unwrap();
+        assert_eq!(processor.call_novelty.len(), 7);
+        assert!(processor.call_novelty.iter().all(|score| !score.anomaly));
+
+        processor.run_frames(&[50.0; 16 * 20], 16, false).unwrap();
+        assert!(processor.call_novelty.iter().all(|score| score.anomaly));
+        let events: Vec<NoveltyScore> = serde_json::from_str(&processor.take_anomaly_events().unwrap()).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(processor.take_anomaly_events().unwrap(), "[]");
+    }
+
+    #[test]
+    fn trained_models_keep_their_recurrent_drive() {
+        use neuromorphic_core::encoding::InputFrames;
+        use neuromorphic_core::surrogate::{SurrogateConfig, SurrogateTrainer};
+
+        let description: NetworkDescription = serde_json::from_str(r#"{
+            "seed": 7,
+            "populations": [{"id": "input", "size": 4}, {"id": "output", "size": 2}],
+            "projections": [
+                {"id": "ff", "pre": "input", "post": "output", "connector": {"kind": "all_to_all"},
+                 "weight": {"low": 0.0, "high": 0.6}},
+                {"id": "rec", "pre": "output", "post": "output", "connector": {"kind": "all_to_all"}, "weight": 0.4}
+            ]
+        }"#).unwrap();
+        let config = SurrogateConfig { recurrent_gain: 0.05, rate_window_ms: 5.0, batch_size: 1, ..SurrogateConfig::default() };
+        let mut trainer = SurrogateTrainer::new(&description, config).unwrap();
+        let frames = InputFrames {
+            channels: 6,
+            data: (0..6 * 40).map(|i| if i % 6 < 4 { 2.0 + (i % 5) as f32 } else { 0.0 }).collect(),
+        };
+        for label in [0, 1, 0] {
+            trainer.train_sample(&frames, label).unwrap();
+        }
+
+        let exported = serde_json::to_string(&trainer.export()).unwrap();
+        let mut processor = NeuromorphicProcessor::from_model(&exported).unwrap();
+        assert_eq!(processor.recurrent_gain, 0.05);
+        assert_eq!(processor.recurrent_rates.config, RateEstimatorConfig::Window { window_ms: 5.0 });
+
+        processor.set_raster_capture(true);
+        processor.run_frames(&frames.data, 6, false).unwrap();
+        let counts: Vec<u32> = (4..6)
+            .map(|neuron| processor.io.raster.chunks(6).map(|row| row[neuron] as u32).sum())
+            .collect();
+        let expected = trainer.predict(&frames);
+        assert!(expected.iter().sum::<u32>() > 0);
+        assert_eq!(counts, expected);
+
+        // The processor's own export carries the drive through another load
+        let reloaded = NeuromorphicProcessor::from_model(&processor.export_model().unwrap()).unwrap();
+        assert_eq!(reloaded.recurrent_gain, 0.05);
+        assert_eq!(reloaded.recurrent_rates.config, processor.recurrent_rates.config);
+    }
+}
@@ -0,0 +1,372 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use neuromorphic_core::energy::OperationCounts;
+
+use crate::error::{parse_json, to_json, NeuromorphicError};
+use crate::{log, NeuromorphicProcessor};
+
+/// Recording format version, bumped whenever the snapshot layout changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// Output fields that depend on wall-clock speed rather than on state.
+const IGNORED_KEYS: &[&str] = &["processing_time_ms"];
+
+/// At most this many differences are listed per report.
+const MAX_DIFFERENCES: usize = 20;
+
+/// An input call that advances the simulation, with its arguments.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum RecordedCall {
+    GenerateSpikes { pattern_length: usize },
+    ProcessInput { input_data: Vec<f32> },
+    RunFrames { frames: Vec<f32>, channels: usize, learn: bool },
+    TuneCriticality { config_json: String },
+    Fit { series: Vec<f32>, config_json: String },
+    Observe { values: Vec<f32> },
+    Evaluate { series: Vec<f32> },
+}
+
+impl RecordedCall {
+    fn method(&self) -> &'static str {
+        match self {
+            RecordedCall::GenerateSpikes { .. } => "generate_spikes",
+            RecordedCall::ProcessInput { .. } => "process_input",
+            RecordedCall::RunFrames { .. } => "run_frames",
+            RecordedCall::TuneCriticality { .. } => "tune_criticality",
+            RecordedCall::Fit { .. } => "fit",
+            RecordedCall::Observe { .. } => "observe",
+            RecordedCall::Evaluate { .. } => "evaluate",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecordedStep {
+    #[serde(flatten)]
+    pub call: RecordedCall,
+    /// Simulation clock at the start of the call.
+    pub start_time: u64,
+    pub output: Value,
+}
+
+/// Everything needed to reproduce a session: the full processor state when
+/// recording started (network, neuron state, RNG seeds, stimuli, probes,
+/// modulators and other configuration) and every input call since. The
+/// configuration cannot change while recording (see `start_recording`), so
+/// the snapshot and the calls describe the whole session.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Recording {
+    pub format_version: u32,
+    pub snapshot: Value,
+    pub calls: Vec<RecordedStep>,
+    /// Processor state when recording stopped.
+    #[serde(default)]
+    pub final_state: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Difference {
+    /// JSON path of the differing value, e.g. `$.spikes[12]`.
+    pub path: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Divergence {
+    /// Index of the first call whose output differed, or `None` if only
+    /// the final state did.
+    pub call_index: Option<usize>,
+    pub method: Option<String>,
+    pub difference_count: usize,
+    pub differences: Vec<Difference>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReplayReport {
+    pub calls: usize,
+    pub calls_replayed: usize,
+    pub identical: bool,
+    pub divergence: Option<Divergence>,
+}
+
+/// Restores the snapshot and re-issues every recorded call with its
+/// original clock, stopping at the first call whose output differs; then
+/// compares the final state if one was recorded.
+pub fn replay(recording: &Recording) -> Result<ReplayReport, NeuromorphicError> {
+    if recording.format_version != FORMAT_VERSION {
+        return Err(NeuromorphicError::InvalidInput(format!(
+            "recording format {} is not supported (expected {})", recording.format_version, FORMAT_VERSION
+        )));
+    }
+    let mut processor: NeuromorphicProcessor = serde_json::from_value(recording.snapshot.clone())
+        .map_err(|e| NeuromorphicError::ParseError(format!("invalid snapshot: {}", e)))?;
+
+    let mut report = ReplayReport {
+        calls: recording.calls.len(),
+        calls_replayed: 0,
+        identical: true,
+        divergence: None,
+    };
+    for (index, step) in recording.calls.iter().enumerate() {
+        processor.replay_start = Some(step.start_time);
+        let actual = processor.invoke(&step.call).unwrap_or_else(|e| Value::String(format!("error: {}", e)));
+        report.calls_replayed += 1;
+
+        let divergence = compare(&step.output, &actual);
+        if divergence.difference_count > 0 {
+            report.identical = false;
+            report.divergence = Some(Divergence {
+                call_index: Some(index),
+                method: Some(step.call.method().to_string()),
+                ..divergence
+            });
+            return Ok(report);
+        }
+    }
+
+    if let Some(expected) = &recording.final_state {
+        let divergence = compare(expected, &processor.snapshot()?);
+        if divergence.difference_count > 0 {
+            report.identical = false;
+            report.divergence = Some(divergence);
+        }
+    }
+    Ok(report)
+}
+
+fn compare(expected: &Value, actual: &Value) -> Divergence {
+    let mut divergence = Divergence { call_index: None, method: None, difference_count: 0, differences: Vec::new() };
+    diff("$".to_string(), expected, actual, &mut divergence);
+    divergence
+}
+
+fn diff(path: String, expected: &Value, actual: &Value, divergence: &mut Divergence) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                if !IGNORED_KEYS.contains(&key.as_str()) {
+                    diff(format!("{}.{}", path, key), value, actual.get(key).unwrap_or(&Value::Null), divergence);
+                }
+            }
+            for (key, value) in actual {
+                if !expected.contains_key(key) {
+                    diff(format!("{}.{}", path, key), &Value::Null, value, divergence);
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                diff(format!("{}[{}]", path, index), expected, actual, divergence);
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            push_difference(divergence, format!("{}.length", path), expected.len().into(), actual.len().into());
+        }
+        _ if expected != actual => {
+            push_difference(divergence, path, expected.clone(), actual.clone());
+        }
+        _ => {}
+    }
+}
+
+fn push_difference(divergence: &mut Divergence, path: String, expected: Value, actual: Value) {
+    divergence.difference_count += 1;
+    if divergence.differences.len() < MAX_DIFFERENCES {
+        divergence.differences.push(Difference { path, expected, actual });
+    }
+}
+
+/// Replays a recording from `stop_recording` and returns the
+/// `ReplayReport` as JSON.
+#[wasm_bindgen]
+pub fn replay_recording(recording_json: &str) -> Result<String, NeuromorphicError> {
+    let recording: Recording = parse_json(recording_json)?;
+    to_json(&replay(&recording)?)
+}
+
+#[wasm_bindgen]
+impl NeuromorphicProcessor {
+    /// Starts recording: snapshots the processor and logs every subsequent
+    /// `generate_spikes`, `process_input`, `run_frames` and
+    /// `tune_criticality` call with its output. Restarts any recording in
+    /// progress. Until the recording stops, calls that change the
+    /// configuration (`set_*`, `add_*`, `remove_*`, `clear_*`, `reset_*`,
+    /// `define_modulator`, `bind_modulator`, `unbind_modulator` and
+    /// `disable_anomaly_detection`) fail with `InvalidInput`, and the network
+    /// cannot take part in a `NetworkCoordinator` run.
+    #[wasm_bindgen]
+    pub fn start_recording(&mut self) -> Result<(), NeuromorphicError> {
+        let snapshot = self.snapshot()?;
+        self.recording = Some(Recording {
+            format_version: FORMAT_VERSION,
+            snapshot,
+            calls: Vec::new(),
+            final_state: None,
+        });
+        console_log!("⏺️ Recording started");
+        Ok(())
+    }
+
+    /// Stops recording and returns the `Recording` as JSON, for
+    /// `replay_recording` or the native `replay` tool.
+    #[wasm_bindgen]
+    pub fn stop_recording(&mut self) -> Result<String, NeuromorphicError> {
+        let mut recording = self.recording.take()
+            .ok_or_else(|| NeuromorphicError::NotFound("recording in progress".to_string()))?;
+        recording.final_state = Some(self.snapshot()?);
+        console_log!("⏹️ Recording stopped after {} calls", recording.calls.len());
+        to_json(&recording)
+    }
+
+    #[wasm_bindgen]
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+}
+
+impl NeuromorphicProcessor {
+    fn snapshot(&self) -> Result<Value, NeuromorphicError> {
+        serde_json::to_value(self).map_err(|e| NeuromorphicError::SerializationError(e.to_string()))
+    }
+
+    /// Fails while recording, for calls that change the configuration.
+    pub(crate) fn ensure_not_recording(&self) -> Result<(), NeuromorphicError> {
+        if self.recording.is_some() {
+            return Err(NeuromorphicError::InvalidInput(
+                "configuration cannot change while recording; stop the recording first".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Logs an input call if recording; `call` is only built when needed.
+    pub(crate) fn record<T: Serialize>(&mut self, call: impl FnOnce() -> RecordedCall, start_time: u64, output: &T) {
+        if let Some(recording) = &mut self.recording {
+            recording.calls.push(RecordedStep {
+                call: call(),
+                start_time,
+                output: output_value(output).unwrap_or(Value::Null),
+            });
+        }
+    }
+
+    /// Starts an input call: resets the per-call operation counts and
+    /// returns the start time, the injected one during replay or else the
+    /// wall clock.
+    pub(crate) fn begin_call(&mut self) -> u64 {
+        self.call_operations = OperationCounts::default();
+        self.call_novelty.clear();
+        self.io.raster.clear();
+        self.replay_start.take().unwrap_or_else(crate::now_ms)
+    }
+
+    fn invoke(&mut self, call: &RecordedCall) -> Result<Value, NeuromorphicError> {
+        let output = match call {
+            RecordedCall::GenerateSpikes { pattern_length } => output_value(&self.generate_spikes(*pattern_length)),
+            RecordedCall::ProcessInput { input_data } => serde_json::from_str(&self.process_input(input_data)?),
+            RecordedCall::RunFrames { frames, channels, learn } => output_value(&self.run_frames(frames, *channels, *learn)?),
+            RecordedCall::TuneCriticality { config_json } => serde_json::from_str(&self.tune_criticality(config_json)?),
+            RecordedCall::Fit { series, config_json } => serde_json::from_str(&self.fit(series, config_json)?),
+            RecordedCall::Observe { values } => output_value(&self.observe(values)?),
+            RecordedCall::Evaluate { series } => serde_json::from_str(&self.evaluate(series)?),
+        };
+        output.map_err(|e| NeuromorphicError::SerializationError(e.to_string()))
+    }
+}
+
+/// Goes through JSON text, as the JS-facing methods do, so f32 values are
+/// compared in the same shortest representation on record and replay.
+fn output_value<T: Serialize>(output: &T) -> serde_json::Result<Value> {
+    serde_json::from_str(&serde_json::to_string(output)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_session() -> Recording {
+        let mut processor = NeuromorphicProcessor::new(24).unwrap();
+        processor.set_structural_plasticity(r#"{"enabled": true, "growth_probability": 0.2}"#).unwrap();
+        processor.start_recording().unwrap();
+
+        let input: Vec<f32> = (0..24).map(|i| (i % 3) as f32).collect();
+        processor.process_input(&input).unwrap();
+        processor.generate_spikes(12);
+        let frames: Vec<f32> = (0..24 * 10).map(|i| ((i * 7) % 5) as f32).collect();
+        processor.run_frames(&frames, 24, true).unwrap();
+        processor.tune_criticality(r#"{"max_iterations": 2, "steps_per_iteration": 30}"#).unwrap();
+        let series: Vec<f32> = (0..120).map(|t| (t as f32 * 0.3).sin()).collect();
+        processor.fit(&series, r#"{"washout": 10, "horizon": 2}"#).unwrap();
+        processor.observe(&series[..5]).unwrap();
+        processor.evaluate(&series[..20]).unwrap();
+
+        serde_json::from_str(&processor.stop_recording().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn replay_is_bit_exact() {
+        let recording = record_session();
+        assert_eq!(recording.calls.len(), 7);
+        let report = replay(&recording).unwrap();
+        assert!(report.identical, "{:?}", report.divergence);
+        assert_eq!(report.calls_replayed, 7);
+
+        // Through JSON text, as the replay tool reads it
+        let json = serde_json::to_string(&recording).unwrap();
+        assert!(serde_json::from_str::<ReplayReport>(&replay_recording(&json).unwrap()).unwrap().identical);
+    }
+
+    #[test]
+    fn tampered_outputs_are_reported_at_the_first_divergent_call() {
+        let mut recording = record_session();
+        recording.calls[2].output[0] = Value::from(2.5);
+        let report = replay(&recording).unwrap();
+        assert!(!report.identical);
+        assert_eq!(report.calls_replayed, 3);
+        let divergence = report.divergence.unwrap();
+        assert_eq!((divergence.call_index, divergence.method.as_deref()), (Some(2), Some("run_frames")));
+        assert_eq!(divergence.differences[0].path, "$[0]");
+
+        let mut recording = record_session();
+        recording.final_state.as_mut().unwrap()["recurrent_gain"] = Value::from(7.0);
+        let divergence = replay(&recording).unwrap().divergence.unwrap();
+        assert_eq!(divergence.call_index, None);
+        assert_eq!(divergence.differences[0].path, "$.recurrent_gain");
+    }
+
+    #[test]
+    fn configuration_changes_are_rejected_while_recording() {
+        let mut processor = NeuromorphicProcessor::new(8).unwrap();
+        let stimulus = processor.add_stimulus(r#"{"kind": "constant", "amplitude": 0.5}"#).unwrap();
+        processor.start_recording().unwrap();
+
+        let rejected = [
+            processor.set_lif_parameters("{}").err(),
+            processor.set_recurrent_gain(0.5).err(),
+            processor.set_fixed_point_mode(true).err(),
+            processor.add_stimulus(r#"{"kind": "constant", "amplitude": 1.0}"#).err(),
+            processor.remove_stimulus(stimulus).err(),
+            processor.reset_state().err(),
+        ];
+        assert!(rejected.iter().all(|e| matches!(e, Some(NeuromorphicError::InvalidInput(_)))), "{:?}", rejected);
+        assert_eq!(processor.recurrent_gain, 0.01);
+        assert!(!processor.is_fixed_point_mode());
+
+        processor.generate_spikes(10);
+        let recording: Recording = serde_json::from_str(&processor.stop_recording().unwrap()).unwrap();
+        assert!(replay(&recording).unwrap().identical);
+        processor.set_recurrent_gain(0.5).unwrap();
+        assert!(processor.remove_stimulus(stimulus).unwrap());
+    }
+
+    #[test]
+    fn unsupported_formats_are_rejected() {
+        let mut recording = record_session();
+        recording.format_version = FORMAT_VERSION + 1;
+        assert!(matches!(replay(&recording), Err(NeuromorphicError::InvalidInput(_))));
+    }
+}